@@ -0,0 +1,85 @@
+//! Demonstrates a backend implemented outside of `safe_bindgen`, generating a GNU linker version
+//! script which exports the `#[no_mangle]` functions of the crate and hides everything else.
+//!
+//! The items are given in the AST of `syntex_syntax`, which backends depend on directly.
+
+use safe_bindgen::{
+    append_output, check_no_mangle, parse_attr, primitive, Bindgen, Error, Lang, Outputs, Primitive,
+};
+use syntex_syntax::ast;
+
+const RUST: &str = r#"
+/// Create a new counter.
+#[no_mangle]
+pub extern "C" fn counter_new(start: u64) -> *mut Counter {}
+
+#[no_mangle]
+pub extern "C" fn counter_increment(counter: *mut Counter) -> u64 {}
+
+#[no_mangle]
+pub extern "C" fn counter_free(counter: *mut Counter) {}
+
+pub mod internal {
+    pub fn not_exported() {}
+}
+"#;
+
+/// Collects the symbols exported by the crate.
+#[derive(Default)]
+struct LangVersionScript {
+    symbols: Vec<String>,
+}
+
+impl Lang for LangVersionScript {
+    fn parse_fn(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (no_mangle, _) = parse_attr(&item.attrs, check_no_mangle, |_| None);
+        if !no_mangle {
+            return Ok(());
+        }
+
+        // Shared helpers describe the Rust types independently of the target language.
+        let mut comment = String::new();
+        if let ast::ItemKind::Fn(ref fn_decl, ..) = item.node {
+            if let ast::FunctionRetTy::Ty(ref ty) = fn_decl.output {
+                if let Some(Primitive::Int { bits, signed }) = primitive(ty) {
+                    let width =
+                        bits.map_or("pointer-sized".to_string(), |bits| format!("{}-bit", bits));
+                    let sign = if signed { "signed" } else { "unsigned" };
+                    comment = format!(" # returns a {} {} integer", width, sign);
+                }
+            }
+        }
+
+        self.symbols
+            .push(format!("{};{}", item.ident.name.as_str(), comment));
+        Ok(())
+    }
+
+    fn finalise_output(&mut self, outputs: &mut Outputs) -> Result<(), Error> {
+        let mut script = String::from("{\n  global:\n");
+        for symbol in &self.symbols {
+            script.push_str(&format!("    {}\n", symbol));
+        }
+        script.push_str("  local:\n    *;\n};\n");
+
+        append_output(script, "exports.map", outputs);
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut bindgen = Bindgen::new().expect("could not create the bindgen context");
+    bindgen.source_code("src/lib.rs", RUST);
+
+    // Backends are used through trait objects, so they can be picked at runtime.
+    let lang: &mut dyn Lang = &mut LangVersionScript::default();
+    let mut outputs = Outputs::new();
+    bindgen.compile_or_panic(lang, &mut outputs, true);
+
+    println!("{}", outputs["exports.map"]);
+}
@@ -6,7 +6,7 @@ extern crate safe_bindgen;
 extern crate unwrap;
 
 use jni::signature::{JavaType, Primitive};
-use safe_bindgen::{Bindgen, LangC, LangCSharp, LangJava};
+use safe_bindgen::{Bindgen, LangC, LangCSharp, LangD, LangJava, LangKotlin, LangObjC, LangSwift};
 use std::collections::HashMap;
 
 fn main() {
@@ -29,7 +29,7 @@ fn main() {
                 .takes_value(true)
                 .required(true)
                 .help("target language")
-                .possible_values(&["csharp", "java", "c"]),
+                .possible_values(&["csharp", "java", "c", "d", "kotlin", "objc", "swift"]),
         )
         .arg(
             clap::Arg::with_name("LIB")
@@ -63,6 +63,24 @@ fn main() {
         "c" => {
             let mut lang = LangC::new();
             lang.set_lib_name(lib);
+            bindgen.run_build(&mut lang, &output_dir);
+            for warning in lang.warnings() {
+                bindgen.print_error(warning);
+            }
+        }
+        "d" => {
+            let mut lang = LangD::new();
+            lang.set_lib_name(lib);
+            bindgen.run_build(&mut lang, &output_dir)
+        }
+        "kotlin" => {
+            let mut lang = LangKotlin::new();
+            lang.set_lib_name(lib);
+            bindgen.run_build(&mut lang, &output_dir)
+        }
+        "objc" => {
+            let mut lang = LangObjC::new();
+            lang.set_lib_name(lib);
             bindgen.run_build(&mut lang, &output_dir)
         }
         "csharp" => {
@@ -70,6 +88,11 @@ fn main() {
             lang.set_lib_name(lib);
             bindgen.run_build(&mut lang, &output_dir)
         }
+        "swift" => {
+            let mut lang = LangSwift::new();
+            lang.set_lib_name(lib);
+            bindgen.run_build(&mut lang, &output_dir)
+        }
         "java" => {
             let mut type_map = HashMap::new();
             type_map.insert(
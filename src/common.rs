@@ -1,10 +1,14 @@
 //! Functions common for all target languages.
+//!
+//! The ones backends implemented in other crates need are re-exported from the crate root.
 
 use crate::syntax::abi::Abi;
 use crate::syntax::ast;
+use crate::syntax::codemap::Span;
 use crate::syntax::print::pprust;
-use crate::Error;
+use crate::{Error, ErrorCode, Level};
 use std::collections::hash_map::{Entry, HashMap};
+use std::convert::TryFrom;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FilterMode {
@@ -12,10 +16,39 @@ pub enum FilterMode {
     Whitelist,
 }
 
+/// Line ending used in the generated files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix-style line endings (`\n`).
+    Lf,
+    /// Windows-style line endings (`\r\n`).
+    CrLf,
+}
+
+impl LineEnding {
+    /// Normalise all line endings in `text` to `self`.
+    pub fn apply(self, text: &str) -> String {
+        let text = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => text,
+            LineEnding::CrLf => text.replace('\n', "\r\n"),
+        }
+    }
+}
+
 /// Outputs several files as a result of an AST transformation.
 pub type Outputs = HashMap<String, String>;
 
 /// Target language support
+///
+/// Every `parse_*` method receives the item, the path of the module it was found in (used by
+/// backends which generate one output per module) and the outputs to append generated code to.
+/// All of them default to ignoring the item, so a backend only implements what it supports.
+/// Once all the items are parsed, `finalise_output` is called exactly once.
+///
+/// This is also the extension point for backends outside of this crate: the trait is object safe,
+/// so any implementation can be passed to `Bindgen::compile` or `walk_crate`, as a
+/// `&mut dyn Lang` if the backend is only chosen at runtime (see `examples/custom_backend.rs`).
 pub trait Lang {
     /// Convert a Rust constant (`pub const NAME: Type = value;`) into a target
     /// language constant.
@@ -58,6 +91,17 @@ pub trait Lang {
         Ok(())
     }
 
+    /// Convert a Rust static (`pub static NAME: Type = value;`) into a target language
+    /// declaration of the global variable.
+    fn parse_static(
+        &mut self,
+        _item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Convert a Rust function declaration into a target language function declaration.
     fn parse_fn(
         &mut self,
@@ -68,6 +112,18 @@ pub trait Lang {
         Ok(())
     }
 
+    /// Convert a function declared in an `extern "ABI" { ... }` block into a target language
+    /// function declaration.
+    fn parse_foreign_fn(
+        &mut self,
+        _item: &ast::ForeignItem,
+        _abi: Abi,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Add extra and custom code after the code generation part is done.
     fn finalise_output(&mut self, _outputs: &mut Outputs) -> Result<(), Error> {
         Ok(())
@@ -89,16 +145,62 @@ pub fn check_no_mangle(attr: &ast::Attribute) -> bool {
     attr.is_word() && attr.check_name("no_mangle")
 }
 
-/// Check the function argument is `user_data: *mut c_void`
-pub fn is_user_data_arg(arg: &ast::Arg) -> bool {
-    pprust::pat_to_string(&*arg.pat) == "user_data"
-        && pprust::ty_to_string(&*arg.ty) == "*mut c_void"
+/// Check the attribute is `#[ffi(...)]` containing the given word (e.g. `#[ffi(export)]`).
+pub fn check_ffi(attr: &ast::Attribute, word: &str) -> bool {
+    attr.check_name("ffi")
+        && attr
+            .meta_item_list()
+            .map(|items| items.iter().any(|item| item.check_name(word)))
+            .unwrap_or(false)
+}
+
+/// Names and types of the arguments of asynchronous functions and their callbacks which are
+/// handled specially: the context passed back to the callbacks and the result of the operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackConvention {
+    /// Name and type of the context argument, `user_data: *mut c_void` by default.
+    pub user_data: (String, String),
+    /// Name and type of the result argument, `result: *const FfiResult` by default.
+    pub result: (String, String),
+}
+
+impl Default for CallbackConvention {
+    fn default() -> Self {
+        CallbackConvention {
+            user_data: ("user_data".to_string(), "*mut c_void".to_string()),
+            result: ("result".to_string(), "*const FfiResult".to_string()),
+        }
+    }
+}
+
+impl CallbackConvention {
+    /// Check the function argument is the context argument (e.g. `user_data: *mut c_void`).
+    pub fn is_user_data_arg(&self, arg: &ast::Arg) -> bool {
+        is_arg(arg, &self.user_data)
+    }
+
+    /// Check the function argument is the result argument (e.g. `result: *const FfiResult`).
+    pub fn is_result_arg(&self, arg: &ast::Arg) -> bool {
+        is_arg(arg, &self.result)
+    }
+
+    /// Detect array ptrs and skip the length args - e.g. for a case of
+    /// `ptr: *const u8, ptr_len: usize` we're going to skip the `len` part.
+    pub fn is_array_arg(&self, arg: &ast::Arg, next_arg: Option<&ast::Arg>) -> bool {
+        if let ast::TyKind::Ptr(..) = arg.ty.node {
+            !self.is_result_arg(arg)
+                && next_arg
+                    .map(|arg| is_ptr_len_arg(&*arg.ty, &pprust::pat_to_string(&*arg.pat)))
+                    .unwrap_or(false)
+        } else {
+            false
+        }
+    }
 }
 
-/// Check the function argument is `result: *const FfiResult`
-pub fn is_result_arg(arg: &ast::Arg) -> bool {
-    pprust::pat_to_string(&*arg.pat) == "result"
-        && pprust::ty_to_string(&*arg.ty) == "*const FfiResult"
+/// Check the function argument has the given name and type.
+fn is_arg(arg: &ast::Arg, (name, ty): &(String, String)) -> bool {
+    pprust::pat_to_string(&*arg.pat) == *name && pprust::ty_to_string(&*arg.ty) == *ty
 }
 
 /// Check the function argument is a length argument for a *const u8 pointer
@@ -107,19 +209,6 @@ pub fn is_ptr_len_arg(ty: &ast::Ty, arg_name: &str) -> bool {
         && (arg_name.ends_with("_len") || arg_name == "len" || arg_name == "size")
 }
 
-/// Detect array ptrs and skip the length args - e.g. for a case of
-/// `ptr: *const u8, ptr_len: usize` we're going to skip the `len` part.
-pub fn is_array_arg(arg: &ast::Arg, next_arg: Option<&ast::Arg>) -> bool {
-    if let ast::TyKind::Ptr(..) = arg.ty.node {
-        !is_result_arg(arg)
-            && next_arg
-                .map(|arg| is_ptr_len_arg(&*arg.ty, &pprust::pat_to_string(&*arg.pat)))
-                .unwrap_or(false)
-    } else {
-        false
-    }
-}
-
 // TODO: Maybe it would be wise to use syntax::attr here.
 /// Loop through a list of attributes.
 ///
@@ -147,22 +236,159 @@ where
 }
 
 /// Check the attribute is #[repr(C)].
+///
+/// `C` may be combined with other modifiers in any order, e.g. `#[repr(packed, C)]`.
 pub fn check_repr_c(attr: &ast::Attribute) -> bool {
-    match unwrap!(attr.meta()).node {
-        ast::MetaItemKind::List(ref word) if attr.check_name("repr") => {
-            match word.first() {
-                Some(word) => {
-                    match word.node {
-                        // Return true only if attribute is #[repr(C)].
-                        ast::NestedMetaItemKind::MetaItem(ref item) if item.name == "C" => true,
-                        _ => false,
-                    }
-                }
-                _ => false,
+    repr_items(attr).iter().any(|item| item.check_name("C"))
+}
+
+/// Layout modifiers given by the `#[repr(...)]` attributes of an item.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Repr {
+    /// `C`
+    pub c: bool,
+    /// `transparent`
+    pub transparent: bool,
+    /// `packed` or `packed(N)`, with the maximum alignment of the fields (1 for `packed`).
+    pub packed: Option<u64>,
+    /// `align(N)`
+    pub align: Option<u64>,
+    /// Primitive integer representation, e.g. `i32`.
+    pub int: Option<&'static str>,
+}
+
+/// Collect the modifiers of all `#[repr(...)]` attributes.
+pub fn parse_repr(attrs: &[ast::Attribute]) -> Repr {
+    let mut repr = Repr::default();
+
+    for attr in attrs {
+        for item in repr_items(attr) {
+            if item.check_name("C") {
+                repr.c = true;
+            } else if item.check_name("transparent") {
+                repr.transparent = true;
+            } else if item.check_name("packed") {
+                repr.packed = Some(repr_int_arg(&item).unwrap_or(1));
+            } else if item.check_name("align") {
+                repr.align = repr_int_arg(&item);
+            } else if let Some(int) = REPR_INTS.iter().find(|int| item.check_name(int)) {
+                repr.int = Some(int);
             }
         }
-        _ => false,
     }
+
+    repr
+}
+
+/// Primitive integer types allowed as the representation of an enum.
+const REPR_INTS: &[&str] = &[
+    "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize",
+];
+
+/// Returns the items of a `#[repr(...)]` attribute, or nothing for other attributes.
+fn repr_items(attr: &ast::Attribute) -> Vec<ast::NestedMetaItem> {
+    if attr.check_name("repr") {
+        attr.meta_item_list().unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Returns `N` from a repr modifier of the form `name(N)`.
+fn repr_int_arg(item: &ast::NestedMetaItem) -> Option<u64> {
+    let args = item.meta_item()?.meta_item_list()?;
+
+    match args.first()?.literal()?.node {
+        ast::LitKind::Int(value, _) => Some(value.low64()),
+        _ => None,
+    }
+}
+
+/// If the attribute is `#[ffi(...)]` containing `name = "value"`, return the value.
+pub fn retrieve_ffi_value(attr: &ast::Attribute, name: &str) -> Option<String> {
+    if !attr.check_name("ffi") {
+        return None;
+    }
+
+    attr.meta_item_list().and_then(|items| {
+        items
+            .iter()
+            .find(|item| item.check_name(name))
+            .and_then(|item| item.value_str())
+            .map(|value| value.as_str().to_string())
+    })
+}
+
+/// If the attribute is `#[ffi(...)]` containing `name(key = "value", ...)`, return the key/value
+/// pairs.
+pub fn retrieve_ffi_pairs(attr: &ast::Attribute, name: &str) -> Vec<(String, String)> {
+    if !attr.check_name("ffi") {
+        return Vec::new();
+    }
+
+    attr.meta_item_list()
+        .into_iter()
+        .flatten()
+        .filter(|item| item.check_name(name))
+        .filter_map(|item| item.meta_item_list().map(|items| items.to_vec()))
+        .flatten()
+        .filter_map(|item| {
+            let key = item.name()?.as_str().to_string();
+            let value = item.value_str()?.as_str().to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// If the attribute is `#[ffi(...)]` containing `name(key = N, ...)`, return the key/value pairs.
+pub fn retrieve_ffi_int_pairs(attr: &ast::Attribute, name: &str) -> Vec<(String, u64)> {
+    if !attr.check_name("ffi") {
+        return Vec::new();
+    }
+
+    attr.meta_item_list()
+        .into_iter()
+        .flatten()
+        .filter(|item| item.check_name(name))
+        .filter_map(|item| item.meta_item_list().map(|items| items.to_vec()))
+        .flatten()
+        .filter_map(|item| {
+            let key = item.name()?.as_str().to_string();
+            match item.meta_item()?.node {
+                ast::MetaItemKind::NameValue(ref lit) => match lit.node {
+                    ast::LitKind::Int(value, _) => Some((key, value.low64())),
+                    _ => None,
+                },
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// If the attribute is `#[ffi(...)]` containing `name = N`, return the integer value.
+pub fn retrieve_ffi_int(attr: &ast::Attribute, name: &str) -> Option<u64> {
+    if !attr.check_name("ffi") {
+        return None;
+    }
+
+    let items = attr.meta_item_list()?;
+    let item = items.iter().find(|item| item.check_name(name))?;
+    match item.meta_item()?.node {
+        ast::MetaItemKind::NameValue(ref lit) => match lit.node {
+            ast::LitKind::Int(value, _) => Some(value.low64()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Check the attribute is `#[derive(...)]` containing the given trait.
+pub fn check_derive(attr: &ast::Attribute, name: &str) -> bool {
+    attr.check_name("derive")
+        && attr
+            .meta_item_list()
+            .map(|items| items.iter().any(|item| item.check_name(name)))
+            .unwrap_or(false)
 }
 
 /// If the attribute is  a docstring, indent it the required amount and return it.
@@ -179,6 +405,239 @@ pub fn retrieve_docstring(attr: &ast::Attribute, prepend: &str) -> Option<String
     }
 }
 
+/// If the attribute is `#[link_name = "..."]`, return the symbol name of the foreign item.
+pub fn retrieve_link_name(attr: &ast::Attribute) -> Option<String> {
+    if attr.check_name("link_name") {
+        attr.value_str().map(|name| name.as_str().to_string())
+    } else {
+        None
+    }
+}
+
+/// If the attribute is `#[export_name = "..."]`, return the symbol name of the defined item.
+pub fn retrieve_export_name(attr: &ast::Attribute) -> Option<String> {
+    if attr.check_name("export_name") {
+        attr.value_str().map(|name| name.as_str().to_string())
+    } else {
+        None
+    }
+}
+
+/// If the attribute is a docstring, return it as a doc comment of the languages sharing the Rust
+/// syntax (D, Kotlin, Swift): sugared docs are kept as they are and the others become `///`
+/// comments.
+pub fn retrieve_rust_style_docstring(attr: &ast::Attribute) -> Option<String> {
+    retrieve_docstring(attr, "").map(|docs| {
+        if docs.starts_with('/') {
+            docs
+        } else {
+            format!("///{}", docs)
+        }
+    })
+}
+
+/// Indent each line of a member declaration (e.g. a field) by four spaces.
+pub fn indent(code: &str) -> String {
+    code.lines().map(|line| format!("    {}\n", line)).collect()
+}
+
+/// Symbol of an exported item: its name, unless it's renamed with `#[export_name = "..."]`.
+pub fn symbol_name(item: &ast::Item) -> String {
+    let (_, export_name) = parse_attr(&item.attrs, |_| false, retrieve_export_name);
+    if export_name.is_empty() {
+        item.ident.name.as_str().to_string()
+    } else {
+        export_name
+    }
+}
+
+/// Error for a `#[repr(C)]` enum with generic parameters.
+pub fn generic_enum_error(span: Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::GenericEnum),
+        span: Some(span),
+        message: "bindgen can not handle parameterized `#[repr(C)]` enums".into(),
+    }
+}
+
+/// Error for a variant of a `#[repr(C)]` enum which has fields.
+pub fn data_enum_error(span: Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::DataEnum),
+        span: Some(span),
+        message: "bindgen can not handle `#[repr(C)]` enums with non-unit variants".into(),
+    }
+}
+
+/// Error for a `#[repr(C)]` struct with generic parameters.
+pub fn generic_struct_error(span: Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::GenericStruct),
+        span: Some(span),
+        message: "bindgen can not handle parameterized `#[repr(C)]` structs".into(),
+    }
+}
+
+/// Error for a unit struct or a tuple struct with several fields.
+pub fn tuple_struct_error(span: Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::TupleStruct),
+        span: Some(span),
+        message: "can not handle unit or tuple `#[repr(C)]` structs with >1 members".into(),
+    }
+}
+
+/// Error for an exported function with generic parameters.
+pub fn generic_fn_error(span: Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::GenericFn),
+        span: Some(span),
+        message: "bindgen can not handle parameterized extern functions".into(),
+    }
+}
+
+/// Error for a function returning `!`.
+pub fn never_return_error(span: Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::NeverReturn),
+        span: Some(span),
+        message: "panics across a C boundary are naughty!".into(),
+    }
+}
+
+/// Returns the values of the variants of the C-like enum `name`.
+///
+/// Like in Rust, a variant without a discriminant is one more than the previous variant.
+/// Discriminants may refer to the previous variants, e.g. `Write = Self::Read as isize * 2`.
+pub fn discriminants(name: &str, definition: &ast::EnumDef) -> Result<Vec<i64>, Error> {
+    let mut values = Vec::with_capacity(definition.variants.len());
+    let mut known = HashMap::new();
+    let mut next = Some(0);
+
+    for variant in &definition.variants {
+        let lookup = |path: &ast::Path| match path.segments[..] {
+            [ref ty, ref variant] if ty.identifier.name == "Self" || ty.identifier.name == name => {
+                known.get(&*variant.identifier.name.as_str()).cloned()
+            }
+            _ => None,
+        };
+        let value = match variant.node.disr_expr {
+            Some(ref expr) => eval_int_with(expr, &lookup).ok_or_else(|| Error {
+                level: Level::Error,
+                code: Some(ErrorCode::UnknownDiscriminant),
+                span: Some(expr.span),
+                message: format!(
+                    "bindgen can not evaluate the discriminant `{}`, it must be an integer \
+                     expression of literals and previous variants",
+                    pprust::expr_to_string(expr)
+                ),
+            })?,
+            None => next.ok_or_else(|| Error {
+                level: Level::Error,
+                code: Some(ErrorCode::UnknownDiscriminant),
+                span: Some(variant.span),
+                message: "the discriminant of the previous variant is the largest integer".into(),
+            })?,
+        };
+        let _ = known.insert(variant.node.name.name.as_str().to_string(), value);
+        values.push(value);
+        next = value.checked_add(1);
+    }
+
+    Ok(values)
+}
+
+/// Returns the source of an integer expression which means the same in C-like languages (e.g.
+/// `1 << 2`), or nothing if it has to be evaluated (e.g. `5u32` or `!0`).
+pub fn c_like_source(expr: &ast::Expr) -> Option<String> {
+    if is_c_like(expr) && eval_int(expr).is_some() {
+        Some(pprust::expr_to_string(expr))
+    } else {
+        None
+    }
+}
+
+/// Check the expression is made of unsuffixed integer literals and arithmetic or bitwise
+/// operators, whose precedence is the same in Rust and C.
+fn is_c_like(expr: &ast::Expr) -> bool {
+    match expr.node {
+        ast::ExprKind::Lit(ref lit) => {
+            matches!(lit.node, ast::LitKind::Int(_, ast::LitIntType::Unsuffixed))
+        }
+        ast::ExprKind::Paren(ref inner) | ast::ExprKind::Unary(ast::UnOp::Neg, ref inner) => {
+            is_c_like(inner)
+        }
+        ast::ExprKind::Binary(_, ref lhs, ref rhs) => is_c_like(lhs) && is_c_like(rhs),
+        _ => false,
+    }
+}
+
+/// Evaluate a constant integer expression, e.g. `5u32`, `-1` or `1 << 2`.
+///
+/// Returns nothing for expressions referring to other items and on overflow.
+pub fn eval_int(expr: &ast::Expr) -> Option<i64> {
+    eval_int_with(expr, &|_| None)
+}
+
+/// Evaluate a constant integer expression, with the values of paths given by `lookup`.
+pub fn eval_int_with(expr: &ast::Expr, lookup: &dyn Fn(&ast::Path) -> Option<i64>) -> Option<i64> {
+    match expr.node {
+        ast::ExprKind::Path(None, ref path) => lookup(path),
+        ast::ExprKind::Lit(ref lit) => match lit.node {
+            ast::LitKind::Int(value, _) => i64::try_from(value.low64()).ok(),
+            _ => None,
+        },
+        ast::ExprKind::Paren(ref inner) => eval_int_with(inner, lookup),
+        ast::ExprKind::Cast(ref inner, ref ty) => {
+            let value = eval_int_with(inner, lookup)?;
+            match primitive(ty)? {
+                // Truncate to the width of the type, sign-extending signed types.
+                Primitive::Int {
+                    bits: Some(bits),
+                    signed,
+                } if bits < 64 => {
+                    let shift = 64 - bits;
+                    Some(if signed {
+                        (value << shift) >> shift
+                    } else {
+                        ((value as u64) << shift >> shift) as i64
+                    })
+                }
+                Primitive::Int { signed: true, .. } => Some(value),
+                Primitive::Int { signed: false, .. } if value >= 0 => Some(value),
+                _ => None,
+            }
+        }
+        ast::ExprKind::Unary(ast::UnOp::Neg, ref inner) => {
+            eval_int_with(inner, lookup)?.checked_neg()
+        }
+        ast::ExprKind::Unary(ast::UnOp::Not, ref inner) => Some(!eval_int_with(inner, lookup)?),
+        ast::ExprKind::Binary(op, ref lhs, ref rhs) => {
+            let (lhs, rhs) = (eval_int_with(lhs, lookup)?, eval_int_with(rhs, lookup)?);
+            match op.node {
+                ast::BinOpKind::Add => lhs.checked_add(rhs),
+                ast::BinOpKind::Sub => lhs.checked_sub(rhs),
+                ast::BinOpKind::Mul => lhs.checked_mul(rhs),
+                ast::BinOpKind::Div => lhs.checked_div(rhs),
+                ast::BinOpKind::Rem => lhs.checked_rem(rhs),
+                ast::BinOpKind::Shl => lhs.checked_shl(u32::try_from(rhs).ok()?),
+                ast::BinOpKind::Shr => lhs.checked_shr(u32::try_from(rhs).ok()?),
+                ast::BinOpKind::BitAnd => Some(lhs & rhs),
+                ast::BinOpKind::BitOr => Some(lhs | rhs),
+                ast::BinOpKind::BitXor => Some(lhs ^ rhs),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Returns whether the calling convention of the function is compatible with
 /// C (i.e. `extern "C"`).
 pub fn is_extern(abi: Abi) -> bool {
@@ -187,3 +646,55 @@ pub fn is_extern(abi: Abi) -> bool {
         _ => false,
     }
 }
+
+/// Rust primitive type, described independently of any target language.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Primitive {
+    /// `()`, which is `void` in C.
+    Unit,
+    Bool,
+    /// Integer with its width in bits, or `None` for `isize` and `usize`.
+    Int {
+        bits: Option<u32>,
+        signed: bool,
+    },
+    Float {
+        bits: u32,
+    },
+}
+
+/// Returns the primitive type `ty` (e.g. `u32`), or `None` for any other type.
+///
+/// The C types of `libc` and `std::os::raw` (`c_int`, ...) vary by platform, so they're left
+/// to the backends.
+pub fn primitive(ty: &ast::Ty) -> Option<Primitive> {
+    match ty.node {
+        ast::TyKind::Tup(ref elems) if elems.is_empty() => Some(Primitive::Unit),
+        ast::TyKind::Path(None, ref path) if path.segments.len() == 1 => {
+            primitive_named(&path.segments[0].identifier.name.as_str())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the primitive type named `name` (e.g. `u32` or `()`), or `None` for any other name.
+pub fn primitive_named(name: &str) -> Option<Primitive> {
+    let int = |bits, signed| Some(Primitive::Int { bits, signed });
+    match name {
+        "()" => Some(Primitive::Unit),
+        "bool" => Some(Primitive::Bool),
+        "i8" => int(Some(8), true),
+        "i16" => int(Some(16), true),
+        "i32" => int(Some(32), true),
+        "i64" => int(Some(64), true),
+        "isize" => int(None, true),
+        "u8" => int(Some(8), false),
+        "u16" => int(Some(16), false),
+        "u32" => int(Some(32), false),
+        "u64" => int(Some(64), false),
+        "usize" => int(None, false),
+        "f32" => Some(Primitive::Float { bits: 32 }),
+        "f64" => Some(Primitive::Float { bits: 64 }),
+        _ => None,
+    }
+}
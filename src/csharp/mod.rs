@@ -313,6 +313,7 @@ impl Lang for LangCSharp {
 
             let ty = transform_type(ty).ok_or_else(|| Error {
                 level: Level::Error,
+                code: None,
                 span: Some(ty.span),
                 message: format!(
                     "bindgen can not handle the type `{}`",
@@ -342,6 +343,7 @@ impl Lang for LangCSharp {
         if let ast::ItemKind::Const(ref ty, ref expr) = item.node {
             let item = transform_const(ty, expr).ok_or_else(|| Error {
                 level: Level::Error,
+                code: None,
                 span: Some(expr.span),
                 message: format!(
                     "bindgen can not handle constant {}",
@@ -382,6 +384,7 @@ impl Lang for LangCSharp {
 
             let item = transform_enum(variants).ok_or_else(|| Error {
                 level: Level::Error,
+                code: None,
                 span: Some(item.span),
                 message: format!(
                     "bindgen can not handle enum {}",
@@ -423,6 +426,7 @@ impl Lang for LangCSharp {
             if !variants.is_struct() {
                 return Err(Error {
                     level: Level::Error,
+                    code: None,
                     span: Some(item.span),
                     message: format!("bindgen can not handle unit or tuple structs ({})", name),
                 });
@@ -430,6 +434,7 @@ impl Lang for LangCSharp {
 
             let item = transform_struct(variants.fields()).ok_or_else(|| Error {
                 level: Level::Error,
+                code: None,
                 span: Some(item.span),
                 message: format!(
                     "bindgen can not handle struct {}",
@@ -480,6 +485,7 @@ impl Lang for LangCSharp {
 
                 Error {
                     level: Level::Error,
+                    code: None,
                     span: Some(item.span),
                     message: format!("bindgen can not handle function {}", string),
                 }
@@ -793,6 +799,7 @@ fn callback_wrapper_name(callback: &Function) -> String {
 fn unsupported_generics_error(item: &ast::Item, name: &str) -> Error {
     Error {
         level: Level::Error,
+        code: None,
         span: Some(item.span),
         message: format!("bindgen can not handle parameterized {}", name),
     }
@@ -2,7 +2,7 @@
 
 use super::types::{callback_name, rust_ty_to_java};
 use super::{Context, Outputs};
-use crate::common::{append_output, is_array_arg, is_user_data_arg};
+use crate::common::append_output;
 use crate::quote;
 use crate::struct_field::StructField;
 use crate::syntax::ast;
@@ -215,16 +215,17 @@ pub fn generate_jni_function(
     let mut stmts = Vec::new();
     let mut callbacks = Vec::new();
     let mut jni_fn_inputs = Vec::new();
+    let convention = context.callbacks.clone();
 
     let mut args_iter = args
         .into_iter()
-        .filter(|arg| !is_user_data_arg(arg))
+        .filter(|arg| !convention.is_user_data_arg(arg))
         .peekable();
 
     while let Some(arg) = args_iter.next() {
         let arg_name = pprust::pat_to_string(&*arg.pat);
 
-        let res = if is_array_arg(&arg, args_iter.peek()) {
+        let res = if convention.is_array_arg(&arg, args_iter.peek()) {
             args_iter.next();
             Some(transform_array_arg(&arg_name))
         } else {
@@ -354,7 +355,7 @@ fn generate_callback(cb: &ast::BareFnTy, context: &Context) -> JniCallback {
 
     let mut args_iter = (*cb.decl.inputs)
         .iter()
-        .filter(|arg| !is_user_data_arg(arg))
+        .filter(|arg| !context.callbacks.is_user_data_arg(arg))
         .peekable();
 
     while let Some(arg) = args_iter.next() {
@@ -363,7 +364,10 @@ fn generate_callback(cb: &ast::BareFnTy, context: &Context) -> JniCallback {
         jni_cb_inputs.push(quote! { #arg_name: #arg_ty });
         args.push(quote! { #arg_name.into() });
 
-        if is_array_arg(arg, args_iter.peek().cloned()) {
+        if context
+            .callbacks
+            .is_array_arg(arg, args_iter.peek().cloned())
+        {
             // Handle array arguments
             let val_java_type = unwrap!(rust_ty_to_signature(&arg.ty, context));
             arg_java_ty.push(JavaType::Array(Box::new(val_java_type)));
@@ -4,8 +4,8 @@ mod jni;
 mod types;
 
 use crate::common::{
-    self, append_output, check_no_mangle, is_array_arg, is_user_data_arg, parse_attr,
-    retrieve_docstring, FilterMode, Outputs,
+    self, append_output, check_no_mangle, parse_attr, retrieve_docstring, CallbackConvention,
+    FilterMode, Outputs,
 };
 use crate::java::types::{callback_name, java_type_to_str, rust_to_java, struct_to_java_classname};
 use crate::jni::signature::JavaType;
@@ -35,6 +35,8 @@ pub struct Context {
     type_map: HashMap<&'static str, JavaType>,
     /// Keeps track of which JNI callback functions has been generated already
     generated_jni_cbs: BTreeSet<String>,
+    /// Arguments of asynchronous functions and callbacks which are handled specially
+    callbacks: CallbackConvention,
 }
 
 impl Default for Context {
@@ -45,6 +47,7 @@ impl Default for Context {
             namespace_model: "net.maidsafe.dummy".to_string(),
             type_map: Default::default(),
             generated_jni_cbs: Default::default(),
+            callbacks: Default::default(),
         }
     }
 }
@@ -60,6 +63,7 @@ impl LangJava {
                 namespace: "net.maidsafe.bindings".to_owned(),
                 namespace_model: "net.maidsafe.model".to_owned(),
                 generated_jni_cbs: BTreeSet::new(),
+                callbacks: CallbackConvention::default(),
             },
         }
     }
@@ -79,6 +83,18 @@ impl LangJava {
         self.context.namespace_model = namespace.into();
     }
 
+    /// Set the name and type of the argument passed back to callbacks as their context, which is
+    /// hidden from the Java signatures (`user_data: *mut c_void` by default).
+    pub fn set_user_data_arg<N: Into<String>, T: Into<String>>(&mut self, name: N, ty: T) {
+        self.context.callbacks.user_data = (name.into(), ty.into());
+    }
+
+    /// Set the name and type of the argument carrying the result of an asynchronous operation
+    /// to callbacks (`result: *const FfiResult` by default).
+    pub fn set_result_arg<N: Into<String>, T: Into<String>>(&mut self, name: N, ty: T) {
+        self.context.callbacks.result = (name.into(), ty.into());
+    }
+
     /// Applies rustfmt to JNI code to improve debuggability
     fn format_jni_output(&self, input: &mut String) {
         let mut output: Vec<u8> = Vec::with_capacity(input.len() * 2);
@@ -165,6 +181,7 @@ impl common::Lang for LangJava {
             if generics.is_parameterized() {
                 return Err(Error {
                     level: Level::Error,
+                    code: None,
                     span: Some(item.span),
                     message: "cheddar can not handle parameterized extern functions".into(),
                 });
@@ -183,6 +200,7 @@ impl common::Lang for LangJava {
         } else {
             Err(Error {
                 level: Level::Bug,
+                code: None,
                 span: Some(item.span),
                 message: "`parse_fn` called on wrong `Item_`".into(),
             })
@@ -220,6 +238,7 @@ impl common::Lang for LangJava {
             if generics.is_parameterized() {
                 return Err(Error {
                     level: Level::Error,
+                    code: None,
                     span: Some(item.span),
                     message: "cheddar can not handle parameterized `#[repr(C)]` structs".into(),
                 });
@@ -252,6 +271,7 @@ impl common::Lang for LangJava {
             } else {
                 return Err(Error {
                     level: Level::Error,
+                    code: None,
                     span: Some(item.span),
                     message: "cheddar can not handle unit or tuple `#[repr(C)]` \
                               structs with >1 members"
@@ -261,6 +281,7 @@ impl common::Lang for LangJava {
         } else {
             return Err(Error {
                 level: Level::Bug,
+                code: None,
                 span: Some(item.span),
                 message: "`parse_struct` called on wrong `Item_`".into(),
             });
@@ -281,6 +302,7 @@ impl common::Lang for LangJava {
             None => {
                 return Err(Error {
                     level: Level::Error,
+                    code: None,
                     span: None,
                     message: "no jni bindings generated?".to_owned(),
                 })
@@ -294,6 +316,7 @@ impl common::Lang for LangJava {
             }
             None => Err(Error {
                 level: Level::Error,
+                code: None,
                 span: None,
                 message: "no native bindings generated?".to_owned(),
             }),
@@ -433,11 +456,12 @@ pub fn transform_native_fn(
     context: &mut Context,
 ) -> Result<(), Error> {
     let mut args_str = Vec::new();
+    let convention = context.callbacks.clone();
 
     let mut fn_args = fn_decl
         .inputs
         .iter()
-        .filter(|arg| !is_user_data_arg(arg))
+        .filter(|arg| !convention.is_user_data_arg(arg))
         .peekable();
 
     while let Some(arg) = fn_args.next() {
@@ -446,7 +470,7 @@ pub fn transform_native_fn(
         // Generate function arguments
         let mut java_type = rust_to_java(&arg.ty, context)?;
 
-        if is_array_arg(arg, fn_args.peek().cloned()) {
+        if convention.is_array_arg(arg, fn_args.peek().cloned()) {
             // Skip the length args - e.g. for a case of `ptr: *const u8, ptr_len: usize`
             // we're going to skip the `len` part.
             java_type = JavaType::Array(Box::new(java_type));
@@ -485,6 +509,7 @@ pub fn transform_native_fn(
         ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
             return Err(Error {
                 level: Level::Error,
+                code: None,
                 span: Some(ty.span),
                 message: "panics across a C boundary are naughty!".into(),
             });
@@ -564,6 +589,7 @@ pub fn transform_callback<S: AsRef<str>>(
         // All other types just have a name associated with them.
         _ => Err(Error {
             level: Level::Error,
+            code: None,
             span: Some(ty.span),
             message: "Invalid callback type".into(),
         }),
@@ -582,6 +608,7 @@ fn callback_to_java(
         _ => {
             return Err(Error {
                 level: Level::Error,
+                code: None,
                 span: Some(fn_span),
                 message: "callbacks that don't have C ABI are not supported".into(),
             })
@@ -591,6 +618,7 @@ fn callback_to_java(
     if !fn_ty.lifetimes.is_empty() {
         return Err(Error {
             level: Level::Error,
+            code: None,
             span: Some(fn_span),
             message: "can not handle lifetimes".into(),
         });
@@ -602,14 +630,17 @@ fn callback_to_java(
     let mut args_iter = fn_decl
         .inputs
         .iter()
-        .filter(|arg| !is_user_data_arg(arg))
+        .filter(|arg| !context.callbacks.is_user_data_arg(arg))
         .peekable();
 
     while let Some(arg) = args_iter.next() {
         let arg_name = pprust::pat_to_string(&*arg.pat);
         let mut java_type = rust_to_java(&*arg.ty, context)?;
 
-        if is_array_arg(arg, args_iter.peek().cloned()) {
+        if context
+            .callbacks
+            .is_array_arg(arg, args_iter.peek().cloned())
+        {
             // Detect array ptrs: skip the length args and add array to the type sig
             java_type = JavaType::Array(Box::new(java_type));
             args_iter.next();
@@ -652,6 +683,7 @@ mod tests {
             namespace: "net.maidsafe.bindings".to_owned(),
             namespace_model: "net.maidsafe.model".to_owned(),
             generated_jni_cbs: BTreeSet::new(),
+            callbacks: CallbackConvention::default(),
         };
 
         let inputs = get_inputs("fn dummy() {}");
@@ -683,5 +715,15 @@ mod tests {
             "CallbackResultString",
             unwrap!(callback_name(&inputs, &context))
         );
+
+        let mut context = context;
+        context.callbacks.user_data = ("ctx".to_owned(), "*mut c_void".to_owned());
+        context.callbacks.result = ("status".to_owned(), "*const Status".to_owned());
+
+        let inputs = get_inputs("fn dummy(ctx: *mut c_void, status: *const Status, b: u64) {}");
+        assert_eq!(
+            "CallbackResultLong",
+            unwrap!(callback_name(&inputs, &context))
+        );
     }
 }
@@ -1,6 +1,5 @@
 //! Functions for converting Rust types to Java types.
 
-use crate::common::{is_array_arg, is_result_arg, is_user_data_arg};
 use crate::java::Context;
 use crate::syntax::abi::Abi;
 use crate::syntax::print::pprust;
@@ -33,6 +32,7 @@ pub fn java_type_to_str(ty: &JavaType) -> Result<String, Error> {
         JavaType::Array(ref boxed) => Ok(format!("{}[]", java_type_to_str(&*boxed)?)),
         JavaType::Method(..) => Err(Error {
             level: Level::Error,
+            code: None,
             span: None,
             message: "Java methods are not supported".into(),
         }),
@@ -55,11 +55,11 @@ pub fn callback_name(inputs: &[ast::Arg], context: &Context) -> Result<String, E
     let mut inputs = inputs.iter().peekable();
 
     while let Some(arg) = inputs.next() {
-        if is_user_data_arg(arg) {
+        if context.callbacks.is_user_data_arg(arg) {
             // Skip user_data args
             continue;
         }
-        if is_result_arg(arg) {
+        if context.callbacks.is_result_arg(arg) {
             // Make sure that a CB taking a single "result: *const FfiResult" param
             // won't end up being called "CallbackVoid" (but "CallbackResult" instead)
             components.push(From::from("Result"));
@@ -69,7 +69,7 @@ pub fn callback_name(inputs: &[ast::Arg], context: &Context) -> Result<String, E
         let arg_type = &rust_ty_to_java_class_name(&*arg.ty, context)?;
         let mut arg_type = struct_to_java_classname(arg_type);
 
-        if is_array_arg(arg, inputs.peek().cloned()) {
+        if context.callbacks.is_array_arg(arg, inputs.peek().cloned()) {
             inputs.next();
             arg_type.push_str("ArrayLen");
         }
@@ -96,6 +96,7 @@ fn callback_arg_to_java(
         _ => {
             return Err(Error {
                 level: Level::Error,
+                code: None,
                 span: Some(fn_span),
                 message: "callbacks that don't have C ABI are not supported".into(),
             })
@@ -105,6 +106,7 @@ fn callback_arg_to_java(
     if !fn_ty.lifetimes.is_empty() {
         return Err(Error {
             level: Level::Error,
+            code: None,
             span: Some(fn_span),
             message: "can not handle lifetimes".into(),
         });
@@ -154,6 +156,7 @@ fn anon_rust_to_java(
         // Function pointers should not be in this function.
         ast::TyKind::BareFn(..) => Err(Error {
             level: Level::Error,
+            code: None,
             span: Some(ty.span),
             message: "C function pointers must have a name or function declaration \
                       associated with them"
@@ -181,6 +184,7 @@ fn anon_rust_to_java(
             } else {
                 Err(Error {
                     level: Level::Error,
+                    code: None,
                     span: Some(ty.span),
                     message: format!("unknown type `{}`", new_type),
                 })
@@ -201,6 +205,7 @@ fn path_to_java(
     if path.segments.is_empty() {
         return Err(Error {
             level: Level::Bug,
+            code: None,
             span: Some(path.span),
             message: "invalid type".into(),
         });
@@ -224,6 +229,7 @@ fn path_to_java(
             }
             _ => Err(Error {
                 level: Level::Error,
+                code: None,
                 span: Some(path.span),
                 message: "can't convert type".into(),
             }),
@@ -0,0 +1,87 @@
+//! JSON description of the layout of the generated structs.
+
+use std::fmt::Write;
+
+/// Layout of a C struct.
+#[derive(Debug)]
+pub struct StructLayout {
+    pub name: String,
+    /// Size in bytes, if given with `#[ffi(size = N)]`.
+    pub size: Option<u64>,
+    pub fields: Vec<FieldLayout>,
+}
+
+/// Layout of a field of a C struct.
+#[derive(Debug)]
+pub struct FieldLayout {
+    pub name: String,
+    /// C type of the field.
+    pub ty: String,
+    /// Offset in bytes, if given with `#[ffi(offset = N)]`.
+    pub offset: Option<u64>,
+}
+
+/// Render the layouts as a JSON document of the form `{"structs": [...]}`.
+pub fn to_json(layouts: &[StructLayout]) -> String {
+    let mut json = String::from("{\n  \"structs\": [");
+
+    for (i, layout) in layouts.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str("\n    {\n");
+        unwrap!(writeln!(
+            json,
+            "      \"name\": {},",
+            json_string(&layout.name)
+        ));
+        if let Some(size) = layout.size {
+            unwrap!(writeln!(json, "      \"size\": {},", size));
+        }
+        json.push_str("      \"fields\": [");
+
+        for (j, field) in layout.fields.iter().enumerate() {
+            if j > 0 {
+                json.push(',');
+            }
+            unwrap!(write!(
+                json,
+                "\n        {{ \"name\": {}, \"type\": {}",
+                json_string(&field.name),
+                json_string(&field.ty)
+            ));
+            if let Some(offset) = field.offset {
+                unwrap!(write!(json, ", \"offset\": {}", offset));
+            }
+            json.push_str(" }");
+        }
+
+        if !layout.fields.is_empty() {
+            json.push_str("\n      ");
+        }
+        json.push_str("]\n    }");
+    }
+
+    if !layouts.is_empty() {
+        json.push_str("\n  ");
+    }
+    json.push_str("]\n}\n");
+
+    json
+}
+
+/// Quote and escape a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => unwrap!(write!(quoted, "\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
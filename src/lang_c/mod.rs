@@ -1,28 +1,263 @@
 //! Functions for converting Rust types to C types.
 
+mod layout;
 #[cfg(test)]
 mod tests;
 mod types;
+mod wrapper;
 
-use self::types::{CPtrType, CType, CTypeNamed};
+use self::layout::{FieldLayout, StructLayout};
+use self::types::SYSTEM_TYPES;
+pub use self::types::{CPtrType, CType, CTypeNamed};
 use crate::common::{
-    append_output, check_no_mangle, check_repr_c, parse_attr, retrieve_docstring, Lang, Outputs,
+    self, append_output, check_derive, check_ffi, check_no_mangle, check_repr_c, parse_attr,
+    parse_repr, retrieve_export_name, retrieve_ffi_int, retrieve_ffi_int_pairs, retrieve_ffi_pairs,
+    retrieve_ffi_value, retrieve_link_name, Lang, LineEnding, Outputs,
 };
 use crate::syntax::abi::Abi;
+use crate::syntax::codemap::FilePathMapping;
+use crate::syntax::parse::{new_parser_from_source_str, ParseSess};
 use crate::syntax::print::pprust;
+use crate::syntax::ptr::P;
 use crate::syntax::{ast, codemap, print};
-use crate::Error;
 use crate::Level;
-use petgraph::{algo, Graph};
+use crate::{Error, ErrorCode};
+use inflector::Inflector;
+use petgraph::graph::NodeIndex;
+use petgraph::{Direction, Graph};
 use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::mem;
 use std::path;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs};
+
+/// Callback naming the header of a module, given the module path and the library name.
+pub type HeaderNamer = Box<dyn Fn(&[String], &str) -> String>;
 
 pub struct LangC {
     lib_name: String,
+    context: Context,
     decls: BTreeMap<String, String>,
     deps: BTreeMap<String, Vec<String>>,
+    std_includes: BTreeMap<String, BTreeSet<&'static str>>,
+    float_extensions: BTreeMap<String, BTreeSet<&'static str>>,
     custom_code: String,
+    /// Comment at the top of the generated files, or `None` for the default one.
+    banner: Option<String>,
+    prelude_includes: String,
+    line_ending: LineEnding,
+    flat_layout: bool,
+    header_namer: Option<HeaderNamer>,
+    module_prefix: Vec<String>,
+    prefix_std_headers: bool,
+    cpp_guard: bool,
+    include_style: IncludeStyle,
+    version: Option<(u32, u32, u32)>,
+    pointer_width: Option<u32>,
+    /// Preprocessor conditions the platform must meet, with the error message otherwise.
+    platform_requirements: Vec<(String, String)>,
+    uses_pointer_sized: bool,
+    allowlist_only: bool,
+    default_initializers: bool,
+    packing_style: PackingStyle,
+    empty_params: EmptyParams,
+    enum_tag_style: EnumTagStyle,
+    enum_to_string: bool,
+    enum_value_checks: bool,
+    discriminant_comments: bool,
+    offset_comments: bool,
+    c23_enums: bool,
+    deferring: bool,
+    /// Deferred items by module path and name, as items of different modules may share a name.
+    deferred: BTreeMap<DeferredKey, Deferred>,
+    internal: bool,
+    internal_headers: BTreeSet<String>,
+    /// C types overriding the types of the arguments of the current function.
+    arg_c_types: BTreeMap<String, String>,
+    /// Whether the current function is marked `#[must_use]`.
+    must_use: bool,
+    /// Functional group of the current item, given with `#[ffi(group = "...")]`.
+    group: Option<String>,
+    /// Optimizer hints of the current function, e.g. `BINDGEN_PURE`.
+    fn_hints: Vec<&'static str>,
+    /// Headers declaring functions with optimizer hints, which need their macros.
+    fn_hint_headers: BTreeSet<String>,
+    nodiscard: bool,
+    /// Column past which the parameters of functions go on separate lines.
+    max_line_width: Option<usize>,
+    def_file: Option<String>,
+    exports: Vec<String>,
+    layout_schema: Option<String>,
+    layouts: Vec<StructLayout>,
+    cmake_file: Option<String>,
+    /// Name, version and linker flags of the pkg-config file.
+    pkg_config: Option<(String, String, String)>,
+    /// C compiler checking the generated headers.
+    compiler: Option<String>,
+    /// Rust file of the wrappers of the functions marked `#[ffi(wrap)]`, with their source.
+    rust_wrappers: Option<(String, String)>,
+    field_types: Vec<FieldType>,
+    warnings: Vec<Error>,
+    strict: bool,
+    tagged_unions: bool,
+    byte_array_typedefs: bool,
+    annotate_source: bool,
+    alias_instantiations: Vec<P<ast::Ty>>,
+    tuple_out_params: bool,
+    c89: bool,
+    typedefs: bool,
+    trace_skipped_items: bool,
+    notes: Vec<Error>,
+}
+
+/// Item which isn't exported itself, kept in case an exported item depends on it.
+struct Deferred {
+    outputs: Outputs,
+    deps: BTreeMap<String, Vec<String>>,
+}
+
+/// Module path and name of a deferred item.
+type DeferredKey = (Vec<String>, String);
+
+/// Type used by a struct field, checked for a declaration once all the items are generated.
+struct FieldType {
+    name: String,
+    module: Vec<String>,
+    strukt: String,
+    field: String,
+    span: codemap::Span,
+}
+
+/// Form of the `#include` directives in the top-level header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncludeStyle {
+    /// `#include "module.h"`, relative to the top-level header.
+    Local,
+    /// `#include <prefix/module.h>`, for headers installed into a system include directory.
+    System { prefix: Option<String> },
+}
+
+impl IncludeStyle {
+    fn include(&self, header: &str) -> String {
+        match *self {
+            IncludeStyle::Local => format!("#include \"{}\"\n", header),
+            IncludeStyle::System { prefix: None } => format!("#include <{}>\n", header),
+            IncludeStyle::System {
+                prefix: Some(ref prefix),
+            } => format!("#include <{}/{}>\n", prefix, header),
+        }
+    }
+}
+
+/// Declaration of packed and over-aligned structs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PackingStyle {
+    /// GCC/Clang `__attribute__((packed))` and `__attribute__((aligned(N)))`. Structs packed to
+    /// more than one byte (`#[repr(packed(N))]`) still use `#pragma pack`, which has no attribute
+    /// equivalent.
+    Attribute,
+    /// MSVC-style `#pragma pack(push, N)` and `#pragma pack(pop)` around the struct, and
+    /// `__declspec(align(N))`.
+    Pragma,
+}
+
+/// Parameter list of functions without arguments.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmptyParams {
+    /// `name(void)`, the only prototype of a function without arguments before C23.
+    Void,
+    /// `name()`, the C++ style. In C before C23, this declares a function with unspecified
+    /// arguments.
+    Empty,
+}
+
+/// Declaration of the typedefs of enums.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EnumTagStyle {
+    /// `typedef enum Name { ... } Name;`
+    Named,
+    /// `typedef enum { ... } Name;`, which only works with typedefs.
+    Anonymous,
+}
+
+/// Function mapping a Rust type name to a C type.
+pub type TypeResolver = Box<dyn Fn(&str) -> Option<String>>;
+
+/// Function renaming a generated C identifier.
+pub type IdentifierTransform = Box<dyn Fn(&str) -> String>;
+
+/// Configuration used when converting Rust types into C types.
+#[derive(Default)]
+pub struct Context {
+    /// User-provided mapping for types without a built-in C equivalent
+    type_resolver: Option<TypeResolver>,
+    /// C types of Rust types, loaded from a mapping table
+    type_mappings: HashMap<String, String>,
+    /// Structs and enums declared without a typedef, which are referred to by their tags.
+    tags: HashMap<String, &'static str>,
+    /// User-provided renaming of the generated identifiers
+    identifier_transform: Option<IdentifierTransform>,
+    /// Values of the public integer constants seen so far, used for the lengths of arrays.
+    consts: HashMap<String, String>,
+    /// Whether `bool` is the `_Bool` keyword rather than the macro from `stdbool.h`.
+    underscore_bool: bool,
+    /// Types of `isize` and `usize`, if not `intptr_t` and `uintptr_t`.
+    pointer_sized: Option<(&'static str, &'static str)>,
+}
+
+impl Context {
+    /// Map a Rust type name using the user-provided type resolver.
+    fn resolve(&self, ty: &str) -> Option<CType> {
+        self.type_mappings
+            .get(ty)
+            .cloned()
+            .or_else(|| {
+                self.type_resolver
+                    .as_ref()
+                    .and_then(|resolver| resolver(ty))
+            })
+            .map(CType::Custom)
+    }
+
+    /// Refer to a struct or enum by its tag, if it's declared without a typedef.
+    fn tag(&self, name: &str) -> Option<CType> {
+        self.tags
+            .get(name)
+            .map(|&kind| CType::Tagged(kind, name.to_string()))
+    }
+
+    /// Refer to a generated type by its C name.
+    fn named(&self, name: String) -> CType {
+        self.tag(&name).unwrap_or(CType::Mapping(name))
+    }
+
+    /// C identifier of a Rust name, using the user-provided identifier transform.
+    fn ident(&self, name: &str) -> String {
+        match self.identifier_transform {
+            Some(ref transform) => transform(name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Use other types than `intptr_t` and `uintptr_t` for `isize` and `usize`, e.g. `NSInteger`
+    /// and `NSUInteger` in Objective-C.
+    pub(crate) fn set_pointer_sized_types(&mut self, isize: &'static str, usize: &'static str) {
+        self.pointer_sized = Some((isize, usize));
+    }
+
+    /// Remember the value of an integer constant (`pub const SIZE: usize = 32;`), so that arrays
+    /// can use it as their length.
+    pub(crate) fn define_const(&mut self, item: &ast::Item) {
+        if let ast::ItemKind::Const(ref ty, ref expr) = item.node {
+            if let Some(common::Primitive::Int { .. }) = common::primitive(ty) {
+                let value = array_len(expr, self).unwrap_or_else(|_| pprust::expr_to_string(expr));
+                self.consts
+                    .insert(item.ident.name.as_str().to_string(), value);
+            }
+        }
+    }
 }
 
 /// Compile the header declarations then add the needed `#include`s.
@@ -30,17 +265,81 @@ pub struct LangC {
 /// Currently includes:
 ///
 /// - `stdint.h`
-/// - `stdbool.h`
+/// - `stdbool.h`, unless `_Bool` is used instead
 impl LangC {
     pub fn new() -> Self {
         Self {
             lib_name: "backend".to_owned(),
+            context: Default::default(),
             decls: BTreeMap::new(),
             deps: BTreeMap::new(),
+            std_includes: BTreeMap::new(),
+            float_extensions: BTreeMap::new(),
             custom_code: Default::default(),
+            banner: None,
+            rust_wrappers: None,
+            prelude_includes: Default::default(),
+            line_ending: LineEnding::Lf,
+            flat_layout: false,
+            header_namer: None,
+            module_prefix: Vec::new(),
+            prefix_std_headers: false,
+            cpp_guard: true,
+            include_style: IncludeStyle::Local,
+            version: None,
+            pointer_width: None,
+            platform_requirements: Vec::new(),
+            uses_pointer_sized: false,
+            allowlist_only: false,
+            default_initializers: false,
+            packing_style: PackingStyle::Attribute,
+            empty_params: EmptyParams::Void,
+            enum_tag_style: EnumTagStyle::Named,
+            enum_to_string: false,
+            enum_value_checks: false,
+            discriminant_comments: false,
+            offset_comments: false,
+            c23_enums: false,
+            deferring: false,
+            deferred: BTreeMap::new(),
+            internal: false,
+            internal_headers: BTreeSet::new(),
+            arg_c_types: BTreeMap::new(),
+            def_file: None,
+            exports: Vec::new(),
+            layout_schema: None,
+            layouts: Vec::new(),
+            cmake_file: None,
+            pkg_config: None,
+            field_types: Vec::new(),
+            warnings: Vec::new(),
+            strict: false,
+            tagged_unions: false,
+            byte_array_typedefs: false,
+            annotate_source: false,
+            alias_instantiations: Vec::new(),
+            tuple_out_params: false,
+            c89: false,
+            typedefs: true,
+            must_use: false,
+            group: None,
+            compiler: None,
+            fn_hints: Vec::new(),
+            fn_hint_headers: BTreeSet::new(),
+            nodiscard: false,
+            max_line_width: None,
+            trace_skipped_items: false,
+            notes: Vec::new(),
         }
     }
 
+    /// Warnings raised while generating the headers, e.g. struct fields using undeclared types.
+    ///
+    /// In strict mode these have the `Error` level.
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
     /// Set the name of the native library.
     pub fn set_lib_name<T: Into<String>>(&mut self, name: T) {
         self.lib_name = name.into();
@@ -52,11 +351,630 @@ impl LangC {
         self.custom_code.push_str(code);
     }
 
+    /// Set the comment at the top of every generated file (except the layout schema, as JSON has
+    /// no comments). By default, it says that the file is generated and shouldn't be edited. An
+    /// empty banner disables it.
+    pub fn set_banner(&mut self, text: &str) {
+        self.banner = Some(text.to_string());
+    }
+
+    /// Include the given header in every module header, after the standard includes.
+    ///
+    /// The header is quoted unless it's already wrapped in quotes or angle brackets.
+    pub fn add_prelude_include(&mut self, header: &str) {
+        let include = if header.starts_with('<') || header.starts_with('"') {
+            format!("#include {}\n", header)
+        } else {
+            format!("#include \"{}\"\n", header)
+        };
+        self.prelude_includes.push_str(&include);
+    }
+
+    /// Set the line ending used in the generated headers (`LineEnding::Lf` by default).
+    pub fn set_line_ending(&mut self, eol: LineEnding) {
+        self.line_ending = eol;
+    }
+
+    /// Put all headers into a single directory, naming them after the full module path
+    /// (e.g. `libname_mod_submod.h`) instead of generating a nested directory tree.
+    pub fn flat_layout(&mut self, flat: bool) {
+        self.flat_layout = flat;
+    }
+
+    /// Drop a leading module path (e.g. `&["ffi", "api"]`) from the paths of the headers, so that
+    /// the items of `ffi::api::net` go to `libname/net.h` and those of `ffi::api` go to
+    /// `libname/libname.h`. Modules outside of the prefix keep their paths.
+    pub fn strip_module_prefix(&mut self, segments: &[&str]) {
+        self.module_prefix = segments.iter().map(|segment| segment.to_string()).collect();
+    }
+
+    /// Prefix the headers of modules named like a standard C header (e.g. `string.h`) with the
+    /// library name (`backend_string.h`), instead of only warning about them.
+    pub fn prefix_std_headers(&mut self, enabled: bool) {
+        self.prefix_std_headers = enabled;
+    }
+
+    /// Name the headers with a callback, instead of the built-in layout.
+    ///
+    /// The callback is given the module path (e.g. `["ffi", "mod"]`) and the library name, and
+    /// returns the path of the header, which is also used in the `#include` directives.
+    pub fn set_header_namer(&mut self, namer: HeaderNamer) {
+        self.header_namer = Some(namer);
+    }
+
+    /// Enable/disable wrapping the declarations in `extern "C"` when compiled as C++
+    /// (enabled by default).
+    pub fn cpp_guard(&mut self, enabled: bool) {
+        self.cpp_guard = enabled;
+    }
+
+    /// Set the library version, defined as `LIBNAME_VERSION_{MAJOR,MINOR,PATCH}` and
+    /// `LIBNAME_VERSION` macros in the top-level header.
+    pub fn set_version(&mut self, major: u32, minor: u32, patch: u32) {
+        self.version = Some((major, minor, patch));
+    }
+
+    /// Assert that pointers have the given width (in bits) in the top-level header, if any
+    /// `isize` or `usize` is used, so that the headers fail to compile on other platforms.
+    pub fn assert_pointer_width(&mut self, bits: u32) {
+        self.pointer_width = Some(bits);
+    }
+
+    /// Enable/disable a comment with the byte offset of each struct field (e.g.
+    /// `/* offset 8 */`), as a reference of the layout for C code.
+    ///
+    /// Offsets are computed from the sizes of the primitive types, pointers being sized by
+    /// `assert_pointer_width`. Fields of other types can be sized with `#[ffi(size = N)]` (and
+    /// `#[ffi(align = N)]`, 1 by default), or have their offset given with `#[ffi(offset = N)]`.
+    /// Offsets which can't be computed are shown as `/* offset ? */`.
+    pub fn emit_offset_comments(&mut self, enabled: bool) {
+        self.offset_comments = enabled;
+    }
+
+    /// Fail the compilation of the top-level header with an `#error` unless the preprocessor
+    /// condition holds, e.g. `require_platform("defined(__LP64__)", "64-bit only")`.
+    ///
+    /// The condition is used verbatim, while the message is quoted. Nothing is included before
+    /// the check, so the condition can only use the macros predefined by the compiler.
+    pub fn require_platform(&mut self, guard: &str, message: &str) {
+        self.platform_requirements
+            .push((guard.to_string(), message.to_string()));
+    }
+
+    /// Generate `#[repr(C)]` enums with data-carrying variants as tagged unions: a struct with
+    /// the `Name_Tag` enum of the variant, followed by a union of the variant payloads.
+    pub fn tagged_unions(&mut self, enabled: bool) {
+        self.tagged_unions = enabled;
+    }
+
+    /// Declare the byte arrays of struct fields (`[u8; N]` with a literal length) with a shared
+    /// `typedef uint8_t libname_bytesN[N];`, used by all the fields with the same length.
+    pub fn byte_array_typedefs(&mut self, enabled: bool) {
+        self.byte_array_typedefs = enabled;
+    }
+
+    /// Add a comment with the Rust signature above each function, e.g.
+    /// `/* rust: pub fn foo(x: i32) -> i32 */`.
+    pub fn annotate_source(&mut self, enabled: bool) {
+        self.annotate_source = enabled;
+    }
+
+    /// Generate a typedef for an instantiation of a generic type alias, e.g. `Handle<Foo>` for
+    /// `pub type Handle<T> = *mut T;` generates `typedef Foo* Handle_Foo;`.
+    ///
+    /// Generic aliases are skipped unless they have instantiations.
+    pub fn add_alias_instantiation(&mut self, instantiation: &str) -> Result<(), Error> {
+        let sess = ParseSess::new(FilePathMapping::empty());
+        let ty = {
+            let mut parser =
+                new_parser_from_source_str(&sess, "<instantiation>".into(), instantiation.into());
+            parser.parse_ty().map_err(|mut diagnostic| {
+                diagnostic.cancel();
+                Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::Parse),
+                    span: None,
+                    message: format!("could not parse the instantiation `{}`", instantiation),
+                }
+            })?
+        };
+        self.alias_instantiations.push(ty);
+        Ok(())
+    }
+
+    /// Use the C99 `_Bool` keyword for `bool` instead of the macro from `stdbool.h`, which isn't
+    /// included then, for freestanding environments without it. `_Bool` isn't a C++ keyword, so
+    /// the headers are only usable from C.
+    pub fn use_underscore_bool(&mut self, enabled: bool) {
+        self.context.underscore_bool = enabled;
+    }
+
+    /// Generate C89 headers, for toolchains without `stdint.h` and `stdbool.h`.
+    ///
+    /// The fixed-width integers are defined with typedefs instead (assuming `long long` is
+    /// supported, as it is by most C89 compilers, and that `long` is pointer-sized except on
+    /// 64-bit Windows), `bool` is an `unsigned char` to match the size of Rust's `bool`, the docs
+    /// are `/* */` comments, functions aren't `inline`, enumerator lists have no trailing commas,
+    /// `[[nodiscard]]` falls back to `__attribute__((warn_unused_result))` and there are no static
+    /// assertions.
+    pub fn c89_mode(&mut self, enabled: bool) {
+        self.c89 = enabled;
+    }
+
+    /// Return tuples through trailing out-parameters (`int32_t* out0, int32_t* out1`) of
+    /// functions returning `void`, instead of failing on them.
+    pub fn tuple_out_params(&mut self, enabled: bool) {
+        self.tuple_out_params = enabled;
+    }
+
+    /// Mark the `#[must_use]` functions with the C23 and C++17 `[[nodiscard]]` attribute, instead
+    /// of `__attribute__((warn_unused_result))`.
+    pub fn nodiscard(&mut self, enabled: bool) {
+        self.nodiscard = enabled;
+    }
+
+    /// Put the parameters of function declarations longer than `width` columns on separate
+    /// lines, aligned after the opening parenthesis. Declarations are on a single line by default.
+    pub fn set_max_line_width(&mut self, width: usize) {
+        self.max_line_width = Some(width);
+    }
+
+    /// Declare the structs and enums with typedefs (enabled by default).
+    ///
+    /// When disabled, they're only declared by their tags (e.g. `struct Name { ... };`), and
+    /// referred to as `struct Name` or `enum Name`. As in C, the types have to be declared before
+    /// they're used to be referred to by their tags.
+    pub fn typedefs(&mut self, enabled: bool) {
+        self.typedefs = enabled;
+    }
+
+    /// Record a note for each struct or enum which isn't generated because it's not
+    /// `#[repr(C)]`, to audit what is generated. The notes are available from `notes`.
+    pub fn trace_skipped_items(&mut self, enabled: bool) {
+        self.trace_skipped_items = enabled;
+    }
+
+    /// Notes about the skipped items, if enabled with `trace_skipped_items`.
+    pub fn notes(&self) -> &[Error] {
+        &self.notes
+    }
+
+    /// Fail the generation if any warning is raised, reporting the warnings as errors.
+    pub fn strict(&mut self, enabled: bool) {
+        self.strict = enabled;
+    }
+
+    /// Only generate items marked with `#[ffi(export)]`, along with the types they depend on.
+    pub fn allowlist_only(&mut self, enabled: bool) {
+        self.allowlist_only = enabled;
+    }
+
+    /// Enable/disable generation of `NAME_DEFAULT` initializer macros for structs deriving
+    /// `Default`. The initializer is `{0}` unless given with `#[ffi(default = "...")]`.
+    pub fn set_default_initializers(&mut self, enabled: bool) {
+        self.default_initializers = enabled;
+    }
+
+    /// Set how packed and `#[repr(align(N))]` structs are declared (`PackingStyle::Attribute` by
+    /// default).
+    pub fn set_packing_style(&mut self, style: PackingStyle) {
+        self.packing_style = style;
+    }
+
+    /// Set the parameter list of functions without arguments (`EmptyParams::Void` by default).
+    pub fn empty_param_style(&mut self, style: EmptyParams) {
+        self.empty_params = style;
+    }
+
+    /// Set whether the typedefs of enums name their tag (`EnumTagStyle::Named` by default).
+    pub fn enum_tag_style(&mut self, style: EnumTagStyle) {
+        self.enum_tag_style = style;
+    }
+
+    /// Enable/disable generation of a `static inline const char* Name_to_string(Name value)`
+    /// helper for each enum, returning the name of the variant (or `"<unknown>"`).
+    pub fn emit_enum_to_string(&mut self, enabled: bool) {
+        self.enum_to_string = enabled;
+    }
+
+    /// Document the values of enums for C code relying on them, e.g. to index arrays: enums without
+    /// explicit discriminants get a static assertion that they're contiguous from 0, and the
+    /// others a comment listing the value of each variant.
+    pub fn emit_enum_value_checks(&mut self, enabled: bool) {
+        self.enum_value_checks = enabled;
+    }
+
+    /// Enable/disable replacing enum discriminants which aren't integer literals by their value,
+    /// followed by a `/* rust: = EXPR */` comment showing the original expression.
+    ///
+    /// The discriminants may only refer to literals and the previous variants of the enum, or
+    /// the generation fails with `E_UNKNOWN_DISCRIMINANT`.
+    pub fn emit_discriminant_comments(&mut self, enabled: bool) {
+        self.discriminant_comments = enabled;
+    }
+
+    /// Enable/disable C23 enums with a fixed underlying type (e.g. `enum Name : int32_t`) for
+    /// enums with an integer representation like `#[repr(C, i32)]`.
+    pub fn c23_enums(&mut self, enabled: bool) {
+        self.c23_enums = enabled;
+    }
+
+    /// Generate a Windows module-definition file with the given name, exporting all the
+    /// generated functions.
+    pub fn emit_def_file(&mut self, name: &str) {
+        self.def_file = Some(name.to_string());
+    }
+
+    /// Generate a JSON file with the given name describing the fields of the generated structs.
+    ///
+    /// Sizes and field offsets are included when given with `#[ffi(size = N)]` on the struct
+    /// and `#[ffi(offset = N)]` on the fields.
+    pub fn set_layout_schema_path(&mut self, name: &str) {
+        self.layout_schema = Some(name.to_string());
+    }
+
+    /// Generate a CMake file with the given name listing the library name and the generated
+    /// headers, with the `install` commands for the headers.
+    pub fn emit_cmake(&mut self, name: &str) {
+        self.cmake_file = Some(name.to_string());
+    }
+
+    /// Generate a pkg-config file (`libname.pc`) with the given package name, version and linker
+    /// flags (`-llibname` if empty), for the headers installed into `include` like with
+    /// `emit_cmake`.
+    pub fn emit_pkg_config(&mut self, name: &str, version: &str, libs: &str) {
+        self.pkg_config = Some((name.to_string(), version.to_string(), libs.to_string()));
+    }
+
+    /// Check that every generated header compiles with the given C compiler (e.g. `cc`), run
+    /// with `-fsyntax-only` once the output is finalised. Its diagnostics are returned as an
+    /// error.
+    pub fn verify_with_compiler(&mut self, cc: &str) {
+        self.compiler = Some(cc.to_string());
+    }
+
+    /// Generate `#[no_mangle]` wrappers of the functions marked `#[ffi(wrap)]` into the Rust file
+    /// `file`, to be included with `include!` where the functions are in scope, and declare the
+    /// wrappers in the headers.
+    ///
+    /// The wrappers are named after the functions with an `_ffi` suffix, or the name given with
+    /// `#[ffi(wrap = "name")]`. They take string slices and slices as a pointer and a length
+    /// (`name` and `name_len`) and references as pointers, which must not be null.
+    pub fn rust_wrappers(&mut self, file: &str) {
+        self.rust_wrappers = Some((file.to_string(), String::new()));
+    }
+
+    /// Set how the top-level header includes the module headers (`IncludeStyle::Local` by
+    /// default).
+    pub fn include_style(&mut self, style: IncludeStyle) {
+        self.include_style = style;
+    }
+
+    /// Set a function used to map Rust types which have no built-in C equivalent.
+    ///
+    /// The function receives the Rust type name (including the module path if there is one) and
+    /// returns the C type to use, or `None` to fall back to the default behaviour.
+    pub fn set_type_resolver(&mut self, f: TypeResolver) {
+        self.context.type_resolver = Some(f);
+    }
+
+    /// Set a function renaming every generated identifier: the names of the types, functions,
+    /// statics, struct fields and enum constants (e.g. `Status_Ok`), and the references to them.
+    ///
+    /// The names of functions and statics are their symbols, so they're left alone when given
+    /// with `#[export_name = "..."]` (or `#[link_name = "..."]` in extern blocks) and otherwise have to match the exported symbols.
+    pub fn set_identifier_transform(&mut self, f: IdentifierTransform) {
+        self.context.identifier_transform = Some(f);
+    }
+
+    /// Load the C types of Rust types from a TOML table, e.g.
+    ///
+    /// ```toml
+    /// [types]
+    /// Handle = "handle_t"
+    /// "chrono::NaiveDate" = "int32_t"
+    /// ```
+    ///
+    /// Types in modules are given with their module path, like in the Rust code. The mappings
+    /// take precedence over the type resolver.
+    pub fn load_mappings_from_str(&mut self, toml: &str) -> Result<(), Error> {
+        let table = match toml.parse::<toml::Value>() {
+            Ok(toml::Value::Table(table)) => table,
+            Ok(_) => return Err(mapping_error("the mappings must be a table".to_string())),
+            Err(error) => {
+                return Err(mapping_error(format!(
+                    "could not parse the mappings: {}",
+                    error
+                )))
+            }
+        };
+
+        for (key, value) in table {
+            let types = match (&*key, value) {
+                ("types", toml::Value::Table(types)) => types,
+                ("types", _) => {
+                    return Err(mapping_error("`types` must be a table".to_string()));
+                }
+                (key, _) => {
+                    return Err(mapping_error(format!("unknown mapping table `{}`", key)));
+                }
+            };
+
+            for (rust_ty, c_ty) in types {
+                match c_ty {
+                    toml::Value::String(c_ty) => {
+                        self.context.type_mappings.insert(rust_ty, c_ty);
+                    }
+                    _ => {
+                        return Err(mapping_error(format!(
+                            "the C type of `{}` must be a string",
+                            rust_ty
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Header for the items of the module, or for its internal items when generating an item
+    /// marked with `#[ffi(internal)]`.
+    ///
+    /// Items marked with `#[ffi(group = "name")]` go into the header of a `name` module at the
+    /// root of the library instead (e.g. `{lib}/name.h`), whichever module they're declared in.
+    fn header_name(&self, module: &[String]) -> Result<String, Error> {
+        let group_module;
+        let module = match self.group {
+            Some(ref group) => {
+                group_module = vec!["ffi".to_string(), group.clone()];
+                &group_module[..]
+            }
+            None => module,
+        };
+        let header = match self.header_namer {
+            Some(ref namer) => namer(module, &self.lib_name),
+            None => {
+                // A stripped prefix stands for the library, like the `ffi` module does.
+                let module = match module.strip_prefix(&self.module_prefix[..]) {
+                    Some(rest) if !self.module_prefix.is_empty() => {
+                        let mut stripped = vec!["ffi".to_string()];
+                        stripped.extend_from_slice(rest);
+                        stripped
+                    }
+                    _ => module.to_vec(),
+                };
+                let header = header_name(&module, &self.lib_name, self.flat_layout)?;
+                if self.prefix_std_headers && is_std_header(&header) {
+                    prefix_file_name(&header, &self.lib_name)
+                } else {
+                    header
+                }
+            }
+        };
+        if self.internal {
+            Ok(format!("{}_internal.h", header.trim_end_matches(".h")))
+        } else {
+            Ok(header)
+        }
+    }
+
+    /// Start generating the item, keeping track of whether it belongs to the internal headers.
+    fn enter_item(&mut self, attrs: &[ast::Attribute]) {
+        self.internal = attrs.iter().any(|attr| check_ffi(attr, "internal"));
+        // Arguments can't have attributes, so their C types are given on the function with
+        // `#[ffi(c_type(arg = "type"))]`.
+        self.arg_c_types = attrs
+            .iter()
+            .flat_map(|attr| retrieve_ffi_pairs(attr, "c_type"))
+            .collect();
+        self.must_use = attrs.iter().any(|attr| attr.check_name("must_use"));
+        self.group = attrs
+            .iter()
+            .find_map(|attr| retrieve_ffi_value(attr, "group"));
+        self.fn_hints = FN_HINTS
+            .iter()
+            .filter(|&&(hint, _)| parse_attr(attrs, |attr| check_ffi(attr, hint), |_| None).0)
+            .map(|&(_, name)| name)
+            .collect();
+    }
+
+    /// Check whether the item has to be deferred because it's not explicitly exported.
+    fn is_deferred(&self, attrs: &[ast::Attribute]) -> bool {
+        self.allowlist_only
+            && !self.deferring
+            && !attrs.iter().any(|attr| check_ffi(attr, "export"))
+    }
+
+    /// Generate an item into a separate buffer, to be added to the output only if an exported
+    /// item depends on it.
+    fn defer<F>(&mut self, item: &ast::Item, module: &[String], generate: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self, &mut Outputs) -> Result<(), Error>,
+    {
+        let deps = mem::take(&mut self.deps);
+        let mut outputs = Outputs::new();
+
+        self.deferring = true;
+        let res = generate(self, &mut outputs);
+        self.deferring = false;
+
+        let deferred = Deferred {
+            outputs,
+            deps: mem::replace(&mut self.deps, deps),
+        };
+        res?;
+
+        let name = self.context.ident(&item.ident.name.as_str());
+        let _ = self.deferred.insert((module.to_vec(), name), deferred);
+        Ok(())
+    }
+
+    /// Add the deferred items which exported items depend on (directly or transitively).
+    ///
+    /// The whole set of needed items is collected first, then added before the exported items
+    /// with dependencies declared before the items using them.
+    ///
+    /// Dependencies are names, which refer to the item of the same module (i.e. going into the
+    /// same header) if there's one, and to the items of that name in other modules otherwise.
+    fn add_deferred(&mut self, outputs: &mut Outputs) {
+        let mut needed = BTreeMap::new();
+        let mut pending: Vec<(String, String)> = header_deps(&self.deps);
+        while let Some((header, name)) = pending.pop() {
+            let candidates: Vec<DeferredKey> = self
+                .deferred
+                .iter()
+                .filter(|&(key, _)| key.1 == name)
+                .map(|(key, _)| key.clone())
+                .collect();
+            let local = candidates
+                .iter()
+                .find(|&key| self.deferred[key].outputs.contains_key(&header))
+                .cloned();
+            let keys = match local {
+                Some(key) => vec![key],
+                None => candidates,
+            };
+
+            for key in keys {
+                if let Some(deferred) = self.deferred.remove(&key) {
+                    pending.extend(header_deps(&deferred.deps));
+                    let _ = needed.insert(key, deferred);
+                }
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::new();
+        for key in needed.keys() {
+            deferred_order(key, &needed, &mut visited, &mut order);
+        }
+
+        let mut code = BTreeMap::new();
+        for key in order {
+            let deferred = needed.remove(&key).expect("ordered items are needed items");
+            for (header, item) in deferred.outputs {
+                code.entry(header)
+                    .or_insert_with(String::new)
+                    .push_str(&item);
+            }
+            for (header, deps) in deferred.deps {
+                self.deps.entry(header).or_default().extend(deps);
+            }
+        }
+
+        // Dependencies have to be declared before they're used.
+        for (header, code) in code {
+            let existing = outputs.remove(&header).unwrap_or_default();
+            let _ = outputs.insert(header, format!("{}{}", code, existing));
+        }
+    }
+
+    /// Warn about the struct fields using types which were never declared, as the C compiler
+    /// would reject them. Fields of the deferred items which didn't get exported are ignored.
+    fn check_field_types(&mut self) {
+        for field_type in mem::take(&mut self.field_types) {
+            if self.decls.contains_key(&field_type.name)
+                || self
+                    .deferred
+                    .contains_key(&(field_type.module.clone(), field_type.strukt.clone()))
+            {
+                continue;
+            }
+
+            self.warn(
+                ErrorCode::UndeclaredType,
+                Some(field_type.span),
+                format!(
+                    "field `{}` of `{}` uses the undeclared type `{}`",
+                    field_type.field, field_type.strukt, field_type.name
+                ),
+            );
+        }
+    }
+
+    /// Warn about the headers named like standard C headers, which they could shadow when
+    /// included with `<...>` or on case-insensitive file systems.
+    fn check_header_names(&mut self, outputs: &Outputs) {
+        let clashes: Vec<String> = outputs
+            .keys()
+            .filter(|header| is_std_header(header))
+            .cloned()
+            .collect();
+
+        for header in clashes {
+            self.warn(
+                ErrorCode::StdHeaderName,
+                None,
+                format!(
+                    "the header `{}` has the name of a standard C header, which it may shadow; \
+                     rename the module or enable `prefix_std_headers`",
+                    header
+                ),
+            );
+        }
+    }
+
+    /// Record a note about an item which isn't generated, if enabled with `trace_skipped_items`.
+    fn trace_skipped(&mut self, item: &ast::Item, kind: &str, reason: &str) {
+        if !self.trace_skipped_items {
+            return;
+        }
+
+        let note = Error {
+            level: Level::Note,
+            code: None,
+            span: Some(item.span),
+            message: format!(
+                "the {} `{}` is skipped, as {}",
+                kind,
+                item.ident.name.as_str(),
+                reason
+            ),
+        };
+        self.notes.push(note);
+    }
+
+    /// Report a warning, which is an error in strict mode.
+    fn warn(&mut self, code: ErrorCode, span: Option<codemap::Span>, message: String) {
+        let warning = Error {
+            level: if self.strict {
+                Level::Error
+            } else {
+                Level::Warning
+            },
+            code: Some(code),
+            span,
+            message,
+        };
+        self.warnings.push(warning);
+    }
+
     fn add_dependencies(&mut self, module: &[String], cty: &CType) -> Result<(), Error> {
+        let includes = cty.std_includes();
+        if !includes.is_empty() {
+            let header = self.header_name(module)?;
+            self.std_includes
+                .entry(header)
+                .or_default()
+                .extend(includes);
+        }
+
+        self.uses_pointer_sized |= cty.is_pointer_sized();
+
+        let extensions = cty.float_extensions();
+        if !extensions.is_empty() {
+            let header = self.header_name(module)?;
+            self.float_extensions
+                .entry(header)
+                .or_default()
+                .extend(extensions);
+        }
+
         let deps = cty.dependencies();
 
         if !deps.is_empty() {
-            let header = header_name(module, &self.lib_name)?;
+            let header = self.header_name(module)?;
 
             match self.deps.entry(header) {
                 Entry::Occupied(o) => o.into_mut().extend(deps.into_iter()),
@@ -75,17 +993,322 @@ impl LangC {
         module: &[String],
         outputs: &mut Outputs,
     ) -> Result<(), Error> {
-        let header = header_name(module, &self.lib_name)?;
+        let header = self.header_name(module)?;
+        if self.internal {
+            let _ = self.internal_headers.insert(header.clone());
+        }
         append_output(buffer, &header, outputs);
         Ok(())
     }
 
+    /// Start the declaration of a struct or enum, which is either a typedef or only a tag.
+    fn declare_tag(&mut self, kind: &'static str, name: &str) -> String {
+        if self.typedefs {
+            format!("typedef {} {}", kind, name)
+        } else {
+            self.context.tags.insert(name.to_string(), kind);
+            format!("{} {}", kind, name)
+        }
+    }
+
+    /// Name given to a struct or enum by its typedef, to end its declaration with.
+    fn typedef_name(&self, name: &str) -> String {
+        if self.typedefs {
+            format!(" {}", name)
+        } else {
+            String::new()
+        }
+    }
+
+    /// C type given with `#[ffi(c_type = "...")]`, which depends on the generated type it names
+    /// (if any).
+    fn custom_c_type(
+        &mut self,
+        c_type: String,
+        name: &str,
+        module: &[String],
+    ) -> Result<CTypeNamed, Error> {
+        if c_type.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            self.deps
+                .entry(self.header_name(module)?)
+                .or_default()
+                .push(c_type.clone());
+        }
+        Ok(CTypeNamed(name.to_string(), CType::Custom(c_type)))
+    }
+
+    /// Expand an enum with data-carrying variants into a tag enum and a tagged union, matching
+    /// the layout of `#[repr(C)]` Rust enums.
+    ///
+    /// Unit variants have no payload, single-field variants use the field type directly and
+    /// other variants are anonymous structs (tuple fields are named `_0`, `_1`, ...).
+    fn tagged_union(
+        &mut self,
+        item: &ast::Item,
+        definition: &ast::EnumDef,
+        mut buffer: String,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let rust_name = item.ident.name.as_str();
+        let name = self.context.ident(&rust_name);
+        let tag = self.context.ident(&format!("{}_Tag", rust_name));
+
+        let mut tag_enum = format!("{} {{\n", self.declare_tag("enum", &tag));
+        let decl = self.declare_tag("struct", &name);
+        let mut payload = String::new();
+        for var in &definition.variants {
+            let var_name = var.node.name.name.as_str();
+            let (_, docs) = parse_attr(
+                &var.node.attrs,
+                |_| true,
+                |attr| retrieve_docstring(attr, "\t"),
+            );
+            tag_enum.push_str(&docs);
+            let constant = self.context.ident(&format!("{}_{}", rust_name, var_name));
+            tag_enum.push_str(&format!("\t{},\n", constant));
+
+            let fields = var.node.data.fields();
+            let mut members = Vec::new();
+            for (index, field) in fields.iter().enumerate() {
+                let field_name = match field.ident {
+                    Some(ident) => self.context.ident(&ident.name.as_str()),
+                    None if fields.len() == 1 => var_name.to_string(),
+                    None => format!("_{}", index),
+                };
+                let ty = field_rust_to_c(&field.ty, &field_name, &self.context)?;
+                self.add_dependencies(module, &ty.1)?;
+                members.push(ty);
+            }
+
+            match (members.len(), var.node.data.is_struct()) {
+                (0, _) => (),
+                (1, false) => payload.push_str(&format!("\t\t{};\n", members[0])),
+                _ => {
+                    payload.push_str("\t\tstruct {\n");
+                    for member in &members {
+                        payload.push_str(&format!("\t\t\t{};\n", member));
+                    }
+                    payload.push_str(&format!("\t\t}} {};\n", var_name));
+                }
+            }
+        }
+        tag_enum.push_str(&format!("}}{};\n\n", self.typedef_name(&tag)));
+
+        // The tag has the integer type given in the `repr`, if any.
+        let tag_ty = match parse_repr(&item.attrs).int {
+            Some(int) => rust_ty_to_c(int, &self.context).to_string(),
+            None => self.context.named(tag.clone()).to_string(),
+        };
+
+        buffer.push_str(&format!(
+            "{} {{\n\t{} tag;\n\tunion {{\n{}\t}} payload;\n}}{};\n\n",
+            decl,
+            tag_ty,
+            payload,
+            self.typedef_name(&name)
+        ));
+
+        self.append_to_header(format!("{}{}", tag_enum, buffer), module, outputs)
+    }
+
+    /// Generate the typedefs of the instantiations of a generic alias, substituting the concrete
+    /// types for the type parameters.
+    fn monomorphize_alias(
+        &mut self,
+        item: &ast::Item,
+        ty: &P<ast::Ty>,
+        generics: &ast::Generics,
+        docs: &str,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let alias = item.ident.name.as_str();
+        let mut generated = false;
+
+        for instantiation in self.alias_instantiations.clone() {
+            let args = match instantiation.node {
+                ast::TyKind::Path(None, ref path) if path.segments.len() == 1 => {
+                    let segment = &path.segments[0];
+                    if segment.identifier.name != item.ident.name {
+                        continue;
+                    }
+                    match segment.parameters {
+                        Some(ref params) => match **params {
+                            ast::PathParameters::AngleBracketed(ref data) => data.types.clone(),
+                            _ => continue,
+                        },
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            if args.len() != generics.ty_params.len() {
+                continue;
+            }
+
+            let params = generics
+                .ty_params
+                .iter()
+                .map(|param| param.ident.name)
+                .zip(args.iter().cloned())
+                .collect();
+            let concrete = substitute_params(ty.clone(), &params);
+
+            let suffix: Vec<_> = args.iter().map(|arg| instantiation_suffix(arg)).collect();
+            let name = self
+                .context
+                .ident(&format!("{}_{}", alias, suffix.join("_")));
+
+            let new_type = rust_to_c(&concrete, &name, &self.context)?;
+            self.add_dependencies(module, &new_type.1)?;
+            self.append_to_header(
+                format!("{}typedef {};\n\n", docs, new_type),
+                module,
+                outputs,
+            )?;
+            self.decls.insert(name, self.header_name(module)?);
+            generated = true;
+        }
+
+        if !generated {
+            self.warn(
+                ErrorCode::GenericAlias,
+                Some(item.span),
+                format!(
+                    "the generic type alias `{}` is skipped, as it has no instantiation",
+                    alias
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Replace a byte array with its shared typedef, declaring the typedef in the current
+    /// header the first time it's used.
+    fn hoist_byte_array(
+        &mut self,
+        cty: CType,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<CType, Error> {
+        let len = match cty {
+            CType::Array(ref inner, ref len)
+                if **inner == CType::Native("uint8_t") && len.parse::<u64>().is_ok() =>
+            {
+                len.clone()
+            }
+            _ => return Ok(cty),
+        };
+
+        let name = format!("{}_bytes{}", sanitise_id(&self.lib_name), len);
+        if !self.decls.contains_key(&name) {
+            let typedef = format!("typedef uint8_t {}[{}];\n\n", name, len);
+            self.append_to_header(typedef, module, outputs)?;
+            self.decls.insert(name.clone(), self.header_name(module)?);
+        }
+
+        Ok(CType::Mapping(name))
+    }
+
+    /// Generate the Rust wrapper of a function marked `#[ffi(wrap)]`, and declare it in the header
+    /// like any other `#[no_mangle]` function, with the attributes of the wrapped function.
+    fn wrap_fn(
+        &mut self,
+        item: &ast::Item,
+        docs: &str,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let name = item
+            .attrs
+            .iter()
+            .find_map(|attr| retrieve_ffi_value(attr, "wrap"))
+            .unwrap_or_else(|| format!("{}_ffi", item.ident.name.as_str()));
+        let source = wrapper::rust_wrapper(item, &name)?;
+
+        let sess = ParseSess::new(FilePathMapping::empty());
+        let wrapper = {
+            let mut parser = new_parser_from_source_str(&sess, name.clone(), source.clone());
+            match parser.parse_item() {
+                Ok(Some(wrapper)) => wrapper,
+                Ok(None) => unreachable!("wrappers are functions"),
+                Err(mut diagnostic) => {
+                    diagnostic.cancel();
+                    return Err(Error {
+                        level: Level::Error,
+                        code: Some(ErrorCode::Parse),
+                        span: Some(item.span),
+                        message: format!("could not parse the wrapper `{}`", name),
+                    });
+                }
+            }
+        };
+
+        let fn_decl = match wrapper.node {
+            ast::ItemKind::Fn(ref fn_decl, ..) => fn_decl,
+            _ => unreachable!("wrappers are functions"),
+        };
+
+        if let Some((_, ref mut wrappers)) = self.rust_wrappers {
+            wrappers.push_str(&source);
+            wrappers.push('\n');
+        }
+        let name = self.context.ident(&name);
+        self.declare_fn(item, wrapper.ident, fn_decl, name, docs, module, outputs)
+    }
+
+    /// Declare the function `fn_decl` exported as `name`, with the `#[ffi(...)]` hints of `item`.
+    #[allow(clippy::too_many_arguments)]
+    fn declare_fn(
+        &mut self,
+        item: &ast::Item,
+        ident: ast::Ident,
+        fn_decl: &ast::FnDecl,
+        name: String,
+        docs: &str,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let noreturn = item.attrs.iter().any(|attr| check_ffi(attr, "noreturn"));
+        let docs = format!("{}{}", docs, self.source_annotation(ident, fn_decl));
+        self.transform_native_fn(fn_decl, &docs, &name, noreturn, module, outputs)?;
+        self.exports.push(name);
+
+        Ok(())
+    }
+
+    /// Comment with the Rust signature of a function, if enabled with `annotate_source`.
+    fn source_annotation(&self, ident: ast::Ident, fn_decl: &ast::FnDecl) -> String {
+        if !self.annotate_source {
+            return String::new();
+        }
+
+        let args: Vec<_> = fn_decl.inputs.iter().map(pprust::arg_to_string).collect();
+        let output = match fn_decl.output {
+            ast::FunctionRetTy::Default(..) => String::new(),
+            ast::FunctionRetTy::Ty(ref ty) => format!(" -> {}", pprust::ty_to_string(ty)),
+        };
+        format!(
+            "/* rust: pub fn {}({}){} */\n",
+            ident.name.as_str(),
+            args.join(", "),
+            output
+        )
+    }
+
     /// Transform a Rust FFI function into a C function decl
+    ///
+    /// Functions returning `!` are only accepted when `noreturn` is set, in which case they're
+    /// declared as `void` functions with the `BINDGEN_NORETURN` specifier, which stands for the
+    /// `noreturn` specifier or attribute of the compiler.
     pub fn transform_native_fn(
         &mut self,
         fn_decl: &ast::FnDecl,
         docs: &str,
         name: &str,
+        noreturn: bool,
         module: &[String],
         outputs: &mut Outputs,
     ) -> Result<(), Error> {
@@ -98,50 +1321,139 @@ impl LangC {
         // Arguments
         for arg in &fn_args {
             let arg_name = pprust::pat_to_string(&*arg.pat);
-            let c_ty = rust_to_c(&arg.ty, &arg_name)?;
+            let c_ty = match self.arg_c_types.get(&arg_name).cloned() {
+                Some(c_type) => self.custom_c_type(c_type, &arg_name, module)?,
+                None => rust_to_c(&arg.ty, &arg_name, &self.context)?,
+            };
             self.add_dependencies(module, &c_ty.1)?;
             args.push(c_ty);
         }
 
-        let buf = format!(
-            "{}({})",
-            name,
-            if args.is_empty() {
-                String::from("void")
-            } else {
-                args.into_iter()
-                    .map(|cty| format!("{}", cty))
-                    .collect::<Vec<_>>()
-                    .join(", ")
+        // Tuples can't be returned by value in C, so they're returned through out-parameters.
+        let tuple_elems = match fn_decl.output {
+            ast::FunctionRetTy::Ty(ref ty) => match ty.node {
+                ast::TyKind::Tup(ref elems) if !elems.is_empty() => Some((ty.span, elems)),
+                _ => None,
+            },
+            ast::FunctionRetTy::Default(..) => None,
+        };
+        let mut docs = docs.to_string();
+        if let Some((span, elems)) = tuple_elems {
+            if !self.tuple_out_params {
+                return Err(Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::TupleReturn),
+                    span: Some(span),
+                    message: "bindgen can not return tuples by value, enable `tuple_out_params` \
+                              to return them through out-parameters"
+                        .into(),
+                });
             }
-        );
+
+            let mut out_params = Vec::new();
+            for (index, elem) in elems.iter().enumerate() {
+                let out_name = format!("out{}", index);
+                let c_ty = CType::Ptr(
+                    Box::new(anon_rust_to_c(elem, &self.context)?),
+                    CPtrType::Mutable,
+                );
+                self.add_dependencies(module, &c_ty)?;
+                out_params.push(format!("`{}`", out_name));
+                args.push(CTypeNamed(out_name, c_ty));
+            }
+            docs.push_str(&format!(
+                "/* The result is returned through {}. */\n",
+                out_params.join(", ")
+            ));
+        }
+
+        let args = if args.is_empty() {
+            match self.empty_params {
+                EmptyParams::Void => vec![String::from("void")],
+                EmptyParams::Empty => Vec::new(),
+            }
+        } else {
+            args.into_iter().map(|cty| format!("{}", cty)).collect()
+        };
+        let buf = format!("{}({})", name, args.join(", "));
 
         // Generate return type
         let output_type = &fn_decl.output;
+        let mut has_result = true;
         let full_declaration = match *output_type {
             ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
-                return Err(Error {
-                    level: Level::Error,
-                    span: Some(ty.span),
-                    message: "panics across a C boundary are naughty!".into(),
-                });
+                if !noreturn {
+                    return Err(Error {
+                        level: Level::Error,
+                        code: Some(ErrorCode::NeverReturn),
+                        span: Some(ty.span),
+                        message: "panics across a C boundary are naughty! Mark functions \
+                                  which really never return with `#[ffi(noreturn)]`"
+                            .into(),
+                    });
+                }
+                has_result = false;
+                let header = self.header_name(module)?;
+                let _ = self.fn_hint_headers.insert(header);
+                format!("BINDGEN_NORETURN void {}", buf)
             }
-            ast::FunctionRetTy::Default(..) => format!("void {}", buf),
-            ast::FunctionRetTy::Ty(ref ty) => {
-                let c_ty = rust_to_c(&*ty, &buf)?;
+            // Returned function pointers have to wrap the rest of the declaration.
+            ast::FunctionRetTy::Ty(ref ty) if matches!(ty.node, ast::TyKind::BareFn(..)) => {
+                let c_ty = rust_to_c(ty, &buf, &self.context)?;
                 self.add_dependencies(module, &c_ty.1)?;
                 format!("{}", c_ty)
             }
+            _ if tuple_elems.is_some() => {
+                has_result = false;
+                format!("void {}", buf)
+            }
+            _ => {
+                let c_ty = ret_ty_to_c(output_type, &self.context)?;
+                self.add_dependencies(module, &c_ty)?;
+                has_result = c_ty != CType::Void;
+                format!("{} {}", c_ty, buf)
+            }
         };
 
-        let mut output = String::new();
-        output.push_str(docs);
-        output.push_str(&full_declaration);
-        output.push_str(";\n\n");
+        let mut declaration = String::new();
+        if self.must_use && has_result {
+            if self.nodiscard && !self.c89 {
+                declaration.push_str("[[nodiscard]] ");
+                declaration.push_str(&full_declaration);
+            } else {
+                declaration.push_str(&full_declaration);
+                declaration.push_str(" __attribute__((warn_unused_result))");
+            }
+        } else {
+            declaration.push_str(&full_declaration);
+        }
+        if !self.fn_hints.is_empty() {
+            let header = self.header_name(module)?;
+            let _ = self.fn_hint_headers.insert(header);
+            for hint in &self.fn_hints {
+                declaration.push(' ');
+                declaration.push_str(hint);
+            }
+        }
+        declaration.push(';');
+
+        // Wrap long declarations, putting each parameter on its own line.
+        match self.max_line_width {
+            Some(width) if declaration.len() > width && args.len() > 1 => {
+                let column = declaration.find(&buf).unwrap_or(0) + name.len() + 1;
+                let separator = format!(",\n{}", " ".repeat(column));
+                let wrapped = format!("{}({})", name, args.join(&separator));
+                declaration = declaration.replacen(&buf, &wrapped, 1);
+            }
+            _ => (),
+        }
 
-        append_output(output, &header_name(module, &self.lib_name)?, outputs);
+        let mut output = String::new();
+        output.push_str(&docs);
+        output.push_str(&declaration);
+        output.push_str("\n\n");
 
-        Ok(())
+        self.append_to_header(output, module, outputs)
     }
 }
 
@@ -152,6 +1464,18 @@ impl Default for LangC {
 }
 
 impl Lang for LangC {
+    /// Constants aren't declared in C, but integer constants are remembered for the lengths of
+    /// the arrays using them.
+    fn parse_const(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        self.context.define_const(item);
+        Ok(())
+    }
+
     /// Convert `pub type A = B;` into `typedef B A;`.
     ///
     /// Aborts if A is generic.
@@ -161,35 +1485,58 @@ impl Lang for LangC {
         module: &[String],
         outputs: &mut Outputs,
     ) -> Result<(), Error> {
+        self.enter_item(&item.attrs);
+
+        if self.is_deferred(&item.attrs) {
+            return self.defer(item, module, |lang, outputs| {
+                lang.parse_ty(item, module, outputs)
+            });
+        }
+
         let (_, docs) = parse_attr(&item.attrs, |_| true, |attr| retrieve_docstring(attr, ""));
 
         let mut buffer = String::new();
         buffer.push_str(&docs);
 
-        let name = item.ident.name.as_str();
+        let name = self.context.ident(&item.ident.name.as_str());
         let new_type = match item.node {
             ast::ItemKind::Ty(ref ty, ref generics) => {
-                // Can not yet convert generics.
+                // Generic aliases are only generated for the instantiations given by the user.
                 if generics.is_parameterized() {
-                    return Ok(());
+                    return self.monomorphize_alias(item, ty, generics, &docs, module, outputs);
+                }
+
+                // `typedef void Name;` would only be usable as `Name*`, which isn't what the
+                // alias means in Rust.
+                if let ast::TyKind::Tup(ref elems) = ty.node {
+                    if elems.is_empty() {
+                        return Err(Error {
+                            level: Level::Error,
+                            code: Some(ErrorCode::UnitAlias),
+                            span: Some(item.span),
+                            message: "bindgen can not handle aliases of the unit type `()`".into(),
+                        });
+                    }
                 }
 
-                rust_to_c(&*ty, &name)?
+                rust_to_c(&*ty, &name, &self.context)?
             }
             _ => {
                 return Err(Error {
                     level: Level::Bug,
+                    code: Some(ErrorCode::WrongItem),
                     span: Some(item.span),
                     message: "`parse_ty` called on wrong `Item_`".into(),
                 });
             }
         };
 
+        self.add_dependencies(module, &new_type.1)?;
         buffer.push_str(&format!("typedef {};\n\n", new_type));
         self.append_to_header(buffer, module, outputs)?;
 
         self.decls
-            .insert(name.to_string(), header_name(module, &self.lib_name)?);
+            .insert(name.to_string(), self.header_name(module)?);
 
         Ok(())
     }
@@ -206,36 +1553,93 @@ impl Lang for LangC {
         module: &[String],
         outputs: &mut Outputs,
     ) -> Result<(), Error> {
+        self.enter_item(&item.attrs);
+
         let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, |attr| {
             retrieve_docstring(attr, "")
         });
         // If it's not #[repr(C)] then it can't be called from C.
         if !repr_c {
+            self.trace_skipped(item, "enum", "it's not `#[repr(C)]`");
             return Ok(());
         }
 
+        if self.is_deferred(&item.attrs) {
+            return self.defer(item, module, |lang, outputs| {
+                lang.parse_enum(item, module, outputs)
+            });
+        }
+
         let mut buffer = String::new();
         buffer.push_str(&docs);
 
-        let name = item.ident.name.as_str();
-        buffer.push_str(&format!("typedef enum {} {{\n", name));
+        let rust_name = item.ident.name.as_str();
+        let name = self.context.ident(&rust_name);
+        let mut variants = Vec::new();
+        let mut constants = Vec::new();
+        let mut messages = Vec::new();
+        let mut discriminants = Vec::new();
         if let ast::ItemKind::Enum(ref definition, ref generics) = item.node {
             if generics.is_parameterized() {
-                return Err(Error {
-                    level: Level::Error,
-                    span: Some(item.span),
-                    message: "bindgen can not handle parameterized `#[repr(C)]` enums".into(),
-                });
+                return Err(common::generic_enum_error(item.span));
+            }
+
+            // Empty enums are not valid C, but they're commonly used as opaque handles in Rust,
+            // so represent them by an incomplete struct type instead.
+            if definition.variants.is_empty() {
+                buffer.push_str("/* Opaque handle type (uninhabited in Rust) */\n");
+                let decl = self.declare_tag("struct", &name);
+                buffer.push_str(&format!("{}{};\n\n", decl, self.typedef_name(&name)));
+                self.append_to_header(buffer, module, outputs)?;
+
+                self.decls
+                    .insert(name.to_string(), self.header_name(module)?);
+
+                return Ok(());
+            }
+
+            if self.tagged_unions
+                && definition
+                    .variants
+                    .iter()
+                    .any(|var| !var.node.data.is_unit())
+            {
+                self.tagged_union(item, definition, buffer, module, outputs)?;
+                self.decls
+                    .insert(name.to_string(), self.header_name(module)?);
+                return Ok(());
             }
 
-            for var in &definition.variants {
+            let decl = match self.enum_tag_style {
+                EnumTagStyle::Anonymous if self.typedefs => String::from("typedef enum"),
+                _ => self.declare_tag("enum", &name),
+            };
+            buffer.push_str(&decl);
+            match parse_repr(&item.attrs).int {
+                Some(int) if self.c23_enums => {
+                    buffer.push_str(&format!(" : {}", rust_ty_to_c(int, &self.context)));
+                }
+                _ => (),
+            }
+            buffer.push_str(" {\n");
+
+            // The values of the commented discriminants have to be kept, or C would renumber them.
+            let values = if self.discriminant_comments
+                && definition
+                    .variants
+                    .iter()
+                    .all(|var| var.node.data.is_unit())
+            {
+                common::discriminants(&rust_name, definition)?
+            } else {
+                Vec::new()
+            };
+
+            for (index, var) in definition.variants.iter().enumerate() {
+                variants.push(var.node.name.name.as_str().to_string());
+
                 if !var.node.data.is_unit() {
-                    return Err(Error {
-                        level: Level::Error,
-                        span: Some(var.span),
-                        message: "bindgen can not handle `#[repr(C)]` enums with non-unit variants"
-                            .into(),
-                    });
+                    return Err(common::data_enum_error(var.span));
                 }
 
                 let (_, docs) = parse_attr(
@@ -245,19 +1649,70 @@ impl Lang for LangC {
                 );
                 buffer.push_str(&docs);
 
-                buffer.push_str(&format!("\t{}_{},\n", name, pprust::variant_to_string(var)));
+                let var_name = var.node.name.name.as_str().to_string();
+                let constant = self.context.ident(&format!("{}_{}", rust_name, var_name));
+                let mut disr_expr = var
+                    .node
+                    .disr_expr
+                    .as_ref()
+                    .map(|expr| pprust::expr_to_string(expr));
+                match var.node.disr_expr {
+                    Some(ref expr) if self.discriminant_comments && !is_int_literal(expr) => {
+                        let value = values[index].to_string();
+                        buffer.push_str(&format!(
+                            "\t{} = {}, /* rust: = {} */\n",
+                            constant,
+                            value,
+                            pprust::expr_to_string(expr)
+                        ));
+                        disr_expr = Some(value);
+                    }
+                    Some(ref expr) => buffer.push_str(&format!(
+                        "\t{} = {},\n",
+                        constant,
+                        pprust::expr_to_string(expr)
+                    )),
+                    None => buffer.push_str(&format!("\t{},\n", constant)),
+                }
+                discriminants.push(disr_expr);
+                constants.push(constant);
+
+                messages.push(doc_message(&var.node.attrs).unwrap_or_else(|| var_name.clone()));
             }
         } else {
             return Err(Error {
                 level: Level::Bug,
+                code: Some(ErrorCode::WrongItem),
                 span: Some(item.span),
                 message: "`parse_enum` called on wrong `Item_`".into(),
             });
         }
 
-        buffer.push_str(&format!("}} {};\n\n", name));
+        buffer.push_str(&format!("}}{};\n\n", self.typedef_name(&name)));
+
+        if self.enum_value_checks {
+            buffer.push_str(&enum_values(&name, &constants, &discriminants, !self.c89));
+        }
+
+        if self.enum_to_string {
+            let ty = self.context.named(name.clone());
+            buffer.push_str(&enum_to_string(&name, &ty, &constants, &variants));
+        }
+
+        // Error code enums get a `strerror` function, with the docs of the variants as messages.
+        if item.attrs.iter().any(|attr| check_ffi(attr, "error_codes")) {
+            buffer.push_str(&strerror(
+                &sanitise_id(&self.lib_name),
+                &constants,
+                &messages,
+            ));
+        }
+
         self.append_to_header(buffer, module, outputs)?;
 
+        self.decls
+            .insert(name.to_string(), self.header_name(module)?);
+
         Ok(())
     }
 
@@ -273,27 +1728,117 @@ impl Lang for LangC {
         module: &[String],
         outputs: &mut Outputs,
     ) -> Result<(), Error> {
+        self.enter_item(&item.attrs);
+
         let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, |attr| {
             retrieve_docstring(attr, "")
         });
+        let repr = parse_repr(&item.attrs);
+        let transparent =
+            repr.transparent || item.attrs.iter().any(|attr| check_ffi(attr, "transparent"));
         // If it's not #[repr(C)] then it can't be called from C.
-        if !repr_c {
+        if !repr_c && !transparent {
+            self.trace_skipped(item, "struct", "it's not `#[repr(C)]`");
+            return Ok(());
+        }
+
+        // Transparent structs which can't collapse into their field are only exported when
+        // they're also `#[repr(C)]`, as regular structs.
+        let transparent_field = if transparent {
+            transparent_field(item)
+        } else {
+            None
+        };
+        if transparent_field.is_none() && !repr_c {
+            self.trace_skipped(
+                item,
+                "struct",
+                "it's transparent but generic or without exactly one non-zero-sized field",
+            );
             return Ok(());
         }
 
+        if self.is_deferred(&item.attrs) {
+            return self.defer(item, module, |lang, outputs| {
+                lang.parse_struct(item, module, outputs)
+            });
+        }
+
         let mut buffer = String::new();
         buffer.push_str(&docs);
 
-        let name = item.ident.name.as_str();
-        buffer.push_str(&format!("typedef struct {}", name));
+        // Fields may be added to non-exhaustive structs, changing their size.
+        let (non_exhaustive, _) = parse_attr(
+            &item.attrs,
+            |attr| attr.check_name("non_exhaustive"),
+            |_| None,
+        );
+        if non_exhaustive {
+            buffer.push_str(
+                "/* Non-exhaustive: fields may be added in later versions, so don't allocate it \
+                 on the stack or rely on its `sizeof`. */\n",
+            );
+        }
+
+        let name = self.context.ident(&item.ident.name.as_str());
+
+        // Packed structs must be packed on the C side too, or the field offsets won't match.
+        let pragma_pack = match repr.packed {
+            Some(1) if self.packing_style == PackingStyle::Attribute => None,
+            packed => packed,
+        };
+
+        let mut fields = Vec::new();
+        // Offset of the end of the previous field, while it's known.
+        let mut end = Some(0u64);
 
         if let ast::ItemKind::Struct(ref variants, ref generics) = item.node {
+            if let Some(error) = generic_array_len_error(variants, generics) {
+                return Err(error);
+            }
+
             if generics.is_parameterized() {
-                return Err(Error {
-                    level: Level::Error,
-                    span: Some(item.span),
-                    message: "bindgen can not handle parameterized `#[repr(C)]` structs".into(),
-                });
+                return Err(common::generic_struct_error(item.span));
+            }
+
+            // Single-field transparent structs collapse into a typedef of the field type.
+            if let Some(field) = transparent_field {
+                let ty = field_rust_to_c(&field.ty, &name, &self.context)?;
+                self.add_dependencies(module, &ty.1)?;
+                buffer.push_str(&format!("typedef {};\n\n", ty));
+                self.append_to_header(buffer, module, outputs)?;
+
+                self.decls
+                    .insert(name.to_string(), self.header_name(module)?);
+
+                return Ok(());
+            }
+
+            if let Some(packed) = pragma_pack {
+                buffer.push_str(&format!("#pragma pack(push, {})\n", packed));
+            }
+
+            // Over-aligned structs must be aligned on the C side too, or their size won't match.
+            let attribute = match (repr.align, self.packing_style) {
+                (Some(align), PackingStyle::Attribute) => {
+                    Some(format!("__attribute__((aligned({})))", align))
+                }
+                (Some(align), PackingStyle::Pragma) => {
+                    Some(format!("__declspec(align({}))", align))
+                }
+                (None, _) if repr.packed.is_some() && pragma_pack.is_none() => {
+                    Some("__attribute__((packed))".to_string())
+                }
+                (None, _) => None,
+            };
+
+            let decl = self.declare_tag("struct", &name);
+            // The attribute goes between the keyword and the name.
+            match attribute {
+                Some(attribute) => {
+                    buffer.push_str(&decl.replacen("struct ", &format!("struct {} ", attribute), 1))
+                }
+                None => buffer.push_str(&decl),
             }
 
             if variants.is_struct() {
@@ -308,39 +1853,238 @@ impl Lang for LangC {
                     buffer.push_str(&docs);
 
                     let name = match field.ident {
-                        Some(name) => name.name.as_str(),
+                        Some(name) => self.context.ident(&name.name.as_str()),
                         None => unreachable!("a tuple struct snuck through"),
                     };
 
-                    let ty = rust_to_c(&*field.ty, &name)?;
+                    let c_type = field
+                        .attrs
+                        .iter()
+                        .filter_map(|attr| retrieve_ffi_value(attr, "c_type"))
+                        .next();
+                    if maybe_uninit_inner(&field.ty).is_some() {
+                        buffer.push_str("\t/* May be uninitialized (`MaybeUninit`). */\n");
+                    }
+
+                    let mut ty = match c_type {
+                        Some(c_type) => self.custom_c_type(c_type, &name, module)?,
+                        None => field_rust_to_c(&*field.ty, &name, &self.context)?,
+                    };
+                    if self.byte_array_typedefs {
+                        ty.1 = self.hoist_byte_array(ty.1, module, outputs)?;
+                    }
+                    if ty.1.is_atomic() {
+                        buffer.push_str("\t/* Atomic: use atomic or volatile access. */\n");
+                    }
+
+                    // The qualifier follows the type, like `const`, so that it applies to the
+                    // field itself rather than to the pointee of pointers.
+                    if field.attrs.iter().any(|attr| check_ffi(attr, "volatile")) {
+                        match ty.1 {
+                            CType::Void | CType::Array(..) | CType::FnDecl { .. } => {
+                                return Err(Error {
+                                    level: Level::Error,
+                                    code: Some(ErrorCode::InvalidVolatile),
+                                    span: Some(field.span),
+                                    message: format!(
+                                        "volatile field `{}` must be a scalar or a pointer",
+                                        name
+                                    ),
+                                });
+                            }
+                            _ => ty.0 = format!("volatile {}", ty.0),
+                        }
+                    }
                     self.add_dependencies(module, &ty.1)?;
-                    buffer.push_str(&format!("\t{};\n", ty));
+                    for dep in ty.1.dependencies() {
+                        self.field_types.push(FieldType {
+                            name: dep,
+                            module: module.to_vec(),
+                            strukt: self.context.ident(&item.ident.name.as_str()),
+                            field: name.to_string(),
+                            span: field.span,
+                        });
+                    }
+                    let offset = field
+                        .attrs
+                        .iter()
+                        .filter_map(|attr| retrieve_ffi_int(attr, "offset"))
+                        .next();
+                    fields.push(FieldLayout {
+                        name: name.to_string(),
+                        ty: ty.1.to_string(),
+                        offset,
+                    });
+
+                    // Bitfields, e.g. `#[ffi(bits = 3)] flags: u32` is `uint32_t flags : 3`, and
+                    // `#[ffi(bits(mode = 3, enabled = 1))] flags: u32` shares the storage of the
+                    // Rust field between several bitfields.
+                    let mut bits: Vec<_> = field
+                        .attrs
+                        .iter()
+                        .flat_map(|attr| retrieve_ffi_int_pairs(attr, "bits"))
+                        .map(|(bitfield, width)| (self.context.ident(&bitfield), width))
+                        .collect();
+                    if let Some(width) = field
+                        .attrs
+                        .iter()
+                        .filter_map(|attr| retrieve_ffi_int(attr, "bits"))
+                        .next()
+                    {
+                        bits.push((name.clone(), width));
+                    }
+
+                    let offset_comment = if self.offset_comments {
+                        let size = field
+                            .attrs
+                            .iter()
+                            .filter_map(|attr| retrieve_ffi_int(attr, "size"))
+                            .next();
+                        let layout = match size {
+                            Some(size) => Some((
+                                size,
+                                field
+                                    .attrs
+                                    .iter()
+                                    .filter_map(|attr| retrieve_ffi_int(attr, "align"))
+                                    .next()
+                                    .unwrap_or(1),
+                            )),
+                            None => c_type_layout(&ty.1, self.pointer_width),
+                        };
+                        // The fields of packed structs are aligned to the packing at most.
+                        let layout = layout.map(|(size, align)| {
+                            (size, repr.packed.map_or(align, |packed| align.min(packed)))
+                        });
+
+                        let start = offset.or(match (end, layout) {
+                            (Some(end), Some((_, align))) => Some(end.div_ceil(align) * align),
+                            _ => None,
+                        });
+                        end = match (start, layout) {
+                            (Some(start), Some((size, _))) => Some(start + size),
+                            _ => None,
+                        };
+                        offset_comment(start)
+                    } else {
+                        String::new()
+                    };
+                    if bits.is_empty() {
+                        buffer.push_str(&format!("\t{};{}\n", ty, offset_comment));
+                    } else {
+                        // The bitfields fill the whole storage of the Rust field, so that C
+                        // compilers don't pack the next fields into it.
+                        let total: u64 = bits.iter().map(|&(_, width)| width).sum();
+                        let storage = int_width(&ty.1).filter(|&storage| {
+                            total <= storage && bits.iter().all(|&(_, width)| width > 0)
+                        });
+                        let storage = match storage {
+                            Some(storage) => storage,
+                            None => {
+                                return Err(Error {
+                                    level: Level::Error,
+                                    code: Some(ErrorCode::InvalidBitfield),
+                                    span: Some(field.span),
+                                    message: format!(
+                                        "bitfields of `{}` must be stored in an integer type of \
+                                         at least {} bits",
+                                        name, total
+                                    ),
+                                });
+                            }
+                        };
+
+                        // Qualifiers like `volatile` precede the name of the field.
+                        let qualifiers = &ty.0[..ty.0.len() - name.len()];
+                        for (index, &(ref bitfield, width)) in bits.iter().enumerate() {
+                            let comment = if index == 0 { &*offset_comment } else { "" };
+                            buffer.push_str(&format!(
+                                "\t{} {}{} : {};{}\n",
+                                ty.1, qualifiers, bitfield, width, comment
+                            ));
+                        }
+                        if total < storage {
+                            let padding = format!("{} {}", ty.1, qualifiers);
+                            buffer.push_str(&format!(
+                                "\t{} : {};\n",
+                                padding.trim_end(),
+                                storage - total
+                            ));
+                        }
+                    }
+                }
+
+                // Space set aside for the fields of later versions, with `#[ffi(reserved = N)]`.
+                let reserved = item
+                    .attrs
+                    .iter()
+                    .filter_map(|attr| retrieve_ffi_int(attr, "reserved"))
+                    .next();
+                if let Some(len) = reserved {
+                    let offset_comment = if self.offset_comments {
+                        offset_comment(end)
+                    } else {
+                        String::new()
+                    };
+                    buffer.push_str(&format!(
+                        "\tuint8_t _reserved[{}];{}\n",
+                        len, offset_comment
+                    ));
                 }
 
                 buffer.push_str("}");
             } else if variants.is_tuple() && variants.fields().len() == 1 {
                 // #[repr(C)] pub struct Foo(Bar);  =>  typedef struct Foo Foo;
             } else {
-                return Err(Error {
-                    level: Level::Error,
-                    span: Some(item.span),
-                    message: "can not handle unit or tuple `#[repr(C)]` structs with >1 members"
-                        .into(),
-                });
+                return Err(common::tuple_struct_error(item.span));
             }
         } else {
             return Err(Error {
                 level: Level::Bug,
+                code: Some(ErrorCode::WrongItem),
                 span: Some(item.span),
                 message: "`parse_struct` called on wrong `Item_`".into(),
             });
         }
 
-        buffer.push_str(&format!(" {};\n\n", name));
+        buffer.push_str(&format!("{};\n", self.typedef_name(&name)));
+        if pragma_pack.is_some() {
+            buffer.push_str("#pragma pack(pop)\n");
+        }
+        buffer.push('\n');
+
+        if self.default_initializers {
+            let (derives_default, init) = parse_attr(
+                &item.attrs,
+                |attr| check_derive(attr, "Default"),
+                |attr| retrieve_ffi_value(attr, "default"),
+            );
+
+            if derives_default || !init.is_empty() {
+                buffer.push_str(&format!(
+                    "#define {}_DEFAULT {}\n\n",
+                    name.to_screaming_snake_case(),
+                    if init.is_empty() { "{0}" } else { &init }
+                ));
+            }
+        }
+
         self.append_to_header(buffer, module, outputs)?;
 
         self.decls
-            .insert(name.to_string(), header_name(module, &self.lib_name)?);
+            .insert(name.to_string(), self.header_name(module)?);
+
+        if self.layout_schema.is_some() {
+            self.layouts.push(StructLayout {
+                name: name.to_string(),
+                size: item
+                    .attrs
+                    .iter()
+                    .filter_map(|attr| retrieve_ffi_int(attr, "size"))
+                    .next(),
+                fields,
+            });
+        }
 
         Ok(())
     }
@@ -357,14 +2101,28 @@ impl Lang for LangC {
         module: &[String],
         outputs: &mut Outputs,
     ) -> Result<(), Error> {
+        self.enter_item(&item.attrs);
+
+        // Functions with a Rust signature are exported through generated wrappers.
+        let wrap =
+            self.rust_wrappers.is_some() && item.attrs.iter().any(|attr| check_ffi(attr, "wrap"));
         let (no_mangle, docs) = parse_attr(&item.attrs, check_no_mangle, |attr| {
             retrieve_docstring(attr, "")
         });
-        // If it's not #[no_mangle] then it can't be called from C.
-        if !no_mangle {
+        // If it's not #[no_mangle] (or wrapped) then it can't be called from C.
+        if !no_mangle && !wrap {
             return Ok(());
         }
 
+        // Functions are never dependencies of other items.
+        if self.is_deferred(&item.attrs) {
+            return Ok(());
+        }
+
+        if wrap {
+            return self.wrap_fn(item, &docs, module, outputs);
+        }
+
         let name = item.ident.name.as_str();
 
         if let ast::ItemKind::Fn(ref fn_decl, _, _, abi, ref generics, _) = item.node {
@@ -375,26 +2133,135 @@ impl Lang for LangC {
             }
 
             if generics.is_parameterized() {
-                return Err(Error {
-                    level: Level::Error,
-                    span: Some(item.span),
-                    message: "bindgen can not handle parameterized extern functions".into(),
-                });
+                return Err(common::generic_fn_error(item.span));
             }
 
-            self.transform_native_fn(&*fn_decl, &docs, &format!("{}", name), module, outputs)?;
+            // The exported symbol may be renamed with `#[export_name = "..."]`.
+            let (_, export_name) = parse_attr(&item.attrs, |_| false, retrieve_export_name);
+            let name = if export_name.is_empty() {
+                self.context.ident(&name)
+            } else {
+                export_name
+            };
 
-            Ok(())
+            self.declare_fn(item, item.ident, fn_decl, name, &docs, module, outputs)
         } else {
             Err(Error {
                 level: Level::Bug,
+                code: Some(ErrorCode::WrongItem),
                 span: Some(item.span),
                 message: "`parse_fn` called on wrong `Item_`".into(),
             })
         }
     }
 
+    /// Convert a `#[no_mangle]` static into an `extern` declaration of the global variable.
+    ///
+    /// Statics which aren't `mut` are declared `const`, after the type like the other
+    /// qualifiers (e.g. `extern uint32_t const FOO;`).
+    fn parse_static(
+        &mut self,
+        item: &ast::Item,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        self.enter_item(&item.attrs);
+
+        let (no_mangle, docs) = parse_attr(&item.attrs, check_no_mangle, |attr| {
+            retrieve_docstring(attr, "")
+        });
+        // If it's not #[no_mangle] then it can't be used from C.
+        if !no_mangle || self.is_deferred(&item.attrs) {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Static(ref ty, mutbl, _) = item.node {
+            let (_, export_name) = parse_attr(&item.attrs, |_| false, retrieve_export_name);
+            let name = if export_name.is_empty() {
+                self.context.ident(&item.ident.name.as_str())
+            } else {
+                export_name
+            };
+
+            let declarator = match mutbl {
+                ast::Mutability::Mutable => name.clone(),
+                ast::Mutability::Immutable => format!("const {}", name),
+            };
+            let c_ty = rust_to_c(ty, &declarator, &self.context)?;
+            self.add_dependencies(module, &c_ty.1)?;
+
+            self.append_to_header(format!("{}extern {};\n\n", docs, c_ty), module, outputs)?;
+            self.exports.push(format!("{} DATA", name));
+
+            Ok(())
+        } else {
+            Err(Error {
+                level: Level::Bug,
+                code: Some(ErrorCode::WrongItem),
+                span: Some(item.span),
+                message: "`parse_static` called on wrong `Item_`".into(),
+            })
+        }
+    }
+
+    /// Convert a function declared in an `extern` block into a C function declaration.
+    ///
+    /// These are implemented by C code the crate links against, so unlike `parse_fn` they don't
+    /// need `#[no_mangle]` and aren't listed in the module-definition file.
+    fn parse_foreign_fn(
+        &mut self,
+        item: &ast::ForeignItem,
+        abi: Abi,
+        module: &[String],
+        outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        self.enter_item(&item.attrs);
+
+        if self.is_deferred(&item.attrs) {
+            return Ok(());
+        }
+
+        match abi {
+            Abi::C | Abi::Cdecl | Abi::Stdcall | Abi::Fastcall | Abi::System => {}
+            _ => return Ok(()),
+        }
+
+        if let ast::ForeignItemKind::Fn(ref fn_decl, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_fn_error(item.span));
+            }
+
+            let (_, docs) = parse_attr(&item.attrs, |_| true, |attr| retrieve_docstring(attr, ""));
+            let (_, link_name) = parse_attr(&item.attrs, |_| false, retrieve_link_name);
+            let name = if link_name.is_empty() {
+                self.context.ident(&item.ident.name.as_str())
+            } else {
+                link_name
+            };
+
+            let noreturn = item.attrs.iter().any(|attr| check_ffi(attr, "noreturn"));
+            let docs = format!("{}{}", docs, self.source_annotation(item.ident, fn_decl));
+            self.transform_native_fn(fn_decl, &docs, &name, noreturn, module, outputs)
+        } else {
+            Err(Error {
+                level: Level::Bug,
+                code: Some(ErrorCode::WrongItem),
+                span: Some(item.span),
+                message: "`parse_foreign_fn` called on wrong `ForeignItem_`".into(),
+            })
+        }
+    }
+
     fn finalise_output(&mut self, outputs: &mut Outputs) -> Result<(), Error> {
+        self.add_deferred(outputs);
+        self.check_field_types();
+        self.check_header_names(outputs);
+        if self.strict {
+            if let Some(error) = self.warnings.first() {
+                return Err(error.clone());
+            }
+        }
+
         let mut depgraph = Graph::<String, String>::new();
         let nodes_map: HashMap<String, _> = outputs
             .keys()
@@ -406,9 +2273,46 @@ impl Lang for LangC {
 
         // Wrap modules with common includes
         for (header_name, value) in outputs.iter_mut() {
-            let code = format!("#include <stdint.h>\n#include <stdbool.h>\n\n{}", value);
+            let std_includes: String = self
+                .std_includes
+                .get(header_name)
+                .into_iter()
+                .flatten()
+                .map(|include| format!("#include <{}>\n", include))
+                .collect();
+            let base_includes = if self.c89 {
+                C89_TYPES
+            } else if self.context.underscore_bool {
+                "#include <stdint.h>\n"
+            } else {
+                "#include <stdint.h>\n#include <stdbool.h>\n"
+            };
+            let includes = dedup_includes(&format!(
+                "{}{}{}",
+                base_includes, std_includes, self.prelude_includes
+            ));
+            // Fail early with a clear message on compilers without the optional float types.
+            let float_checks: String = self
+                .float_extensions
+                .get(header_name)
+                .into_iter()
+                .flatten()
+                .map(|ty| float_extension_check(ty))
+                .collect();
+            let fn_hints = if self.fn_hint_headers.contains(header_name) {
+                FN_HINT_MACROS
+            } else {
+                ""
+            };
+            if self.c89 {
+                *value = to_c89(value);
+            }
+            let mut code = format!("{}{}{}\n{}", includes, float_checks, fn_hints, value);
+            if self.cpp_guard {
+                code = wrap_extern(&code);
+            }
 
-            *value = wrap_guard(&wrap_extern(&code), header_name);
+            *value = wrap_guard(&code, header_name);
 
             // Building a graph of dependencies
             if let Some(module_deps) = self.deps.get(header_name) {
@@ -427,59 +2331,459 @@ impl Lang for LangC {
 
         // Build a full dependency graph and topologically sort dependencies
         depgraph.extend_with_edges(&edges);
-        let sorted_deps = unwrap!(algo::toposort(&depgraph, None));
+        let sorted_deps = sort_headers(&depgraph);
 
         // Generate a top-level header and add custom user code
         let mut top_level_header = String::new();
+        let mut internal_header = String::new();
+        for (guard, message) in &self.platform_requirements {
+            top_level_header.push_str(&platform_requirement(guard, message));
+        }
+        if let Some((major, minor, patch)) = self.version {
+            top_level_header.push_str(&version_macros(&self.lib_name, major, minor, patch));
+        }
         if !self.custom_code.is_empty() {
             top_level_header.push_str(&format!("{}\n", self.custom_code));
         }
         for node_id in sorted_deps {
             let header_name = &node_ids_map[&node_id];
-            top_level_header.push_str(&format!("#include \"{}\"\n", header_name));
+            let include = self.include_style.include(header_name);
+            // The public header never exposes the internal items.
+            if self.internal_headers.contains(header_name) {
+                internal_header.push_str(&include);
+            } else {
+                top_level_header.push_str(&include);
+            }
+        }
+        // C89 has no static assertions.
+        if let Some(bits) = self
+            .pointer_width
+            .filter(|_| self.uses_pointer_sized && !self.c89)
+        {
+            top_level_header.push_str(&pointer_width_assert(&self.lib_name, bits));
+        }
+
+        if self.c89 {
+            top_level_header = to_c89(&top_level_header);
+            internal_header = to_c89(&internal_header);
+        }
+
+        outputs.insert(
+            format!("{}.h", self.lib_name),
+            wrap_guard(
+                &dedup_includes(&top_level_header),
+                &format!("{}_root", self.lib_name),
+            ),
+        );
+
+        // Internal items may use public types, so the internal header includes the public one.
+        if !internal_header.is_empty() {
+            let public_header = self.include_style.include(&format!("{}.h", self.lib_name));
+            outputs.insert(
+                format!("{}_internal.h", self.lib_name),
+                wrap_guard(
+                    &dedup_includes(&format!("{}{}", public_header, internal_header)),
+                    &format!("{}_internal_root", self.lib_name),
+                ),
+            );
+        }
+
+        if let Some(ref def_file) = self.def_file {
+            let mut def = format!("LIBRARY {}\nEXPORTS\n", self.lib_name);
+            for name in &self.exports {
+                def.push_str(&format!("\t{}\n", name));
+            }
+            outputs.insert(def_file.clone(), def);
+        }
+
+        if let Some(ref layout_schema) = self.layout_schema {
+            outputs.insert(layout_schema.clone(), layout::to_json(&self.layouts));
+        }
+
+        if let Some(ref cmake_file) = self.cmake_file {
+            let cmake = cmake_snippet(&self.lib_name, outputs);
+            outputs.insert(cmake_file.clone(), cmake);
+        }
+
+        if let Some((ref file, ref wrappers)) = self.rust_wrappers {
+            if !wrappers.is_empty() {
+                outputs.insert(file.clone(), wrappers.clone());
+            }
+        }
+
+        if let Some((ref name, ref version, ref libs)) = self.pkg_config {
+            let libs = if libs.is_empty() {
+                format!("-l{}", self.lib_name)
+            } else {
+                libs.clone()
+            };
+            outputs.insert(
+                format!("{}.pc", self.lib_name),
+                pkg_config(&self.lib_name, name, version, &libs),
+            );
+        }
+
+        let banner = match self.banner {
+            Some(ref banner) => banner.clone(),
+            None => format!(
+                "DO NOT EDIT: this file is generated by safe_bindgen for {}.",
+                self.lib_name
+            ),
+        };
+        if !banner.is_empty() {
+            for (name, value) in outputs.iter_mut() {
+                let comment = if name.ends_with(".h") {
+                    block_comment(&banner)
+                } else if self.def_file.as_ref() == Some(name) {
+                    line_comment(";", &banner)
+                } else if self.cmake_file.as_ref() == Some(name) || name.ends_with(".pc") {
+                    line_comment("#", &banner)
+                } else if name.ends_with(".rs") {
+                    line_comment("//", &banner)
+                } else {
+                    continue;
+                };
+                *value = format!("{}\n{}", comment, value.trim_start_matches('\n'));
+            }
+        }
+
+        for value in outputs.values_mut() {
+            *value = self.line_ending.apply(&normalise_blank_lines(value));
+        }
+
+        if let Some(ref cc) = self.compiler {
+            verify_headers(cc, &self.lib_name, outputs)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compile each generated header on its own with `cc -fsyntax-only`, from a temporary copy of
+/// the outputs so that the headers can include each other.
+///
+/// Each call gets its own directory, so that concurrent verifications (e.g. in parallel tests or
+/// build scripts) don't overwrite each other's headers.
+fn verify_headers(cc: &str, lib_name: &str, outputs: &Outputs) -> Result<(), Error> {
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+    let dir = env::temp_dir().join(format!(
+        "safe_bindgen_{}_{}_{}",
+        lib_name,
+        process::id(),
+        NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+    ));
+    let res = compile_headers(cc, &dir, outputs);
+    let _ = fs::remove_dir_all(&dir);
+    res
+}
+
+fn compile_headers(cc: &str, dir: &path::Path, outputs: &Outputs) -> Result<(), Error> {
+    let compile_error = |message: String| Error {
+        level: Level::Error,
+        code: Some(ErrorCode::CompileError),
+        span: None,
+        message,
+    };
+
+    for (name, contents) in outputs {
+        crate::write_file(&dir.join(name), contents).map_err(|error| {
+            compile_error(format!(
+                "could not write `{}` to be compiled: {}",
+                name, error
+            ))
+        })?;
+    }
+
+    for name in outputs.keys().filter(|name| name.ends_with(".h")) {
+        let output = Command::new(cc)
+            .arg("-fsyntax-only")
+            .args(["-x", "c"])
+            .arg("-I")
+            .arg(dir)
+            .arg(dir.join(name))
+            .output()
+            .map_err(|error| compile_error(format!("could not run `{}`: {}", cc, error)))?;
+
+        if !output.status.success() {
+            return Err(compile_error(format!(
+                "`{}` does not compile:\n{}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the field a transparent struct collapses into: its only field which isn't
+/// zero-sized, provided the struct isn't generic.
+fn transparent_field(item: &ast::Item) -> Option<&ast::StructField> {
+    let variants = match item.node {
+        ast::ItemKind::Struct(ref variants, ref generics) if !generics.is_parameterized() => {
+            variants
         }
+        _ => return None,
+    };
+
+    let mut fields = variants
+        .fields()
+        .iter()
+        .filter(|field| !is_zero_sized(&field.ty));
+    match (fields.next(), fields.next()) {
+        (Some(field), None) => Some(field),
+        _ => None,
+    }
+}
 
-        outputs.insert(
-            format!("{}.h", self.lib_name),
-            wrap_guard(&top_level_header, &format!("{}_root", self.lib_name)),
-        );
+/// Check the type is zero-sized: `()`, `PhantomData` or an empty array.
+fn is_zero_sized(ty: &ast::Ty) -> bool {
+    match ty.node {
+        ast::TyKind::Tup(ref elems) => elems.is_empty(),
+        ast::TyKind::Array(_, ref len) => pprust::expr_to_string(len) == "0",
+        ast::TyKind::Path(None, ref path) => path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.identifier.name == "PhantomData"),
+        _ => false,
+    }
+}
 
-        Ok(())
+/// Find a struct field which is an array with its length given by a generic parameter.
+///
+/// Such structs can only be exported once monomorphised, so report which parameter and field are
+/// responsible instead of a blanket error about parameterized structs.
+fn generic_array_len_error(variants: &ast::VariantData, generics: &ast::Generics) -> Option<Error> {
+    for field in variants.fields() {
+        if let ast::TyKind::Array(_, ref len) = field.ty.node {
+            let len = pprust::expr_to_string(len);
+            let param = generics
+                .ty_params
+                .iter()
+                .find(|param| param.ident.name.as_str() == &*len);
+
+            if let Some(param) = param {
+                let field_name = field
+                    .ident
+                    .map(|ident| ident.name.as_str().to_string())
+                    .unwrap_or_default();
+
+                return Some(Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::GenericArrayLen),
+                    span: Some(field.span),
+                    message: format!(
+                        "bindgen can not handle the array field `{}` whose length is the generic \
+                         parameter `{}`; declare a struct with a concrete array length instead",
+                        field_name, param.ident.name
+                    ),
+                });
+            }
+        }
     }
+
+    None
 }
 
 /// Turn a Rust type with an associated name or type into a C type.
-pub fn rust_to_c(ty: &ast::Ty, assoc: &str) -> Result<CTypeNamed, Error> {
+pub fn rust_to_c(ty: &ast::Ty, assoc: &str, context: &Context) -> Result<CTypeNamed, Error> {
+    if let Some(inner) = maybe_uninit_inner(ty) {
+        return rust_to_c(inner, assoc, context);
+    }
+
     match ty.node {
         // Function pointers make life an absolute pain here.
         ast::TyKind::BareFn(ref bare_fn) => Ok(CTypeNamed(
             Default::default(),
-            fn_ptr_to_c(bare_fn, ty.span, assoc)?,
+            fn_ptr_to_c(bare_fn, ty.span, assoc, context)?,
         )),
+        // Arrays of function pointers decay into pointers to them, `void (* const* name)(void)`.
+        ast::TyKind::Array(ref elem, _) if matches!(elem.node, ast::TyKind::BareFn(..)) => {
+            rust_to_c(elem, &format!(" const* {}", assoc), context)
+        }
+        // Multi-dimensional arrays decay into pointers to their rows, `float (*matrix)[4]`.
+        ast::TyKind::Array(ref elem, _) if matches!(elem.node, ast::TyKind::Array(..)) => {
+            field_rust_to_c(elem, &format!("(*{})", assoc), context)
+        }
         // All other types just have a name associated with them.
-        _ => Ok(CTypeNamed(assoc.to_string(), anon_rust_to_c(ty)?)),
+        _ => Ok(CTypeNamed(assoc.to_string(), anon_rust_to_c(ty, context)?)),
+    }
+}
+
+/// Turn the type of a struct field into a C type.
+///
+/// Unlike in function signatures, fixed-length arrays are stored inline in the struct, so they
+/// are kept as arrays (e.g. `name: [c_char; 64]` becomes `char name[64]`).
+pub(crate) fn field_rust_to_c(
+    ty: &ast::Ty,
+    name: &str,
+    context: &Context,
+) -> Result<CTypeNamed, Error> {
+    if let Some(inner) = maybe_uninit_inner(ty) {
+        return field_rust_to_c(inner, name, context);
+    }
+
+    match ty.node {
+        // The length goes inside the declarator of function pointers, `void (*name[8])(void)`.
+        ast::TyKind::Array(ref elem, ref len) if matches!(elem.node, ast::TyKind::BareFn(..)) => {
+            let declarator = format!("{}[{}]", name, array_len(len, context)?);
+            rust_to_c(elem, &declarator, context)
+        }
+        // Nested arrays are multi-dimensional arrays, `float matrix[4][4]`.
+        ast::TyKind::Array(ref elem, ref len) => {
+            let elem = match elem.node {
+                ast::TyKind::Array(ref inner, _)
+                    if !matches!(inner.node, ast::TyKind::BareFn(..)) =>
+                {
+                    field_rust_to_c(elem, name, context)?.1
+                }
+                _ => anon_rust_to_c(elem, context)?,
+            };
+            Ok(CTypeNamed(
+                name.to_string(),
+                CType::Array(Box::new(elem), array_len(len, context)?),
+            ))
+        }
+        _ => rust_to_c(ty, name, context),
+    }
+}
+
+/// Error for `Ipv4Addr` and `Ipv6Addr` (from `std::net` or `core::net`), whose layout isn't
+/// guaranteed to be their octets, so they have to be passed as `[u8; 4]` and `[u8; 16]` instead.
+fn ip_addr_error(ty: &ast::Ty) -> Option<Error> {
+    let path = match ty.node {
+        ast::TyKind::Path(None, ref path) => path,
+        _ => return None,
+    };
+    let (ty, module) = path.segments.split_last()?;
+    let module = module
+        .iter()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .collect::<Vec<_>>()
+        .join("::");
+
+    match &*module {
+        "std::net" | "core::net" => (),
+        _ => return None,
+    }
+
+    let (name, len) = match &*ty.identifier.name.as_str() {
+        "Ipv4Addr" => ("Ipv4Addr", 4),
+        "Ipv6Addr" => ("Ipv6Addr", 16),
+        _ => return None,
+    };
+    Some(Error {
+        level: Level::Error,
+        code: Some(ErrorCode::IpAddress),
+        span: Some(path.span),
+        message: format!(
+            "`{0}` has no guaranteed layout, pass its octets as `[u8; {1}]` instead (from \
+             `{0}::octets`)",
+            name, len
+        ),
+    })
+}
+
+/// Returns the length of an array, with a named constant replaced by its value.
+fn array_len(len: &ast::Expr, context: &Context) -> Result<String, Error> {
+    match len.node {
+        ast::ExprKind::Path(None, ref path) => {
+            let name = pprust::path_to_string(path);
+            context.consts.get(&name).cloned().ok_or_else(|| Error {
+                level: Level::Error,
+                code: Some(ErrorCode::UnknownArrayLen),
+                span: Some(len.span),
+                message: format!(
+                    "unknown array length `{}`, it must be a `pub` integer constant declared \
+                     before the array",
+                    name
+                ),
+            })
+        }
+        _ => Ok(pprust::expr_to_string(len)),
+    }
+}
+
+/// Returns the type wrapped by `MaybeUninit<T>` (from `std::mem` or `core::mem`), which has the
+/// same layout so it's converted like the wrapped type.
+fn maybe_uninit_inner(ty: &ast::Ty) -> Option<&ast::Ty> {
+    let path = match ty.node {
+        ast::TyKind::Path(None, ref path) => path,
+        _ => return None,
+    };
+    let (ty, module) = path.segments.split_last()?;
+    let module = module
+        .iter()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .collect::<Vec<_>>()
+        .join("::");
+
+    match &*module {
+        "" | "mem" | "std::mem" | "core::mem" if ty.identifier.name == "MaybeUninit" => (),
+        _ => return None,
+    }
+
+    match **ty.parameters.as_ref()? {
+        ast::PathParameters::AngleBracketed(ref data) if data.types.len() == 1 => {
+            Some(&data.types[0])
+        }
+        _ => None,
     }
 }
 
 /// Turn a Rust type into a C type.
-fn anon_rust_to_c(ty: &ast::Ty) -> Result<CType, Error> {
+fn anon_rust_to_c(ty: &ast::Ty, context: &Context) -> Result<CType, Error> {
+    if let Some(inner) = maybe_uninit_inner(ty) {
+        return anon_rust_to_c(inner, context);
+    }
+
+    if let Some(span) = trait_object_span(ty) {
+        return Err(Error {
+            level: Level::Error,
+            code: Some(ErrorCode::TraitObject),
+            span: Some(span),
+            message: "trait objects and closures have no C equivalent, pass an `extern \"C\" fn` \
+                      pointer instead (with a `*mut c_void` argument for its state if needed)"
+                .into(),
+        });
+    }
+
     match ty.node {
         // Function pointers should not be in this function.
         ast::TyKind::BareFn(..) => Err(Error {
             level: Level::Error,
+            code: Some(ErrorCode::AnonymousFnPtr),
             span: Some(ty.span),
             message:
                 "C function ptrs must have a name or function declaration associated with them"
                     .into(),
         }),
         // Fixed-length arrays, converted into pointers.
-        ast::TyKind::Array(ref ty, _) => {
-            Ok(CType::Ptr(Box::new(anon_rust_to_c(ty)?), CPtrType::Const))
+        ast::TyKind::Array(ref ty, _) => Ok(CType::Ptr(
+            Box::new(anon_rust_to_c(ty, context)?),
+            CPtrType::Const,
+        )),
+        // `CStr` and `CString` are often mistaken for C strings.
+        ast::TyKind::Ptr(ref ptr) | ast::TyKind::Rptr(_, ref ptr) if is_c_str(&ptr.ty) => {
+            Err(Error {
+                level: Level::Error,
+                code: Some(ErrorCode::CStrPointer),
+                span: Some(ty.span),
+                message: format!(
+                    "`{}` is not a C string: pointers to `CStr` are fat pointers and `CString` \
+                     is a Rust struct, pass a `*const c_char` instead (from `CStr::as_ptr` or \
+                     `CString::into_raw`)",
+                    pprust::ty_to_string(ty)
+                ),
+            })
         }
         // Standard pointers.
-        ast::TyKind::Ptr(ref ptr) => ptr_to_c(ptr),
-        // Plain old types.
-        ast::TyKind::Path(None, ref path) => path_to_c(path),
+        ast::TyKind::Ptr(ref ptr) => ptr_to_c(ptr, context),
+        // Plain old types, except IP addresses which aren't laid out like their octets.
+        ast::TyKind::Path(None, ref path) => match ip_addr_error(ty) {
+            Some(error) => Err(error),
+            None => path_to_c(path, context),
+        },
         // Possibly void, likely not.
         _ => {
             let new_type = print::pprust::ty_to_string(ty);
@@ -489,6 +2793,7 @@ fn anon_rust_to_c(ty: &ast::Ty) -> Result<CType, Error> {
             } else {
                 Err(Error {
                     level: Level::Error,
+                    code: Some(ErrorCode::UnsupportedType),
                     span: Some(ty.span),
                     message: format!("bindgen can not handle the type `{}`", new_type),
                 })
@@ -497,9 +2802,76 @@ fn anon_rust_to_c(ty: &ast::Ty) -> Result<CType, Error> {
     }
 }
 
+/// Returns the span of the trait object or closure type (e.g. `Fn()` or `impl Fn()`) the type is
+/// or points to, including through `Box`, `Rc` and `Arc`.
+///
+/// Without `dyn`, trait objects parse as paths, so only the closure traits are recognised there.
+fn trait_object_span(ty: &ast::Ty) -> Option<codemap::Span> {
+    match ty.node {
+        ast::TyKind::TraitObject(..) | ast::TyKind::ImplTrait(..) => Some(ty.span),
+        ast::TyKind::Ptr(ref ptr) | ast::TyKind::Rptr(_, ref ptr) => trait_object_span(&ptr.ty),
+        ast::TyKind::Paren(ref ty) => trait_object_span(ty),
+        ast::TyKind::Path(None, ref path) => {
+            let segment = path.segments.last()?;
+            match &*segment.identifier.name.as_str() {
+                "Fn" | "FnMut" | "FnOnce" => return Some(ty.span),
+                "Box" | "Rc" | "Arc" => (),
+                _ => return None,
+            }
+            match **segment.parameters.as_ref()? {
+                ast::PathParameters::AngleBracketed(ref data) => {
+                    trait_object_span(data.types.first()?)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Check the type is `CStr` or `CString`, with a path from `ffi` (e.g. `std::ffi::CStr`) so
+/// that user types with the same names aren't mistaken for them.
+fn is_c_str(ty: &ast::Ty) -> bool {
+    let path = match ty.node {
+        ast::TyKind::Path(None, ref path) => path,
+        _ => return false,
+    };
+    let segments: Vec<_> = path
+        .segments
+        .iter()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .collect();
+    match segments.split_last() {
+        Some((ty, module)) if ty == "CStr" || ty == "CString" => matches!(
+            &*module.join("::"),
+            "ffi" | "std::ffi" | "core::ffi" | "alloc::ffi"
+        ),
+        _ => false,
+    }
+}
+
 /// Turn a Rust pointer (*mut or *const) into the correct C form.
-fn ptr_to_c(ty: &ast::MutTy) -> Result<CType, Error> {
-    let new_type = anon_rust_to_c(&ty.ty)?;
+fn ptr_to_c(ty: &ast::MutTy, context: &Context) -> Result<CType, Error> {
+    // Pointers to slices also carry the length, so they're twice as wide as C pointers.
+    if let ast::TyKind::Slice(ref elem) = ty.ty.node {
+        let ptr = match ty.mutbl {
+            ast::Mutability::Immutable => "*const",
+            ast::Mutability::Mutable => "*mut",
+        };
+        return Err(Error {
+            level: Level::Error,
+            code: Some(ErrorCode::SlicePointer),
+            span: Some(ty.ty.span),
+            message: format!(
+                "pointers to slices are fat pointers with no C equivalent, pass the pointer to \
+                 the first element and the length instead (`{} {}, usize`)",
+                ptr,
+                pprust::ty_to_string(elem)
+            ),
+        });
+    }
+
+    let new_type = anon_rust_to_c(&ty.ty, context)?;
     let const_spec = match ty.mutbl {
         // *const T
         ast::Mutability::Immutable => CPtrType::Const,
@@ -525,10 +2897,16 @@ fn ptr_to_c(ty: &ast::MutTy) -> Result<CType, Error> {
 /// ```
 ///
 /// where `inner` could either be a name or the rest of a function declaration.
-fn fn_ptr_to_c(fn_ty: &ast::BareFnTy, fn_span: codemap::Span, inner: &str) -> Result<CType, Error> {
+fn fn_ptr_to_c(
+    fn_ty: &ast::BareFnTy,
+    fn_span: codemap::Span,
+    inner: &str,
+    context: &Context,
+) -> Result<CType, Error> {
     if !fn_ty.lifetimes.is_empty() {
         return Err(Error {
             level: Level::Error,
+            code: Some(ErrorCode::Lifetimes),
             span: Some(fn_span),
             message: "bindgen can not handle lifetimes".into(),
         });
@@ -543,41 +2921,42 @@ fn fn_ptr_to_c(fn_ty: &ast::BareFnTy, fn_span: codemap::Span, inner: &str) -> Re
         let mut args = vec![];
         for arg in &fn_decl.inputs {
             let arg_name = print::pprust::pat_to_string(&*arg.pat);
-            let arg_type = rust_to_c(&*arg.ty, &arg_name)?;
+            let arg_type = rust_to_c(&*arg.ty, &arg_name, context)?;
             args.push(arg_type);
         }
         args
     };
 
-    let output_type = &fn_decl.output;
-
-    let return_type = match *output_type {
-        ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
-            return Err(Error {
-                level: Level::Error,
-                span: Some(ty.span),
-                message: "panics across a C boundary are naughty!".into(),
-            });
-        }
-        ast::FunctionRetTy::Default(..) => CType::Void,
-        ast::FunctionRetTy::Ty(ref ty) => anon_rust_to_c(&*ty)?,
-    };
-
     Ok(CType::FnDecl {
         inner: inner.to_string(),
         args,
-        return_type: Box::new(return_type),
+        return_type: Box::new(ret_ty_to_c(&fn_decl.output, context)?),
     })
 }
 
+/// Convert the return type of a function or function pointer into C.
+///
+/// Returned function pointers are not handled here, since the rest of the declaration has to be
+/// nested inside of them (see `rust_to_c`).
+pub(crate) fn ret_ty_to_c(output: &ast::FunctionRetTy, context: &Context) -> Result<CType, Error> {
+    match *output {
+        ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
+            Err(common::never_return_error(ty.span))
+        }
+        ast::FunctionRetTy::Default(..) => Ok(CType::Void),
+        ast::FunctionRetTy::Ty(ref ty) => anon_rust_to_c(ty, context),
+    }
+}
+
 /// Convert a Rust path type (e.g. `my_mod::MyType`) to a C type.
 ///
 /// Types hidden behind modules are almost certainly custom types (which wouldn't work) except
 /// types in `libc` which we special case.
-fn path_to_c(path: &ast::Path) -> Result<CType, Error> {
+fn path_to_c(path: &ast::Path, context: &Context) -> Result<CType, Error> {
     if path.segments.is_empty() {
         return Err(Error {
             level: Level::Bug,
+            code: Some(ErrorCode::InvalidType),
             span: Some(path.span),
             message: "invalid type".into(),
         });
@@ -598,15 +2977,52 @@ fn path_to_c(path: &ast::Path) -> Result<CType, Error> {
         match &*module {
             "libc" => Ok(libc_ty_to_c(ty)),
             "std::os::raw" => Ok(osraw_ty_to_c(ty)),
-            _ => Err(Error {
-                level: Level::Error,
-                span: Some(path.span),
-                message: "can not handle types in other modules (except `libc` and `std::os::raw`)"
-                    .into(),
-            }),
+            "std::sync::atomic" | "core::sync::atomic" => match atomic_ty_to_c(ty) {
+                Some(CType::Atomic("bool")) if context.underscore_bool => {
+                    Ok(CType::Atomic("_Bool"))
+                }
+                Some(cty) => Ok(cty),
+                None => Err(Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::UnsupportedAtomic),
+                    span: Some(path.span),
+                    message: format!("bindgen can not handle the atomic type `{}`", ty),
+                }),
+            },
+            "widestring" => widestring_ty_to_c(ty)
+                .or_else(|| context.resolve(&format!("{}::{}", module, ty)))
+                .ok_or_else(|| foreign_module_error(path.span)),
+            _ => context
+                .resolve(&format!("{}::{}", module, ty))
+                .ok_or_else(|| foreign_module_error(path.span)),
         }
     } else {
-        Ok(rust_ty_to_c(&path.segments[0].identifier.name.as_str()))
+        Ok(rust_ty_to_c(
+            &path.segments[0].identifier.name.as_str(),
+            context,
+        ))
+    }
+}
+
+/// Error for an invalid type mapping table.
+fn mapping_error(message: String) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::InvalidMapping),
+        span: None,
+        message,
+    }
+}
+
+/// Error for a type from a module which bindgen doesn't know about.
+fn foreign_module_error(span: codemap::Span) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::ForeignModuleType),
+        span: Some(span),
+        message: "can not handle types in other modules (except `libc`, `std::os::raw`, \
+                  `std::sync::atomic`, the `std::net` addresses and `widestring`)"
+            .into(),
     }
 }
 
@@ -629,8 +3045,76 @@ fn libc_ty_to_c(ty: &str) -> CType {
         "c_ulong" => CType::Native("unsigned long"),
         "c_longlong" => CType::Native("long long"),
         "c_ulonglong" => CType::Native("unsigned long long"),
-        // All other types should map over to C.
-        ty => CType::Mapping(ty.to_string()),
+        // Types defined by system headers, which vary by platform.
+        ty => match SYSTEM_TYPES.iter().find(|&&(name, _)| name == ty) {
+            Some(&(name, _)) => CType::Native(name),
+            // All other types should map over to C.
+            None => CType::Mapping(ty.to_string()),
+        },
+    }
+}
+
+/// Returns the width in bits of an integer C type.
+fn int_width(cty: &CType) -> Option<u64> {
+    match *cty {
+        CType::Native("bool") | CType::Native("_Bool") => Some(1),
+        CType::Native("int8_t")
+        | CType::Native("uint8_t")
+        | CType::Native("char")
+        | CType::Native("signed char")
+        | CType::Native("unsigned char") => Some(8),
+        CType::Native("int16_t")
+        | CType::Native("uint16_t")
+        | CType::Native("short")
+        | CType::Native("unsigned short") => Some(16),
+        CType::Native("int32_t")
+        | CType::Native("uint32_t")
+        | CType::Native("int")
+        | CType::Native("unsigned int") => Some(32),
+        CType::Native("int64_t")
+        | CType::Native("uint64_t")
+        | CType::Native("long long")
+        | CType::Native("unsigned long long") => Some(64),
+        _ => None,
+    }
+}
+
+/// Returns the size and alignment in bytes of a C type, if they're known: for primitives,
+/// pointers (given the width of pointers) and arrays of them.
+fn c_type_layout(cty: &CType, pointer_width: Option<u32>) -> Option<(u64, u64)> {
+    let pointer = pointer_width.map(|bits| (u64::from(bits) / 8, u64::from(bits) / 8));
+    match *cty {
+        CType::Native("float") => Some((4, 4)),
+        CType::Native("double") => Some((8, 8)),
+        CType::Native("size_t")
+        | CType::Native("ptrdiff_t")
+        | CType::Native("intptr_t")
+        | CType::Native("uintptr_t") => pointer,
+        CType::Ptr(..) | CType::FnDecl { .. } => pointer,
+        CType::Array(ref elem, ref len) => {
+            let (size, align) = c_type_layout(elem, pointer_width)?;
+            Some((size * len.parse::<u64>().ok()?, align))
+        }
+        CType::Atomic(int) => c_type_layout(&CType::Native(int), pointer_width),
+        _ => int_width(cty).map(|bits| (bits.div_ceil(8), bits.div_ceil(8))),
+    }
+}
+
+/// Comment with the offset of a struct field, or `?` if it's unknown.
+fn offset_comment(offset: Option<u64>) -> String {
+    match offset {
+        Some(offset) => format!(" /* offset {} */", offset),
+        None => String::from(" /* offset ? */"),
+    }
+}
+
+/// Convert a character type from the `widestring` crate into a C type.
+fn widestring_ty_to_c(ty: &str) -> Option<CType> {
+    match ty {
+        "WideChar" => Some(CType::Native("wchar_t")),
+        "U16Char" => Some(CType::Native("uint16_t")),
+        "U32Char" => Some(CType::Native("uint32_t")),
+        _ => None,
     }
 }
 
@@ -658,28 +3142,278 @@ fn osraw_ty_to_c(ty: &str) -> CType {
     }
 }
 
+/// Convert a Rust type from `std::sync::atomic` into a C type.
+///
+/// Atomics have the same in-memory representation as their underlying integer, so they map to
+/// the plain integer type, and struct fields get a comment reminding that C code must access them
+/// atomically. Only the paths qualified with the module are mapped, as a bare `AtomicU32` may be
+/// any type.
+fn atomic_ty_to_c(ty: &str) -> Option<CType> {
+    let native = match ty {
+        "AtomicBool" => "bool",
+        "AtomicI8" => "int8_t",
+        "AtomicI16" => "int16_t",
+        "AtomicI32" => "int32_t",
+        "AtomicI64" => "int64_t",
+        "AtomicIsize" => "intptr_t",
+        "AtomicU8" => "uint8_t",
+        "AtomicU16" => "uint16_t",
+        "AtomicU32" => "uint32_t",
+        "AtomicU64" => "uint64_t",
+        "AtomicUsize" => "uintptr_t",
+        _ => return None,
+    };
+
+    Some(CType::Atomic(native))
+}
+
 /// Convert any Rust type into C.
 ///
 /// This includes user-defined types. We currently trust the user not to use types which we don't
 /// know the structure of (like String).
-fn rust_ty_to_c(ty: &str) -> CType {
+pub(crate) fn rust_ty_to_c(ty: &str, context: &Context) -> CType {
     match ty {
         "()" => CType::Void,
         "f32" => CType::Native("float"),
         "f64" => CType::Native("double"),
+        "f16" => CType::Native("_Float16"),
+        "f128" => CType::Native("_Float128"),
         "i8" => CType::Native("int8_t"),
         "i16" => CType::Native("int16_t"),
         "i32" => CType::Native("int32_t"),
         "i64" => CType::Native("int64_t"),
-        "isize" => CType::Native("intptr_t"),
+        "isize" => CType::Native(context.pointer_sized.map_or("intptr_t", |types| types.0)),
         "u8" => CType::Native("uint8_t"),
         "u16" => CType::Native("uint16_t"),
         "u32" => CType::Native("uint32_t"),
         "u64" => CType::Native("uint64_t"),
-        "usize" => CType::Native("uintptr_t"),
+        "usize" => CType::Native(context.pointer_sized.map_or("uintptr_t", |types| types.1)),
+        "bool" if context.underscore_bool => CType::Native("_Bool"),
         "bool" => CType::Native("bool"),
-        ty => libc_ty_to_c(ty),
+        ty => match libc_ty_to_c(ty) {
+            CType::Mapping(name) => context
+                .resolve(&name)
+                .unwrap_or_else(|| context.named(context.ident(&name))),
+            cty => cty,
+        },
+    }
+}
+
+/// Generate a function returning the name of a variant of the enum, given the constants of the
+/// variants.
+fn enum_to_string(name: &str, ty: &CType, constants: &[String], variants: &[String]) -> String {
+    let mut buffer = format!(
+        "static inline const char* {}_to_string({} value) {{\n\tswitch (value) {{\n",
+        name, ty
+    );
+    for (constant, variant) in constants.iter().zip(variants) {
+        buffer.push_str(&format!("\tcase {}: return \"{}\";\n", constant, variant));
+    }
+    buffer.push_str("\tdefault: return \"<unknown>\";\n\t}\n}\n\n");
+
+    buffer
+}
+
+/// Generate the `libname_strerror` function returning the message of an error code.
+fn strerror(prefix: &str, constants: &[String], messages: &[String]) -> String {
+    let mut buffer = format!(
+        "static inline const char* {}_strerror(int code) {{\n\tswitch (code) {{\n",
+        prefix
+    );
+    for (constant, message) in constants.iter().zip(messages) {
+        buffer.push_str(&format!(
+            "\tcase {}: return \"{}\";\n",
+            constant,
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+    buffer.push_str("\tdefault: return \"<unknown>\";\n\t}\n}\n\n");
+
+    buffer
+}
+
+/// Text of the doc comments of an item, joined into a single line.
+fn doc_message(attrs: &[ast::Attribute]) -> Option<String> {
+    let (_, docs) = parse_attr(attrs, |_| true, |attr| common::retrieve_docstring(attr, ""));
+    let words: Vec<_> = docs
+        .lines()
+        .map(|line| {
+            line.trim_start_matches("///")
+                .trim_start_matches("/**")
+                .trim_end_matches("*/")
+                .trim()
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+/// Check the expression is an integer literal, possibly negated.
+fn is_int_literal(expr: &ast::Expr) -> bool {
+    match expr.node {
+        ast::ExprKind::Lit(ref lit) => matches!(lit.node, ast::LitKind::Int(..)),
+        ast::ExprKind::Unary(ast::UnOp::Neg, ref expr) => is_int_literal(expr),
+        ast::ExprKind::Paren(ref expr) => is_int_literal(expr),
+        _ => false,
+    }
+}
+
+/// Dependencies along with the header of the items depending on them.
+fn header_deps(deps: &BTreeMap<String, Vec<String>>) -> Vec<(String, String)> {
+    deps.iter()
+        .flat_map(|(header, deps)| deps.iter().map(move |dep| (header.clone(), dep.clone())))
+        .collect()
+}
+
+/// Order the deferred item `key` after the needed items it depends on (depth first). Items
+/// depending on each other (through pointers) are kept in the order they're first reached.
+fn deferred_order(
+    key: &DeferredKey,
+    needed: &BTreeMap<DeferredKey, Deferred>,
+    visited: &mut BTreeSet<DeferredKey>,
+    order: &mut Vec<DeferredKey>,
+) {
+    if !visited.insert(key.clone()) {
+        return;
+    }
+    if let Some(deferred) = needed.get(key) {
+        for dep in deferred.deps.values().flatten() {
+            for dep_key in needed.keys().filter(|dep_key| dep_key.1 == *dep) {
+                deferred_order(dep_key, needed, visited, order);
+            }
+        }
+        order.push(key.clone());
+    }
+}
+
+/// Generate the assertion that an enum without explicit discriminants is contiguous from 0 (if
+/// static assertions are available), or a comment listing the values of the variants otherwise.
+fn enum_values(
+    name: &str,
+    constants: &[String],
+    discriminants: &[Option<String>],
+    static_assert: bool,
+) -> String {
+    if static_assert && discriminants.iter().all(Option::is_none) {
+        let last = &constants[constants.len() - 1];
+        return format!(
+            "#ifdef __cplusplus\n\
+             static_assert({0} == {1}, \"{2} must be contiguous from 0\");\n\
+             #else\n\
+             _Static_assert({0} == {1}, \"{2} must be contiguous from 0\");\n\
+             #endif\n\n",
+            last,
+            constants.len() - 1,
+            name
+        );
+    }
+
+    // Variants without a discriminant follow the previous one, which may not be a literal.
+    let mut values = Vec::new();
+    let mut previous: Option<(String, i64)> = None;
+    for (constant, discriminant) in constants.iter().zip(discriminants) {
+        let (base, offset) = match (discriminant, previous) {
+            (Some(expr), _) => match expr.parse::<i64>() {
+                Ok(value) => (String::new(), value),
+                Err(_) => (expr.clone(), 0),
+            },
+            (None, Some((base, offset))) => (base, offset + 1),
+            (None, None) => (String::new(), 0),
+        };
+        let value = match (&*base, offset) {
+            ("", offset) => offset.to_string(),
+            (base, 0) => base.to_string(),
+            (base, offset) => format!("({}) + {}", base, offset),
+        };
+        values.push(format!(" * {} = {}\n", constant, value));
+        previous = Some((base, offset));
+    }
+
+    format!("/* Values of {}:\n{} */\n\n", name, values.concat())
+}
+
+/// Generate the assertion of the width of pointers, which `uintptr_t` has.
+fn pointer_width_assert(lib_name: &str, bits: u32) -> String {
+    format!(
+        "\n#ifdef __cplusplus\n\
+         static_assert(sizeof(uintptr_t) == {0}, \"{1} requires {2}-bit pointers\");\n\
+         #else\n\
+         _Static_assert(sizeof(uintptr_t) == {0}, \"{1} requires {2}-bit pointers\");\n\
+         #endif\n",
+        bits / 8,
+        lib_name,
+        bits
+    )
+}
+
+/// Sort the headers so that they come after the headers they depend on, and in alphabetical
+/// order otherwise. Every header is kept, including the ones without any dependency.
+fn sort_headers(depgraph: &Graph<String, String>) -> Vec<NodeIndex> {
+    let mut in_degrees: HashMap<NodeIndex, usize> = depgraph
+        .node_indices()
+        .map(|node| {
+            let deps = depgraph.neighbors_directed(node, Direction::Incoming);
+            (node, deps.count())
+        })
+        .collect();
+    let mut ready: BTreeSet<(&str, NodeIndex)> = in_degrees
+        .iter()
+        .filter(|&(_, &degree)| degree == 0)
+        .map(|(&node, _)| (&*depgraph[node], node))
+        .collect();
+
+    let mut sorted = Vec::new();
+    while let Some(&next) = ready.iter().next() {
+        let _ = ready.remove(&next);
+        let (_, node) = next;
+        sorted.push(node);
+        for dependent in depgraph.neighbors_directed(node, Direction::Outgoing) {
+            let degree = unwrap!(in_degrees.get_mut(&dependent));
+            *degree -= 1;
+            if *degree == 0 {
+                let _ = ready.insert((&*depgraph[dependent], dependent));
+            }
+        }
     }
+
+    // Headers depending on each other can't be sorted, but their include guards make any order
+    // work, so they're included last.
+    let mut cyclic: Vec<_> = in_degrees
+        .into_iter()
+        .filter(|&(_, degree)| degree > 0)
+        .map(|(node, _)| (&*depgraph[node], node))
+        .collect();
+    cyclic.sort();
+    sorted.extend(cyclic.into_iter().map(|(_, node)| node));
+
+    sorted
+}
+
+/// Generate the check of a platform requirement.
+fn platform_requirement(guard: &str, message: &str) -> String {
+    format!(
+        "#if !({})\n#error \"{}\"\n#endif\n\n",
+        guard,
+        message.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Generate the version macros for the library.
+fn version_macros(lib_name: &str, major: u32, minor: u32, patch: u32) -> String {
+    let prefix = sanitise_id(lib_name).to_uppercase();
+
+    format!(
+        "#define {0}_VERSION_MAJOR {1}\n\
+         #define {0}_VERSION_MINOR {2}\n\
+         #define {0}_VERSION_PATCH {3}\n\
+         #define {0}_VERSION \"{1}.{2}.{3}\"\n\n",
+        prefix, major, minor, patch
+    )
 }
 
 /// Wrap a block of code with an extern declaration.
@@ -700,6 +3434,195 @@ extern "C" {{
     )
 }
 
+/// Definitions of the `stdint.h` and `stdbool.h` types for C89 headers.
+const C89_TYPES: &str = "#ifndef bindgen_c89_types
+#define bindgen_c89_types
+typedef signed char int8_t;
+typedef short int16_t;
+typedef int int32_t;
+typedef long long int64_t;
+typedef unsigned char uint8_t;
+typedef unsigned short uint16_t;
+typedef unsigned int uint32_t;
+typedef unsigned long long uint64_t;
+#ifdef _WIN64
+typedef long long intptr_t;
+typedef unsigned long long uintptr_t;
+#else
+typedef long intptr_t;
+typedef unsigned long uintptr_t;
+#endif
+typedef unsigned char bool;
+#endif
+";
+
+/// Optimizer hints given with `#[ffi(...)]` on functions, with the macros they're declared with.
+const FN_HINTS: &[(&str, &str)] = &[
+    ("pure", "BINDGEN_PURE"),
+    ("const", "BINDGEN_CONST"),
+    ("hot", "BINDGEN_HOT"),
+    ("cold", "BINDGEN_COLD"),
+];
+
+/// Definitions of the optimizer hint macros, which are empty on compilers without GCC attributes,
+/// and of the `noreturn` specifier of the compiler.
+const FN_HINT_MACROS: &str = "#ifndef bindgen_fn_hints
+#define bindgen_fn_hints
+#if defined(__GNUC__) || defined(__clang__)
+#define BINDGEN_PURE __attribute__((pure))
+#define BINDGEN_CONST __attribute__((const))
+#define BINDGEN_HOT __attribute__((hot))
+#define BINDGEN_COLD __attribute__((cold))
+#else
+#define BINDGEN_PURE
+#define BINDGEN_CONST
+#define BINDGEN_HOT
+#define BINDGEN_COLD
+#endif
+#if defined(__cplusplus) && __cplusplus >= 201103L
+#define BINDGEN_NORETURN [[noreturn]]
+#elif defined(__STDC_VERSION__) && __STDC_VERSION__ >= 201112L
+#define BINDGEN_NORETURN _Noreturn
+#elif defined(_MSC_VER)
+#define BINDGEN_NORETURN __declspec(noreturn)
+#elif defined(__GNUC__) || defined(__clang__)
+#define BINDGEN_NORETURN __attribute__((noreturn))
+#else
+#define BINDGEN_NORETURN
+#endif
+#endif
+";
+
+/// Rewrite generated declarations into C89: `//` comments become `/* */` comments, functions
+/// aren't `inline` and enumerator lists have no trailing commas.
+fn to_c89(code: &str) -> String {
+    let mut c89 = String::new();
+    for line in code.lines() {
+        let comment = line.trim_start();
+        if comment.starts_with("//") {
+            let indent = &line[..line.len() - comment.len()];
+            let text = comment.trim_start_matches('/').replace("*/", "* /");
+            c89.push_str(&format!("{}/*{} */\n", indent, text));
+        } else {
+            c89.push_str(&line.replace("static inline ", "static "));
+            c89.push('\n');
+        }
+    }
+
+    c89.replace(",\n}", "\n}")
+}
+
+/// Check that the compiler supports an optional floating-point type, using the macros GCC and
+/// Clang define along with it.
+fn float_extension_check(ty: &str) -> String {
+    let macro_name = match ty {
+        "_Float16" => "__FLT16_MANT_DIG__",
+        _ => "__FLT128_MANT_DIG__",
+    };
+    format!(
+        "#ifndef {}\n#error \"{} is not supported by this compiler\"\n#endif\n",
+        macro_name, ty
+    )
+}
+
+/// Replace the type parameters of a generic alias with concrete types.
+fn substitute_params(ty: P<ast::Ty>, params: &HashMap<ast::Name, P<ast::Ty>>) -> P<ast::Ty> {
+    if let ast::TyKind::Path(None, ref path) = ty.node {
+        if path.segments.len() == 1 && path.segments[0].parameters.is_none() {
+            if let Some(concrete) = params.get(&path.segments[0].identifier.name) {
+                return concrete.clone();
+            }
+        }
+    }
+
+    ty.map(|mut ty| {
+        ty.node = match ty.node {
+            ast::TyKind::Ptr(mt) => ast::TyKind::Ptr(ast::MutTy {
+                ty: substitute_params(mt.ty, params),
+                mutbl: mt.mutbl,
+            }),
+            ast::TyKind::Rptr(lifetime, mt) => ast::TyKind::Rptr(
+                lifetime,
+                ast::MutTy {
+                    ty: substitute_params(mt.ty, params),
+                    mutbl: mt.mutbl,
+                },
+            ),
+            ast::TyKind::Array(inner, len) => {
+                ast::TyKind::Array(substitute_params(inner, params), len)
+            }
+            ast::TyKind::BareFn(bare_fn) => ast::TyKind::BareFn(bare_fn.map(|mut bare_fn| {
+                bare_fn.decl = bare_fn.decl.map(|mut decl| {
+                    for arg in &mut decl.inputs {
+                        arg.ty = substitute_params(arg.ty.clone(), params);
+                    }
+                    if let ast::FunctionRetTy::Ty(ref mut output) = decl.output {
+                        *output = substitute_params(output.clone(), params);
+                    }
+                    decl
+                });
+                bare_fn
+            })),
+            node => node,
+        };
+        ty
+    })
+}
+
+/// Part of the name of an alias instantiation for a concrete type, e.g. `Foo` for `Foo` and
+/// `const_u8` for `*const u8`.
+fn instantiation_suffix(ty: &ast::Ty) -> String {
+    let name = pprust::ty_to_string(ty).replace('*', "");
+    let words: Vec<_> = name
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .collect();
+    words.join("_")
+}
+
+/// End the text with exactly one newline and collapse runs of three or more blank lines into
+/// two.
+fn normalise_blank_lines(text: &str) -> String {
+    let mut normalised = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.trim_end().lines() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines > 2 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        normalised.push_str(line);
+        normalised.push('\n');
+    }
+    normalised
+}
+
+/// Turn text into a C block comment, e.g. `/* text */`, with a line for each line of the text.
+fn block_comment(text: &str) -> String {
+    let text = text.replace("*/", "* /");
+    if !text.contains('\n') {
+        return format!("/* {} */\n", text);
+    }
+
+    let mut comment = String::from("/*\n");
+    for line in text.lines() {
+        comment.push_str(format!(" * {}", line).trim_end());
+        comment.push('\n');
+    }
+    comment.push_str(" */\n");
+    comment
+}
+
+/// Turn text into line comments starting with the given marker, e.g. `# text`.
+fn line_comment(marker: &str, text: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}\n", format!("{} {}", marker, line).trim_end()))
+        .collect()
+}
+
 /// Wrap a block of code with an include-guard.
 fn wrap_guard(code: &str, id: &str) -> String {
     format!(
@@ -716,9 +3639,75 @@ fn wrap_guard(code: &str, id: &str) -> String {
     )
 }
 
-/// Transform a module name into a header name
-fn header_name(module: &[String], lib_name: &str) -> Result<String, Error> {
+/// Contents of a pkg-config file, relative to the default installation prefix.
+fn pkg_config(lib_name: &str, name: &str, version: &str, libs: &str) -> String {
+    format!(
+        "prefix=/usr/local\n\
+         includedir=${{prefix}}/include\n\
+         libdir=${{prefix}}/lib\n\
+         \n\
+         Name: {}\n\
+         Description: C bindings of {}\n\
+         Version: {}\n\
+         Cflags: -I${{includedir}}\n\
+         Libs: -L${{libdir}} {}\n",
+        name, lib_name, version, libs
+    )
+}
+
+/// CMake code setting `<LIB>_LIB_NAME` and `<LIB>_HEADERS`, and installing the headers into
+/// `include`, keeping their directory layout. Paths are relative to the CMake file.
+fn cmake_snippet(lib_name: &str, outputs: &Outputs) -> String {
+    let prefix = lib_name.to_screaming_snake_case();
+    let headers: BTreeSet<String> = outputs
+        .keys()
+        .filter(|name| name.ends_with(".h"))
+        .map(|name| name.replace(path::MAIN_SEPARATOR, "/"))
+        .collect();
+
+    let mut cmake = format!(
+        "set({}_LIB_NAME \"{}\")\nset({}_HEADERS\n",
+        prefix, lib_name, prefix
+    );
+    for header in &headers {
+        cmake.push_str(&format!("\t\"${{CMAKE_CURRENT_LIST_DIR}}/{}\"\n", header));
+    }
+    cmake.push_str(")\n\n");
+
+    for header in &headers {
+        let destination = match header.rfind('/') {
+            Some(index) => format!("include/{}", &header[..index]),
+            None => "include".to_string(),
+        };
+        cmake.push_str(&format!(
+            "install(FILES \"${{CMAKE_CURRENT_LIST_DIR}}/{}\" DESTINATION {})\n",
+            header, destination
+        ));
+    }
+
+    cmake
+}
+
+/// Remove the `#include` directives already present earlier in the code, keeping the first
+/// occurrence so the dependency order is preserved.
+fn dedup_includes(code: &str) -> String {
+    let mut seen = BTreeSet::new();
+    code.lines()
+        .filter(|line| !line.trim_start().starts_with("#include") || seen.insert(line.trim()))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Transform a module name into a header name.
+///
+/// With `flat` set the module segments are joined with `_` instead of forming a directory tree.
+fn header_name(module: &[String], lib_name: &str, flat: bool) -> Result<String, Error> {
     let mut module_name: Vec<String> = module.to_vec();
+    // Items at the root of a crate without a module path belong to the library, like the ones of
+    // the `ffi` module. The `lib_name.h` header is the one including all the others.
+    if module_name.is_empty() {
+        module_name.push("ffi".to_string());
+    }
     if module_name[0] == "ffi" {
         module_name[0] = lib_name.to_string();
 
@@ -728,11 +3717,117 @@ fn header_name(module: &[String], lib_name: &str) -> Result<String, Error> {
         }
     }
 
-    let header_name = format!("{}.h", module_name.join(&path::MAIN_SEPARATOR.to_string()));
+    let separator = if flat {
+        "_".to_string()
+    } else {
+        path::MAIN_SEPARATOR.to_string()
+    };
+    let header_name = format!("{}.h", module_name.join(&separator));
 
     Ok(header_name)
 }
 
+/// Names of the standard C (and common POSIX) headers.
+const STD_HEADERS: &[&str] = &[
+    "assert",
+    "complex",
+    "ctype",
+    "errno",
+    "fenv",
+    "float",
+    "inttypes",
+    "iso646",
+    "limits",
+    "locale",
+    "math",
+    "setjmp",
+    "signal",
+    "stdalign",
+    "stdarg",
+    "stdatomic",
+    "stdbool",
+    "stddef",
+    "stdint",
+    "stdio",
+    "stdlib",
+    "stdnoreturn",
+    "string",
+    "tgmath",
+    "threads",
+    "time",
+    "uchar",
+    "wchar",
+    "wctype",
+    "fcntl",
+    "pthread",
+    "strings",
+    "unistd",
+];
+
+/// Check whether the file name of the header is the name of a standard header, ignoring case.
+fn is_std_header(header: &str) -> bool {
+    let file_name = header.rsplit(path::MAIN_SEPARATOR).next().unwrap_or(header);
+    file_name
+        .to_lowercase()
+        .strip_suffix(".h")
+        .map(|stem| STD_HEADERS.contains(&stem))
+        .unwrap_or(false)
+}
+
+/// Prefix the file name of the header with the library name, e.g. `lib/lib_string.h`.
+fn prefix_file_name(header: &str, lib_name: &str) -> String {
+    match header.rfind(path::MAIN_SEPARATOR) {
+        Some(index) => format!("{}{}_{}", &header[..=index], lib_name, &header[index + 1..]),
+        None => format!("{}_{}", lib_name, header),
+    }
+}
+
+/// If the attribute is a docstring, turn it into a well-formed C comment.
+pub(crate) fn retrieve_docstring(attr: &ast::Attribute, prepend: &str) -> Option<String> {
+    common::retrieve_docstring(attr, "")
+        .map(|docs| format_docstring(docs.trim_end_matches('\n'), prepend))
+}
+
+/// Render the contents of a doc attribute as a C comment.
+///
+/// Sugared `///` comments are kept as they are, block comments have any `*/` which would close
+/// the C comment prematurely escaped, and plain `#[doc = "..."]` attributes become `///` comments.
+/// Every line is indented with `prepend`, with the leading `*` of the continuation lines of block
+/// comments aligned under the opening `/**`, or dropped once they become `///` comments.
+fn format_docstring(docs: &str, prepend: &str) -> String {
+    if docs.starts_with("//") {
+        format!("{}{}\n", prepend, docs)
+    } else if let Some(inner) = docs
+        .strip_prefix("/*")
+        .and_then(|docs| docs.strip_suffix("*/"))
+        .filter(|inner| !inner.is_empty())
+    {
+        // The marker of block doc comments (`/**` or `/*!`) is replaced by the C one.
+        let inner = inner.strip_prefix(['*', '!']).unwrap_or(inner);
+        let comment = format!("/**{}*/", inner.replace("*/", "* /"));
+        comment
+            .lines()
+            .enumerate()
+            .map(|(index, line)| match line.trim_start() {
+                line if index > 0 && line.starts_with('*') => format!("{} {}\n", prepend, line),
+                _ => format!("{}{}\n", prepend, line),
+            })
+            .collect()
+    } else {
+        docs.lines()
+            .enumerate()
+            .map(|(index, line)| match line.trim_start() {
+                // Unsugared block comments keep the indentation and the leading `*` of their
+                // continuation lines, which are dropped like rustdoc does.
+                trimmed if index > 0 && trimmed.starts_with('*') => {
+                    format!("{}///{}\n", prepend, trimmed[1..].trim_end())
+                }
+                _ => format!("{}///{}\n", prepend, line),
+            })
+            .collect()
+    }
+}
+
 /// Remove illegal characters from the identifier.
 ///
 /// This is because macros names must be valid C identifiers. Note that the identifier will always
@@ -1,8 +1,12 @@
 use super::types::CType;
-use crate::lang_c::LangC;
+use crate::common::LineEnding;
+use crate::common::{parse_repr, Lang, Outputs};
+use crate::lang_c::{EmptyParams, EnumTagStyle, IncludeStyle, LangC, PackingStyle};
 use crate::syntax::codemap::FilePathMapping;
 use crate::syntax::{ast, parse};
 use crate::test_utils::fetch;
+use crate::Bindgen;
+use crate::ErrorCode;
 
 #[test]
 fn sanitise_id() {
@@ -28,6 +32,23 @@ fn ty(source: &str) -> ast::Ty {
     }
 }
 
+fn item(source: &str) -> ast::Item {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let result = {
+        let mut parser = parse::new_parser_from_source_str(&sess, "".into(), source.into());
+        parser.parse_item()
+    };
+
+    match result {
+        Ok(Some(p)) => (*p).clone(),
+        Ok(None) => panic!("internal testing error: no item in {:?}", source),
+        Err(e) => panic!(
+            "internal testing error: could not parse item from {:?}, reason: {:?}",
+            source, e
+        ),
+    }
+}
+
 #[test]
 fn pure_rust_types() {
     let type_map = [
@@ -49,11 +70,11 @@ fn pure_rust_types() {
     let name = "gabriel";
 
     for &(rust_type, ref correct_c_type) in &type_map {
-        let parsed_c_type = super::anon_rust_to_c(&ty(rust_type))
+        let parsed_c_type = super::anon_rust_to_c(&ty(rust_type), &Default::default())
             .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", rust_type));
         assert_eq!(&parsed_c_type, correct_c_type);
 
-        let parsed_c_type = super::rust_to_c(&ty(rust_type), name)
+        let parsed_c_type = super::rust_to_c(&ty(rust_type), name, &Default::default())
             .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", rust_type, name));
         assert_eq!(
             format!("{}", parsed_c_type),
@@ -88,11 +109,11 @@ fn libc_types() {
     let name = "lucifer";
 
     for &(rust_type, correct_c_type) in &type_map {
-        let parsed_c_type = super::anon_rust_to_c(&ty(rust_type))
+        let parsed_c_type = super::anon_rust_to_c(&ty(rust_type), &Default::default())
             .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", rust_type));
         assert_eq!(format!("{}", parsed_c_type), correct_c_type);
 
-        let parsed_c_type = super::rust_to_c(&ty(rust_type), name)
+        let parsed_c_type = super::rust_to_c(&ty(rust_type), name, &Default::default())
             .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", rust_type, name));
         assert_eq!(
             format!("{}", parsed_c_type),
@@ -106,22 +127,22 @@ fn const_pointers() {
     let name = "maalik";
 
     let source = "*const u8";
-    let parsed_type = super::anon_rust_to_c(&ty(source))
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", source));
     assert_eq!(format!("{}", parsed_type), "uint8_t const*");
 
     let source = "*const ()";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(format!("{}", parsed_type), format!("void const* {}", name));
 
     let source = "*const *const f64";
-    let parsed_type = super::anon_rust_to_c(&ty(source))
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", source));
     assert_eq!(format!("{}", parsed_type), "double const* const*");
 
     let source = "*const *const i64";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(
         format!("{}", parsed_type),
@@ -134,22 +155,22 @@ fn mut_pointers() {
     let name = "raphael";
 
     let source = "*mut u16";
-    let parsed_type = super::anon_rust_to_c(&ty(source))
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", source));
     assert_eq!(format!("{}", parsed_type), "uint16_t*");
 
     let source = "*mut f32";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(format!("{}", parsed_type), format!("float* {}", name));
 
     let source = "*mut *mut *mut i32";
-    let parsed_type = super::anon_rust_to_c(&ty(source))
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", source));
     assert_eq!(format!("{}", parsed_type), "int32_t***");
 
     let source = "*mut *mut i8";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(format!("{}", parsed_type), format!("int8_t** {}", name));
 }
@@ -159,12 +180,12 @@ fn mixed_pointers() {
     let name = "samael";
 
     let source = "*const *mut *const bool";
-    let parsed_type = super::anon_rust_to_c(&ty(source))
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", source));
     assert_eq!(format!("{}", parsed_type), "bool const** const*");
 
     let source = "*mut *mut *const libc::c_ulonglong";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(
         format!("{}", parsed_type),
@@ -172,7 +193,7 @@ fn mixed_pointers() {
     );
 
     let source = "*const *mut *mut i8";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(
         format!("{}", parsed_type),
@@ -185,14 +206,14 @@ fn function_pointers() {
     let name = "sariel";
 
     let source = "fn(a: bool)";
-    let parsed_type = super::anon_rust_to_c(&ty(source));
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default());
     assert!(
         parsed_type.is_err(),
         "C function pointers should have an inner or name associated"
     );
 
     // let source = "fn(a: i8) -> f64";
-    // let parsed_type = super::rust_to_c(&ty(source), name).unwrap_or_else(|_| panic!(
+    // let parsed_type = super::rust_to_c(&ty(source), name, &Default::default()).unwrap_or_else(|_| panic!(
     //     "error while parsing {:?} with name {:?}",
     //     source,
     //     name
@@ -200,7 +221,7 @@ fn function_pointers() {
     // assert!(parsed_type.is_none(), "parsed a non-C function pointer");
 
     let source = "extern fn(hi: libc::c_int) -> libc::c_double";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(
         format!("{}", parsed_type),
@@ -213,24 +234,24 @@ fn paths() {
     let name = "zachariel";
 
     let source = "MyType";
-    let parsed_type = super::anon_rust_to_c(&ty(source))
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", source));
     assert_eq!(format!("{}", parsed_type), "MyType");
 
     let source = "SomeType";
-    let parsed_type = super::rust_to_c(&ty(source), name)
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default())
         .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", source, name));
     assert_eq!(format!("{}", parsed_type), format!("SomeType {}", name));
 
     let source = "my_mod::MyType";
-    let parsed_type = super::anon_rust_to_c(&ty(source));
+    let parsed_type = super::anon_rust_to_c(&ty(source), &Default::default());
     assert!(
         parsed_type.is_err(),
         "can't use a multi-segment path which isn't `libc`"
     );
 
     let source = "some_mod::SomeType";
-    let parsed_type = super::rust_to_c(&ty(source), name);
+    let parsed_type = super::rust_to_c(&ty(source), name, &Default::default());
     assert!(
         parsed_type.is_err(),
         "can't use a multi-segment path which isn't `libc`"
@@ -250,7 +271,7 @@ fn async_functions() {
     });
 
     let expected = indoc!(
-        "\n\n#ifndef bindgen_h
+        "/* DO NOT EDIT: this file is generated by safe_bindgen for backend. */\n\n#ifndef bindgen_h
 #define bindgen_h
 
 
@@ -264,7 +285,6 @@ extern \"C\" {
 void foo(void* user_data, void (*o_cb)(void* user_data, FfiResult const* result));
 
 
-
 #ifdef __cplusplus
 }
 #endif
@@ -293,7 +313,7 @@ fn sync_functions() {
     });
 
     let expected = indoc!(
-        "\n\n#ifndef bindgen_h
+        "/* DO NOT EDIT: this file is generated by safe_bindgen for backend. */\n\n#ifndef bindgen_h
 #define bindgen_h
 
 
@@ -307,6 +327,41 @@ extern \"C\" {
 FfiResult const* new_block(uint8_t const* payload, uint8_t const* const* public_ids, Vote const* const* votes, uintptr_t votes_len, Block const** o_block);
 
 
+#ifdef __cplusplus
+}
+#endif
+
+
+#endif\n\n"
+    );
+
+    let actual = fetch(&outputs, ".h");
+
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn empty_enums() {
+    let outputs = compile!(LangC::default(), {
+        #[repr(C)]
+        pub enum App {}
+    });
+
+    let expected = indoc!(
+        "/* DO NOT EDIT: this file is generated by safe_bindgen for backend. */\n\n#ifndef bindgen_h
+#define bindgen_h
+
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+#include <stdint.h>
+#include <stdbool.h>
+
+/* Opaque handle type (uninhabited in Rust) */
+typedef struct App App;
+
 
 #ifdef __cplusplus
 }
@@ -320,3 +375,3027 @@ FfiResult const* new_block(uint8_t const* payload, uint8_t const* const* public_
 
     assert_multiline_eq!(actual, expected);
 }
+
+#[test]
+fn crlf_line_endings() {
+    let mut lang = LangC::new();
+    lang.set_line_ending(LineEnding::CrLf);
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("void foo(void);\r\n"));
+    assert!(!actual.replace("\r\n", "").contains('\n'));
+
+    let actual = fetch(&outputs, "backend.h");
+    assert!(actual.contains("#include \".h\"\r\n"));
+    assert!(!actual.replace("\r\n", "").contains('\n'));
+}
+
+#[test]
+fn atomic_types() {
+    let type_map = [
+        ("std::sync::atomic::AtomicBool", "bool"),
+        ("std::sync::atomic::AtomicI32", "int32_t"),
+        ("std::sync::atomic::AtomicU32", "uint32_t"),
+        ("core::sync::atomic::AtomicUsize", "uintptr_t"),
+        ("std::sync::atomic::AtomicU64", "uint64_t"),
+    ];
+
+    let name = "uriel";
+
+    for &(rust_type, correct_c_type) in &type_map {
+        let parsed_c_type = super::rust_to_c(&ty(rust_type), name, &Default::default())
+            .unwrap_or_else(|_| panic!("error while parsing {:?} with name {:?}", rust_type, name));
+        assert_eq!(
+            format!("{}", parsed_c_type),
+            format!("{} {}", correct_c_type, name)
+        );
+    }
+
+    // Bare names aren't known to be atomics.
+    let parsed_c_type = unwrap!(super::rust_to_c(
+        &ty("AtomicU64"),
+        name,
+        &Default::default()
+    ));
+    assert_eq!(format!("{}", parsed_c_type), "AtomicU64 uriel");
+
+    let source = "std::sync::atomic::AtomicFoo";
+    assert!(super::anon_rust_to_c(&ty(source), &Default::default()).is_err());
+}
+
+#[test]
+fn header_names() {
+    let module: Vec<String> = vec!["ffi".into(), "ipc".into(), "req".into()];
+    let sep = ::std::path::MAIN_SEPARATOR;
+
+    assert_eq!(
+        unwrap!(super::header_name(&module, "safe_app", false)),
+        format!("safe_app{0}ipc{0}req.h", sep)
+    );
+    assert_eq!(
+        unwrap!(super::header_name(&module, "safe_app", true)),
+        "safe_app_ipc_req.h"
+    );
+    assert_eq!(
+        unwrap!(super::header_name(&module[..1], "safe_app", true)),
+        "safe_app_safe_app.h"
+    );
+}
+
+#[test]
+fn docstrings() {
+    assert_eq!(super::format_docstring("/// Foo", ""), "/// Foo\n");
+    assert_eq!(super::format_docstring("//! Foo", "\t"), "\t//! Foo\n");
+    assert_eq!(
+        super::format_docstring("/** Matches `*/` */", ""),
+        "/** Matches `* /` */\n"
+    );
+    assert_eq!(
+        super::format_docstring("/*! Inner\n * docs */", ""),
+        "/** Inner\n * docs */\n"
+    );
+    assert_eq!(
+        super::format_docstring(" Foo */\n Bar", "\t"),
+        "\t/// Foo */\n\t/// Bar\n"
+    );
+    assert_eq!(
+        super::format_docstring("/** Inner\n     * docs\n     */", "\t"),
+        "\t/** Inner\n\t * docs\n\t */\n"
+    );
+    assert_eq!(super::format_docstring("/*é*/", ""), "/**é*/\n");
+    assert_eq!(super::format_docstring("/**é */", ""), "/**é */\n");
+}
+
+#[test]
+fn multi_line_variant_docs() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub enum Status {
+            /// The operation succeeded,
+            /// and the result is
+            /// ready to be read.
+            Ok,
+            /** The operation failed,
+             * see the logs
+             * for the details. */
+            Failed,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let expected = "typedef enum Status {\n\
+                    \t/// The operation succeeded,\n\
+                    \t/// and the result is\n\
+                    \t/// ready to be read.\n\
+                    \tStatus_Ok,\n\
+                    \t/// The operation failed,\n\
+                    \t/// see the logs\n\
+                    \t/// for the details.\n\
+                    \tStatus_Failed,\n\
+                    } Status;";
+    assert!(actual.contains(expected), "{}", actual);
+}
+
+#[test]
+fn doc_attributes() {
+    let outputs = compile!(LangC::default(), {
+        /// Returns `*/`.
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("/// Returns `*/`.\nvoid foo(void);\n"));
+}
+
+#[test]
+fn without_cpp_guard() {
+    let mut lang = LangC::new();
+    lang.cpp_guard(false);
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let expected = indoc!(
+        "/* DO NOT EDIT: this file is generated by safe_bindgen for backend. */\n\n#ifndef bindgen_h
+#define bindgen_h
+
+#include <stdint.h>
+#include <stdbool.h>
+
+void foo(void);
+
+
+#endif\n\n"
+    );
+
+    let actual = fetch(&outputs, ".h");
+
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn array_fields() {
+    let outputs = compile!(LangC::default(), {
+        #[repr(C)]
+        pub struct Account {
+            name: [c_char; 64],
+            key: [u8; 32],
+            owner: [libc::c_char; 16],
+        }
+
+        #[no_mangle]
+        pub extern "C" fn foo(key: [u8; 32]) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Account {\n\tchar name[64];\n\tuint8_t key[32];\n\tchar owner[16];\n} Account;"
+    ));
+    assert!(actual.contains("void foo(uint8_t const* key);"));
+}
+
+#[test]
+fn type_resolver() {
+    let mut lang = LangC::new();
+    lang.set_type_resolver(Box::new(|ty| match ty {
+        "Uuid" | "uuid::Uuid" => Some("uuid_t".to_string()),
+        _ => None,
+    }));
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Entry {
+            id: Uuid,
+            parent: *const uuid::Uuid,
+            data: *const Data,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Entry {\n\tuuid_t id;\n\tuuid_t const* parent;\n\tData const* data;\n} Entry;"
+    ));
+}
+
+#[test]
+fn generic_array_fields() {
+    let errors = unwrap!(try_compile!(LangC::default(), {
+        #[repr(C)]
+        pub struct Buffer<N> {
+            data: [u8; N],
+        }
+    })
+    .err());
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.contains("`data`"));
+    assert!(errors[0].message.contains("generic parameter `N`"));
+}
+
+#[test]
+fn system_includes() {
+    let mut lang = LangC::new();
+    lang.include_style(IncludeStyle::System {
+        prefix: Some("safe".to_string()),
+    });
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, "backend.h");
+    assert!(actual.contains("#include <safe/.h>\n"));
+}
+
+#[test]
+fn version_macros() {
+    let mut lang = LangC::new();
+    lang.set_lib_name("safe-app");
+    lang.set_version(0, 9, 1);
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, "safe-app.h");
+    assert!(actual.contains(
+        "#define SAFEAPP_VERSION_MAJOR 0
+#define SAFEAPP_VERSION_MINOR 9
+#define SAFEAPP_VERSION_PATCH 1
+#define SAFEAPP_VERSION \"0.9.1\"
+"
+    ));
+}
+
+#[test]
+fn nested_pointers() {
+    let type_map = [
+        ("*mut *mut libc::c_void", "void**"),
+        ("*mut *const u8", "uint8_t const**"),
+        ("*const *mut u8", "uint8_t* const*"),
+        ("*const *const libc::c_char", "char const* const*"),
+        ("*mut *mut *mut Handle", "Handle***"),
+        ("*const *mut *const u32", "uint32_t const** const*"),
+        ("*mut *const *mut u32", "uint32_t* const**"),
+    ];
+
+    for &(rust_type, correct_c_type) in &type_map {
+        let parsed_c_type = super::anon_rust_to_c(&ty(rust_type), &Default::default())
+            .unwrap_or_else(|_| panic!("error while parsing {:?} with no name", rust_type));
+        assert_eq!(format!("{}", parsed_c_type), correct_c_type);
+    }
+}
+
+#[test]
+fn allowlist_only() {
+    let mut lang = LangC::new();
+    lang.allowlist_only(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Unused {
+            a: i32,
+        }
+
+        #[repr(C)]
+        pub struct Inner {
+            a: i32,
+        }
+
+        #[repr(C)]
+        pub struct Outer {
+            inner: *const Inner,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn internal() {}
+
+        #[ffi(export)]
+        #[no_mangle]
+        pub extern "C" fn exported(outer: *const Outer) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(!actual.contains("Unused"));
+    assert!(!actual.contains("internal"));
+    assert!(actual.contains("void exported(Outer const* outer);"));
+
+    let inner = unwrap!(actual.find("typedef struct Inner"));
+    let outer = unwrap!(actual.find("typedef struct Outer"));
+    let exported = unwrap!(actual.find("void exported"));
+    assert!(inner < outer && outer < exported);
+}
+
+#[test]
+fn allowlist_only_dependency_order() {
+    let mut lang = LangC::new();
+    lang.allowlist_only(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct A {
+            b: B,
+        }
+
+        #[repr(C)]
+        pub struct B {
+            x: i32,
+        }
+
+        #[ffi(export)]
+        #[repr(C)]
+        pub struct E {
+            a: A,
+            b: B,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let b = unwrap!(actual.find("typedef struct B"));
+    let a = unwrap!(actual.find("typedef struct A"));
+    let e = unwrap!(actual.find("typedef struct E"));
+    assert!(b < a && a < e);
+    assert_eq!(actual.matches("typedef struct B {").count(), 1);
+}
+
+#[test]
+fn allowlist_only_same_names() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            pub mod net {
+                #[repr(C)]
+                pub struct Config {
+                    port: u16,
+                }
+
+                #[ffi(export)]
+                #[repr(C)]
+                pub struct Server {
+                    config: Config,
+                }
+            }
+
+            pub mod disk {
+                #[repr(C)]
+                pub struct Config {
+                    path_len: u32,
+                }
+
+                #[ffi(export)]
+                #[repr(C)]
+                pub struct Volume {
+                    config: Config,
+                }
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    lang.allowlist_only(true);
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    // Each module gets its own `Config`, rather than the last one declared.
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let net = fetch(&outputs, &format!("backend{}net.h", sep));
+    assert!(net.contains("typedef struct Config {\n\tuint16_t port;\n} Config;"));
+    assert!(!net.contains("path_len"));
+    let disk = fetch(&outputs, &format!("backend{}disk.h", sep));
+    assert!(disk.contains("typedef struct Config {\n\tuint32_t path_len;\n} Config;"));
+    assert!(!disk.contains("port"));
+}
+
+#[test]
+fn transparent_structs() {
+    let outputs = compile!(LangC::default(), {
+        #[repr(C)]
+        #[ffi(transparent)]
+        pub struct Seconds {
+            value: u64,
+        }
+
+        #[repr(transparent)]
+        pub struct Handle(*mut c_void);
+
+        #[repr(C)]
+        pub struct Timeout {
+            value: u64,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef uint64_t Seconds;\n"));
+    assert!(actual.contains("typedef void* Handle;\n"));
+    assert!(actual.contains("typedef struct Timeout {\n\tuint64_t value;\n} Timeout;\n"));
+
+    // Structs which can't collapse are regular structs if they're `#[repr(C)]`, or skipped.
+    let mut bindgen = unwrap!(Bindgen::new());
+    bindgen.source_code(
+        "ffi/src/lib.rs",
+        "#[repr(C)]\n#[ffi(transparent)]\npub struct Point {\n    x: i32,\n    y: i32,\n}\n\
+         #[repr(transparent)]\npub struct Meters(f64, PhantomData<u8>);\n\
+         #[repr(transparent)]\npub struct Wrapper<T>(T);\n\
+         #[repr(transparent)]\npub struct Pair(u32, u32);\n",
+    );
+
+    let mut lang = LangC::new();
+    lang.trace_skipped_items(true);
+    let mut outputs = Outputs::default();
+    unwrap!(bindgen.compile(&mut lang, &mut outputs, true));
+
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let actual = fetch(&outputs, &format!("backend{}backend.h", sep));
+    assert!(actual.contains("typedef struct Point {\n\tint32_t x;\n\tint32_t y;\n} Point;\n"));
+    assert!(actual.contains("typedef double Meters;\n"));
+    assert!(!actual.contains("Wrapper"));
+    assert!(!actual.contains("Pair"));
+
+    let notes: Vec<_> = lang.notes().iter().map(|note| &note.message).collect();
+    assert_eq!(
+        notes,
+        [
+            "the struct `Wrapper` is skipped, as it's transparent but generic or without exactly \
+             one non-zero-sized field",
+            "the struct `Pair` is skipped, as it's transparent but generic or without exactly one \
+             non-zero-sized field",
+        ]
+    );
+}
+
+#[test]
+fn export_names() {
+    let outputs = compile!(LangC::default(), {
+        #[no_mangle]
+        #[export_name = "safe_app_free"]
+        pub extern "C" fn app_free(app: *mut App) {}
+
+        // `link_name` only renames foreign items.
+        #[no_mangle]
+        #[link_name = "safe_app_clear"]
+        pub extern "C" fn app_clear(app: *mut App) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("void safe_app_free(App* app);"));
+    assert!(!actual.contains("void app_free("));
+    assert!(actual.contains("void app_clear(App* app);"));
+}
+
+#[test]
+fn error_codes() {
+    let errors = unwrap!(try_compile!(LangC::default(), {
+        #[repr(C)]
+        pub struct Wrapper<T> {
+            inner: T,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn abort() -> ! {
+            loop {}
+        }
+    })
+    .err());
+
+    let codes: Vec<_> = errors.iter().map(|error| error.code).collect();
+    assert_eq!(
+        codes,
+        vec![Some(ErrorCode::GenericStruct), Some(ErrorCode::NeverReturn)]
+    );
+    assert!(format!("{}", errors[0]).starts_with("error[E_GENERIC_STRUCT]: "));
+}
+
+#[test]
+fn default_initializers() {
+    let mut lang = LangC::new();
+    lang.set_default_initializers(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        #[derive(Clone, Default)]
+        pub struct AppConfig {
+            retries: u32,
+        }
+
+        #[repr(C)]
+        #[ffi(default = "{ 80, true }")]
+        pub struct Server {
+            port: u16,
+            enabled: bool,
+        }
+
+        #[repr(C)]
+        pub struct Point {
+            x: i32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("} AppConfig;\n\n#define APP_CONFIG_DEFAULT {0}\n"));
+    assert!(actual.contains("} Server;\n\n#define SERVER_DEFAULT { 80, true }\n"));
+    assert!(!actual.contains("POINT_DEFAULT"));
+}
+
+#[test]
+fn repr_attributes() {
+    let repr = parse_repr(&item("#[repr(packed, C)] struct Foo;").attrs);
+    assert!(repr.c);
+    assert_eq!(repr.packed, Some(1));
+
+    let repr = parse_repr(&item("#[repr(C)] #[repr(packed(2))] struct Foo;").attrs);
+    assert!(repr.c);
+    assert_eq!(repr.packed, Some(2));
+
+    let repr = parse_repr(&item("#[repr(align(8), C)] struct Foo;").attrs);
+    assert!(repr.c);
+    assert_eq!(repr.align, Some(8));
+    assert_eq!(repr.packed, None);
+
+    let repr = parse_repr(&item("#[repr(u8)] enum Foo {}").attrs);
+    assert!(!repr.c);
+    assert_eq!(repr.int, Some("u8"));
+
+    let outputs = compile!(LangC::default(), {
+        #[repr(packed, C)]
+        pub struct Header {
+            tag: u8,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct __attribute__((packed)) Header {"));
+}
+
+#[test]
+fn packed_structs() {
+    let outputs = compile!(LangC::default(), {
+        #[repr(C, packed)]
+        pub struct Header {
+            tag: u8,
+            len: u32,
+        }
+
+        #[repr(C, packed(2))]
+        pub struct Frame {
+            tag: u8,
+            len: u32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct __attribute__((packed)) Header {\n"));
+    assert!(actual.contains(
+        "#pragma pack(push, 2)\ntypedef struct Frame {\n\tuint8_t tag;\n\tuint32_t len;\n} Frame;\n#pragma pack(pop)\n"
+    ));
+
+    let mut lang = LangC::new();
+    lang.set_packing_style(PackingStyle::Pragma);
+
+    let outputs = compile!(lang, {
+        #[repr(C, packed)]
+        pub struct Header {
+            tag: u8,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "#pragma pack(push, 1)\ntypedef struct Header {\n\tuint8_t tag;\n} Header;\n#pragma pack(pop)\n"
+    ));
+}
+
+#[test]
+fn aligned_structs() {
+    let outputs = compile!(LangC::default(), {
+        #[repr(C, align(16))]
+        pub struct Block {
+            data: u32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct __attribute__((aligned(16))) Block {\n"));
+
+    let mut lang = LangC::new();
+    lang.set_packing_style(PackingStyle::Pragma);
+
+    let outputs = compile!(lang, {
+        #[repr(C, align(16))]
+        pub struct Block {
+            data: u32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct __declspec(align(16)) Block {\n"));
+}
+
+#[test]
+fn inline_modules() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            #[no_mangle]
+            pub extern "C" fn root() {}
+
+            pub mod ipc {
+                #[no_mangle]
+                pub extern "C" fn encode() {}
+            }
+
+            mod private {
+                #[no_mangle]
+                pub extern "C" fn hidden() {}
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+
+    let sep = ::std::path::MAIN_SEPARATOR;
+    assert!(fetch(&outputs, &format!("backend{}backend.h", sep)).contains("void root(void);"));
+    assert!(fetch(&outputs, &format!("backend{}ipc.h", sep)).contains("void encode(void);"));
+    assert!(!outputs.values().any(|output| output.contains("hidden")));
+}
+
+#[test]
+fn prelude_includes() {
+    let mut lang = LangC::new();
+    lang.add_prelude_include("common_types.h");
+    lang.add_prelude_include("<stddef.h>");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "#include <stdint.h>\n#include <stdbool.h>\n#include \"common_types.h\"\n#include <stddef.h>\n\nvoid foo(void);"
+    ));
+}
+
+#[test]
+fn c_strings() {
+    let rust_types = [
+        "&std::ffi::CStr",
+        "*const std::ffi::CStr",
+        "&'static core::ffi::CStr",
+        "*mut ffi::CString",
+        "*const alloc::ffi::CString",
+    ];
+
+    for rust_type in &rust_types {
+        let error = unwrap!(super::rust_to_c(&ty(rust_type), "cstr", &Default::default()).err());
+        assert_eq!(error.code, Some(ErrorCode::CStrPointer), "{}", rust_type);
+        assert!(error.message.contains("`*const c_char`"));
+    }
+
+    // User types with the same name aren't affected.
+    let c_type = unwrap!(super::rust_to_c(
+        &ty("*const CStr"),
+        "cstr",
+        &Default::default()
+    ));
+    assert_eq!(c_type.to_string(), "CStr const* cstr");
+}
+
+#[test]
+fn internal_items() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Handle {
+            id: u32,
+        }
+
+        #[ffi(internal)]
+        #[repr(C)]
+        pub struct Cache {
+            owner: Handle,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_new() -> Handle {}
+
+        #[ffi(internal)]
+        #[no_mangle]
+        pub extern "C" fn cache_clear(cache: *mut Cache) {}
+    });
+
+    let public = fetch(&outputs, ".h");
+    assert!(public.contains("handle_new"));
+    assert!(!public.contains("Cache"));
+
+    let internal = fetch(&outputs, "_internal.h");
+    assert!(internal.contains("typedef struct Cache {\n\tHandle owner;\n} Cache;"));
+    assert!(internal.contains("void cache_clear(Cache* cache);"));
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains("#include \".h\""));
+    assert!(!root.contains("internal"));
+
+    let internal_root = fetch(&outputs, "backend_internal.h");
+    assert!(internal_root.contains("#include \"backend.h\"\n#include \"_internal.h\"\n"));
+}
+
+#[test]
+fn error_locations() {
+    let mut bindgen = unwrap!(Bindgen::new());
+    bindgen.source_code(
+        "src/lib.rs",
+        "#[repr(C)]\npub struct Foo<T> {\n    bar: T,\n}\n",
+    );
+
+    let mut outputs = Outputs::default();
+    let errors = bindgen
+        .compile(&mut LangC::new(), &mut outputs, false)
+        .unwrap_err();
+
+    let location = unwrap!(bindgen.error_location(&errors[0]));
+    assert_eq!(location.line, 2);
+    assert_eq!(location.column, 1);
+    assert_eq!(location.byte_offset, 11);
+    assert_eq!(
+        bindgen.format_error(&errors[0]),
+        "src/lib.rs:2:1: error[E_GENERIC_STRUCT]: bindgen can not handle parameterized \
+         `#[repr(C)]` structs"
+    );
+}
+
+#[test]
+fn enum_to_string() {
+    let mut lang = LangC::new();
+    lang.emit_enum_to_string(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Colour {
+            Red = 1,
+            Green,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let expected = "typedef enum Colour {\n\
+                    \tColour_Red = 1,\n\
+                    \tColour_Green,\n\
+                    } Colour;\n\n\
+                    static inline const char* Colour_to_string(Colour value) {\n\
+                    \tswitch (value) {\n\
+                    \tcase Colour_Red: return \"Red\";\n\
+                    \tcase Colour_Green: return \"Green\";\n\
+                    \tdefault: return \"<unknown>\";\n\
+                    \t}\n\
+                    }\n";
+    assert!(actual.contains(expected));
+}
+
+#[test]
+fn unit_alias() {
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        pub type Nothing = ();
+    })
+    .err());
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnitAlias));
+
+    // Unit returns in function pointer aliases are still fine.
+    let outputs = compile!(LangC::new(), {
+        pub type Callback = extern "C" fn();
+    });
+    assert!(fetch(&outputs, ".h").contains("typedef void (*Callback)(void);"));
+}
+
+#[test]
+fn c23_enums() {
+    let mut lang = LangC::new();
+    lang.c23_enums(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C, i32)]
+        pub enum Status {
+            Ok,
+            Failed = -1,
+        }
+
+        #[repr(C)]
+        pub enum Mode {
+            Read,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef enum Status : int32_t {\n\tStatus_Ok,\n"));
+    assert!(actual.contains("typedef enum Mode {\n"));
+
+    // Without the flag the integer representation is ignored.
+    let outputs = compile!(LangC::new(), {
+        #[repr(C, i32)]
+        pub enum Status {
+            Ok,
+        }
+    });
+    assert!(fetch(&outputs, ".h").contains("typedef enum Status {\n"));
+}
+
+#[test]
+fn compile_crates() {
+    let root = ::std::env::temp_dir().join("safe_bindgen_compile_crates");
+    let sources = [
+        ("ffi_a", "#[repr(C)]\npub struct Point {\n    x: i32,\n}\n"),
+        (
+            "ffi_b",
+            "#[no_mangle]\npub extern \"C\" fn origin() -> Point {}\n",
+        ),
+    ];
+
+    let bindgen = unwrap!(Bindgen::new());
+    let mut crates = Vec::new();
+    for &(name, source) in &sources {
+        let path = root.join(name).join("src").join("lib.rs");
+        unwrap!(::std::fs::create_dir_all(unwrap!(path.parent())));
+        unwrap!(::std::fs::write(&path, source));
+        crates.push(unwrap!(bindgen.parse_crate(&path)));
+    }
+
+    let mut outputs = Outputs::default();
+    unwrap!(bindgen.compile_crates(&mut LangC::new(), &mut outputs, &crates, true));
+
+    let header =
+        |suffix: &str| unwrap!(outputs.keys().find(|name| name.ends_with(suffix)).cloned());
+    let point_header = header("ffi_a.h");
+    let origin_header = header("ffi_b.h");
+    assert!(outputs[&point_header].contains("typedef struct Point {"));
+    assert!(outputs[&origin_header].contains("Point origin(void);"));
+
+    // The header declaring `Point` has to be included first.
+    let root_header = fetch(&outputs, "backend.h");
+    let point_include = unwrap!(root_header.find(&point_header));
+    let origin_include = unwrap!(root_header.find(&origin_header));
+    assert!(point_include < origin_include);
+}
+
+#[test]
+fn noreturn_fns() {
+    let outputs = compile!(LangC::new(), {
+        #[ffi(noreturn)]
+        #[no_mangle]
+        pub extern "C" fn terminate(code: i32) -> ! {
+            ::std::process::exit(code)
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(
+        actual.contains("#elif defined(_MSC_VER)\n#define BINDGEN_NORETURN __declspec(noreturn)\n")
+    );
+    assert!(actual.contains("BINDGEN_NORETURN void terminate(int32_t code);"));
+
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn terminate() -> ! {
+            loop {}
+        }
+    })
+    .err());
+    assert_eq!(errors[0].code, Some(ErrorCode::NeverReturn));
+}
+
+#[test]
+fn fn_ptr_aliases() {
+    let outputs = compile!(LangC::new(), {
+        pub type Callback = extern "C" fn(value: c_int);
+        pub type Getter = extern "C" fn(user_data: *mut c_void) -> *const c_char;
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef void (*Callback)(int value);\n"));
+    assert!(actual.contains("typedef char const* (*Getter)(void* user_data);\n"));
+}
+
+#[test]
+fn wide_chars() {
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn open(path: *const libc::wchar_t) {}
+
+        #[no_mangle]
+        pub extern "C" fn title(buf: *mut widestring::WideChar, utf16: *const widestring::U16Char) {
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("#include <stdbool.h>\n#include <wchar.h>\n"));
+    assert!(actual.contains("void open(wchar_t const* path);"));
+    assert!(actual.contains("void title(wchar_t* buf, uint16_t const* utf16);"));
+
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn close(fd: c_int) {}
+    });
+    assert!(!fetch(&outputs, ".h").contains("wchar.h"));
+}
+
+#[test]
+fn def_file() {
+    let mut lang = LangC::new();
+    lang.set_lib_name("safe_app");
+    lang.emit_def_file("safe_app.def");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn app_new() {}
+
+        #[no_mangle]
+        #[export_name = "app_release"]
+        pub extern "C" fn app_free() {}
+
+        pub extern "C" fn not_exported() {}
+    });
+
+    assert_eq!(
+        fetch(&outputs, "safe_app.def"),
+        "; DO NOT EDIT: this file is generated by safe_bindgen for safe_app.\n\n\
+         LIBRARY safe_app\nEXPORTS\n\tapp_new\n\tapp_release\n"
+    );
+}
+
+#[test]
+fn bitfields() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Flags {
+            #[ffi(bits = 3)]
+            mode: u32,
+            #[ffi(bits = 1)]
+            enabled: bool,
+            count: u16,
+            #[ffi(bits(read = 1, write = 1, level = 6))]
+            access: u8,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Flags {\n\
+         \tuint32_t mode : 3;\n\
+         \tuint32_t : 29;\n\
+         \tbool enabled : 1;\n\
+         \tuint16_t count;\n\
+         \tuint8_t read : 1;\n\
+         \tuint8_t write : 1;\n\
+         \tuint8_t level : 6;\n\
+         }"
+    ));
+
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Flags {
+            #[ffi(bits = 9)]
+            mode: u8,
+            #[ffi(bits = 2)]
+            ratio: f32,
+            #[ffi(bits(low = 4, high = 5))]
+            nibbles: u8,
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::InvalidBitfield));
+}
+
+#[test]
+fn const_ptr_returns() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Foo {
+            id: u32,
+        }
+
+        pub type Getter = extern "C" fn() -> *const Foo;
+
+        #[repr(C)]
+        pub struct Registry {
+            getter: extern "C" fn(index: usize) -> *const Foo,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn foo_get() -> *const Foo {}
+
+        #[no_mangle]
+        pub extern "C" fn foo_getter() -> extern "C" fn() -> *const Foo {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("Foo const* foo_get(void);"));
+    assert!(actual.contains("typedef Foo const* (*Getter)(void);"));
+    assert!(actual.contains("\tFoo const* (*getter)(uintptr_t index);\n"));
+    assert!(actual.contains("Foo const* (*foo_getter(void))(void);"));
+}
+
+#[test]
+fn layout_schema() {
+    let mut lang = LangC::new();
+    lang.set_layout_schema_path("layout.json");
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        #[ffi(size = 12)]
+        pub struct Point {
+            #[ffi(offset = 0)]
+            x: i32,
+            #[ffi(offset = 4)]
+            y: i32,
+            tags: [c_char; 4],
+        }
+
+        #[repr(C)]
+        pub struct Empty {}
+    });
+
+    let expected = indoc!(
+        r#"{
+          "structs": [
+            {
+              "name": "Point",
+              "size": 12,
+              "fields": [
+                { "name": "x", "type": "int32_t", "offset": 0 },
+                { "name": "y", "type": "int32_t", "offset": 4 },
+                { "name": "tags", "type": "char[4]" }
+              ]
+            },
+            {
+              "name": "Empty",
+              "fields": []
+            }
+          ]
+        }
+        "#
+    );
+    assert_multiline_eq!(fetch(&outputs, "layout.json"), expected);
+}
+
+#[test]
+fn unit_pointers() {
+    let type_map = [
+        ("*const ()", "void const*"),
+        ("*mut ()", "void*"),
+        ("*const *mut ()", "void* const*"),
+        ("*mut *const ()", "void const**"),
+    ];
+
+    for &(rust_type, correct_c_type) in &type_map {
+        let parsed_c_type = unwrap!(super::anon_rust_to_c(&ty(rust_type), &Default::default()));
+        assert_eq!(format!("{}", parsed_c_type), correct_c_type);
+    }
+}
+
+#[test]
+fn undeclared_field_types() {
+    let mut bindgen = unwrap!(Bindgen::new());
+    bindgen.source_code(
+        "ffi/src/lib.rs",
+        "#[repr(C)]\npub struct Foo {\n    bar: Bar,\n    baz: *const Baz,\n    qux: Qux,\n    \
+         kind: Kind,\n}\n\
+         #[repr(C)]\npub struct Qux {\n    x: i32,\n}\n\
+         #[repr(C)]\npub enum Kind {\n    A,\n}\n",
+    );
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(bindgen.compile(&mut lang, &mut outputs, true));
+
+    // Types declared after their use are fine.
+    let warnings = lang.warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].code, Some(ErrorCode::UndeclaredType));
+    assert_eq!(
+        warnings[0].message,
+        "field `bar` of `Foo` uses the undeclared type `Bar`"
+    );
+    assert_eq!(
+        warnings[1].message,
+        "field `baz` of `Foo` uses the undeclared type `Baz`"
+    );
+}
+
+#[test]
+fn duplicate_includes() {
+    let mut lang = LangC::new();
+    lang.add_prelude_include("<stdint.h>");
+    lang.add_prelude_include("common_types.h");
+    lang.add_prelude_include("common_types.h");
+    // `.h` is the header of the test module, which the top-level header includes anyway.
+    lang.add_custom_code("#include \".h\"\n");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "#include <stdint.h>\n#include <stdbool.h>\n#include \"common_types.h\"\n\nvoid foo(void);"
+    ));
+
+    let root = fetch(&outputs, "backend.h");
+    assert_eq!(root.matches("#include \".h\"").count(), 1);
+}
+
+#[test]
+fn c_type_overrides() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Event {
+            #[ffi(c_type = "time_t")]
+            timestamp: u64,
+            #[ffi(c_type = "Kind")]
+            kind: u32,
+        }
+
+        #[no_mangle]
+        #[ffi(c_type(since = "time_t", kind = "Kind"))]
+        pub extern "C" fn count_events(since: u64, kind: u32, limit: u32) -> u32 {}
+
+        #[repr(C)]
+        pub enum Kind {
+            Click,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct Event {\n\ttime_t timestamp;\n\tKind kind;\n} Event;"));
+    assert!(actual.contains("uint32_t count_events(time_t since, Kind kind, uint32_t limit);"));
+}
+
+#[test]
+fn cmake_snippet() {
+    let mut lang = LangC::new();
+    lang.set_lib_name("safe_app");
+    lang.emit_cmake("safe_app.cmake");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn app_new() {}
+    });
+
+    let expected = "# DO NOT EDIT: this file is generated by safe_bindgen for safe_app.\n\
+                    \n\
+                    set(SAFE_APP_LIB_NAME \"safe_app\")\n\
+                    set(SAFE_APP_HEADERS\n\
+                    \t\"${CMAKE_CURRENT_LIST_DIR}/.h\"\n\
+                    \t\"${CMAKE_CURRENT_LIST_DIR}/safe_app.h\"\n\
+                    )\n\
+                    \n\
+                    install(FILES \"${CMAKE_CURRENT_LIST_DIR}/.h\" DESTINATION include)\n\
+                    install(FILES \"${CMAKE_CURRENT_LIST_DIR}/safe_app.h\" DESTINATION include)\n";
+    assert_multiline_eq!(fetch(&outputs, "safe_app.cmake"), expected);
+}
+
+#[test]
+fn nested_structs_by_value() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            pub mod shapes {
+                use super::geometry::Point;
+
+                #[repr(C)]
+                pub struct Circle {
+                    center: Point,
+                    radius: f32,
+                }
+            }
+
+            pub mod geometry {
+                #[repr(C)]
+                pub struct Point {
+                    x: f32,
+                    y: f32,
+                }
+
+                #[repr(C)]
+                pub struct Segment {
+                    start: Point,
+                    end: Point,
+                }
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let shapes = fetch(&outputs, &format!("backend{}shapes.h", sep));
+    assert!(shapes.contains("typedef struct Circle {\n\tPoint center;\n\tfloat radius;\n} Circle;"));
+
+    let geometry = fetch(&outputs, &format!("backend{}geometry.h", sep));
+    assert!(geometry.contains("typedef struct Segment {\n\tPoint start;\n\tPoint end;\n} Segment;"));
+
+    // The header declaring `Point` has to be included before the one using it.
+    let root = fetch(&outputs, "backend.h");
+    let geometry_include = unwrap!(root.find(&format!("backend{}geometry.h", sep)));
+    let shapes_include = unwrap!(root.find(&format!("backend{}shapes.h", sep)));
+    assert!(geometry_include < shapes_include);
+    assert!(lang.warnings().is_empty());
+}
+
+#[test]
+fn unnamed_fn_ptr_params() {
+    let outputs = compile!(LangC::new(), {
+        pub type Compare = extern "C" fn(c_int, c_int) -> c_int;
+        pub type Fill = extern "C" fn(*mut u8, usize);
+        pub type Named = extern "C" fn(lhs: c_int, rhs: c_int) -> c_int;
+
+        #[no_mangle]
+        pub extern "C" fn sort(cb: extern "C" fn(*const c_void, *const c_void) -> bool) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef int (*Compare)(int, int);\n"));
+    assert!(actual.contains("typedef void (*Fill)(uint8_t*, uintptr_t);\n"));
+    assert!(actual.contains("typedef int (*Named)(int lhs, int rhs);\n"));
+    assert!(actual.contains("void sort(bool (*cb)(void const*, void const*));\n"));
+}
+
+#[test]
+fn extern_blocks() {
+    let outputs = compile!(LangC::new(), {
+        extern "C" {
+            /// Provided by the platform.
+            pub fn platform_log(level: c_int, message: *const c_char);
+
+            #[link_name = "platform_time_ms"]
+            pub fn platform_time() -> u64;
+
+            fn private_helper();
+
+            pub static PLATFORM_VERSION: u32;
+        }
+
+        extern "Rust" {
+            pub fn rust_abi();
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "/// Provided by the platform.\nvoid platform_log(int level, char const* message);\n"
+    ));
+    assert!(actual.contains("uint64_t platform_time_ms(void);\n"));
+    assert!(!actual.contains("private_helper"));
+    assert!(!actual.contains("PLATFORM_VERSION"));
+    assert!(!actual.contains("rust_abi"));
+}
+
+#[test]
+fn strict_mode() {
+    let mut lang = LangC::new();
+    lang.strict(true);
+
+    let errors = unwrap!(try_compile!(lang, {
+        #[repr(C)]
+        pub struct Foo {
+            bar: Bar,
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UndeclaredType));
+    assert_eq!(
+        errors[0].to_string(),
+        "error[E_UNDECLARED_TYPE]: field `bar` of `Foo` uses the undeclared type `Bar`"
+    );
+
+    let mut lang = LangC::new();
+    lang.strict(true);
+    let _ = compile!(lang, {
+        #[repr(C)]
+        pub struct Foo {
+            bar: i32,
+        }
+    });
+}
+
+#[test]
+fn tagged_unions() {
+    let mut lang = LangC::new();
+    lang.tagged_unions(true);
+
+    let outputs = compile!(lang, {
+        /// A shape.
+        #[repr(C)]
+        pub enum Shape {
+            /// Radius of the circle.
+            Circle(f32),
+            Rect {
+                width: f32,
+                height: f32,
+            },
+            Line(Point, Point),
+            Empty,
+        }
+
+        #[repr(C, u8)]
+        pub enum Value {
+            Int(i64),
+            Text(*const c_char),
+        }
+
+        #[repr(C)]
+        pub struct Point {
+            x: f32,
+            y: f32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let expected = "typedef enum Shape_Tag {\n\
+                    \t/// Radius of the circle.\n\
+                    \tShape_Circle,\n\
+                    \tShape_Rect,\n\
+                    \tShape_Line,\n\
+                    \tShape_Empty,\n\
+                    } Shape_Tag;\n\
+                    \n\
+                    /// A shape.\n\
+                    typedef struct Shape {\n\
+                    \tShape_Tag tag;\n\
+                    \tunion {\n\
+                    \t\tfloat Circle;\n\
+                    \t\tstruct {\n\
+                    \t\t\tfloat width;\n\
+                    \t\t\tfloat height;\n\
+                    \t\t} Rect;\n\
+                    \t\tstruct {\n\
+                    \t\t\tPoint _0;\n\
+                    \t\t\tPoint _1;\n\
+                    \t\t} Line;\n\
+                    \t} payload;\n\
+                    } Shape;\n";
+    assert!(actual.contains(expected));
+    assert!(actual.contains(
+        "typedef struct Value {\n\tuint8_t tag;\n\tunion {\n\t\tint64_t Int;\n\t\tchar const* Text;\n\t} payload;\n} Value;"
+    ));
+
+    // Data-carrying variants are still rejected unless tagged unions are enabled.
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[repr(C)]
+        pub enum Value {
+            Int(i64),
+        }
+    })
+    .err());
+    assert_eq!(errors[0].code, Some(ErrorCode::DataEnum));
+}
+
+#[test]
+fn blank_lines() {
+    let mut lang = LangC::new();
+    lang.add_custom_code("#define FOO 1\n\n\n\n\n#define BAR 2\n\n\n");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    for output in outputs.values() {
+        assert!(!output.contains("\n\n\n\n"));
+        assert!(output.ends_with("#endif\n"));
+    }
+    assert!(fetch(&outputs, "backend.h").contains("#define FOO 1\n\n\n#define BAR 2\n"));
+}
+
+#[test]
+fn byte_array_typedefs() {
+    let mut lang = LangC::new();
+    lang.set_lib_name("safe_app");
+    lang.byte_array_typedefs(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Keys {
+            public: [u8; 32],
+            secret: [u8; 32],
+            nonce: [u8; 24],
+            words: [u32; 4],
+            name: [u8; 16 * 4],
+        }
+
+        #[repr(C)]
+        pub struct Hash {
+            digest: [u8; 32],
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let expected = "typedef uint8_t safe_app_bytes32[32];\n\
+                    \n\
+                    typedef uint8_t safe_app_bytes24[24];\n\
+                    \n\
+                    typedef struct Keys {\n\
+                    \tsafe_app_bytes32 public;\n\
+                    \tsafe_app_bytes32 secret;\n\
+                    \tsafe_app_bytes24 nonce;\n\
+                    \tuint32_t words[4];\n\
+                    \tuint8_t name[16 * 4];\n\
+                    } Keys;\n\
+                    \n\
+                    typedef struct Hash {\n\
+                    \tsafe_app_bytes32 digest;\n\
+                    } Hash;\n";
+    assert!(actual.contains(expected));
+}
+
+#[test]
+fn annotate_source() {
+    let mut lang = LangC::new();
+    lang.annotate_source(true);
+
+    let outputs = compile!(lang, {
+        /// Adds one.
+        #[no_mangle]
+        pub extern "C" fn foo(x: i32) -> i32 {}
+
+        #[no_mangle]
+        pub extern "C" fn bar(name: *const c_char, cb: extern "C" fn(user_data: *mut c_void)) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "/// Adds one.\n/* rust: pub fn foo(x: i32) -> i32 */\nint32_t foo(int32_t x);\n"
+    ));
+    assert!(actual.contains(
+        "/* rust: pub fn bar(name: *const c_char, cb: extern \"C\" fn(user_data: *mut c_void)) */\n\
+         void bar(char const* name, void (*cb)(void* user_data));\n"
+    ));
+}
+
+#[test]
+fn half_and_quad_floats() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Sample {
+            half: f16,
+            values: [f16; 4],
+        }
+
+        #[no_mangle]
+        pub extern "C" fn precise(x: f128) -> f128 {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "#include <stdbool.h>\n\
+         #ifndef __FLT128_MANT_DIG__\n\
+         #error \"_Float128 is not supported by this compiler\"\n\
+         #endif\n\
+         #ifndef __FLT16_MANT_DIG__\n\
+         #error \"_Float16 is not supported by this compiler\"\n\
+         #endif\n"
+    ));
+    assert!(actual.contains("typedef struct Sample {\n\t_Float16 half;\n\t_Float16 values[4];\n}"));
+    assert!(actual.contains("_Float128 precise(_Float128 x);"));
+
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn foo(x: f32) {}
+    });
+    assert!(!fetch(&outputs, ".h").contains("#error"));
+}
+
+#[test]
+fn alias_instantiations() {
+    let mut lang = LangC::new();
+    unwrap!(lang.add_alias_instantiation("Handle<Foo>"));
+    unwrap!(lang.add_alias_instantiation("Handle<*const u8>"));
+    unwrap!(lang.add_alias_instantiation("Pair<i32, Foo>"));
+    unwrap!(lang.add_alias_instantiation("Unused<i32>"));
+    assert!(lang.add_alias_instantiation("Handle<").is_err());
+
+    let outputs = compile!(lang, {
+        /// Handle to a `T`.
+        pub type Handle<T> = *mut T;
+        pub type Pair<A, B> = extern "C" fn(first: A, second: *const B);
+        pub type Skipped<T> = *mut T;
+
+        #[repr(C)]
+        pub struct Foo {
+            x: i32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("/// Handle to a `T`.\ntypedef Foo* Handle_Foo;\n"));
+    assert!(actual.contains("typedef uint8_t const** Handle_const_u8;\n"));
+    assert!(actual.contains("typedef void (*Pair_i32_Foo)(int32_t first, Foo const* second);\n"));
+    assert!(!actual.contains("Skipped"));
+}
+
+#[test]
+fn skipped_items() {
+    let mut bindgen = unwrap!(Bindgen::new());
+    bindgen.source_code(
+        "ffi/src/lib.rs",
+        "pub type Handle<T> = *mut T;\n\
+         pub struct Internal {\n    x: i32,\n}\n\
+         pub enum Mode {\n    A,\n}\n\
+         #[repr(C)]\npub struct Exported {\n    x: i32,\n}\n",
+    );
+
+    let mut lang = LangC::new();
+    lang.trace_skipped_items(true);
+    let mut outputs = Outputs::default();
+    unwrap!(bindgen.compile(&mut lang, &mut outputs, true));
+
+    let warnings = lang.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, Some(ErrorCode::GenericAlias));
+    assert_eq!(
+        warnings[0].message,
+        "the generic type alias `Handle` is skipped, as it has no instantiation"
+    );
+
+    let notes: Vec<_> = lang.notes().iter().map(|note| &note.message).collect();
+    assert_eq!(
+        notes,
+        [
+            "the struct `Internal` is skipped, as it's not `#[repr(C)]`",
+            "the enum `Mode` is skipped, as it's not `#[repr(C)]`",
+        ]
+    );
+}
+
+#[test]
+fn header_namer() {
+    let mut lang = LangC::new();
+    lang.set_header_namer(Box::new(|module, lib_name| {
+        format!("include/{}/{}.h", lib_name, module.join("_"))
+    }));
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn foo() {}
+    });
+
+    let actual = fetch(&outputs, "include/backend/.h");
+    assert!(actual.contains("void foo(void);"));
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains("#include \"include/backend/.h\""));
+}
+
+#[test]
+fn tuple_returns() {
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn min_max(values: *const i32, len: usize) -> (i32, i32) {}
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::TupleReturn));
+
+    let mut lang = LangC::new();
+    lang.tuple_out_params(true);
+    let outputs = compile!(lang, {
+        /// Find the bounds of the values.
+        #[no_mangle]
+        pub extern "C" fn min_max(values: *const i32, len: usize) -> (i32, bool) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "/// Find the bounds of the values.\n\
+         /* The result is returned through `out0`, `out1`. */\n\
+         void min_max(int32_t const* values, uintptr_t len, int32_t* out0, bool* out1);"
+    ));
+}
+
+#[test]
+fn without_typedefs() {
+    let mut lang = LangC::new();
+    lang.typedefs(false);
+    lang.tagged_unions(true);
+    lang.emit_enum_to_string(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Mode {
+            Read,
+        }
+
+        #[repr(C)]
+        pub enum Never {}
+
+        #[repr(C)]
+        pub struct Node {
+            next: *mut Node,
+            mode: Mode,
+        }
+
+        #[repr(C)]
+        pub struct Handle(u64);
+
+        #[repr(C)]
+        pub enum Shape {
+            Point,
+            Circle(f32),
+        }
+
+        #[no_mangle]
+        pub extern "C" fn visit(node: *const Node, handle: *mut Handle, shape: Shape) -> Mode {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let expected = indoc!(
+        "enum Mode {
+         \tMode_Read,
+         };
+
+         static inline const char* Mode_to_string(enum Mode value) {
+         \tswitch (value) {
+         \tcase Mode_Read: return \"Read\";
+         \tdefault: return \"<unknown>\";
+         \t}
+         }
+
+         /* Opaque handle type (uninhabited in Rust) */
+         struct Never;
+
+         struct Node {
+         \tstruct Node* next;
+         \tenum Mode mode;
+         };
+
+         struct Handle;
+
+         enum Shape_Tag {
+         \tShape_Point,
+         \tShape_Circle,
+         };
+
+         struct Shape {
+         \tenum Shape_Tag tag;
+         \tunion {
+         \t\tfloat Circle;
+         \t} payload;
+         };
+
+         enum Mode visit(struct Node const* node, struct Handle* handle, struct Shape shape);"
+    );
+    assert!(actual.contains(expected), "{}", actual);
+}
+
+#[test]
+fn must_use() {
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        #[must_use]
+        pub extern "C" fn open(path: *const c_char) -> i32 {}
+
+        #[no_mangle]
+        #[must_use = "the handle leaks otherwise"]
+        pub extern "C" fn handle() -> *mut c_void {}
+
+        #[no_mangle]
+        #[must_use]
+        pub extern "C" fn close() {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("int32_t open(char const* path) __attribute__((warn_unused_result));"));
+    assert!(actual.contains("void* handle(void) __attribute__((warn_unused_result));"));
+    assert!(actual.contains("void close(void);"));
+
+    let mut lang = LangC::new();
+    lang.nodiscard(true);
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        #[must_use]
+        pub extern "C" fn open(path: *const c_char) -> i32 {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("[[nodiscard]] int32_t open(char const* path);"));
+}
+
+#[test]
+fn slice_pointers() {
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn hash(data: *const [u8]) -> u64 {}
+    })
+    .err());
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::SlicePointer));
+    assert_eq!(
+        errors[0].message,
+        "pointers to slices are fat pointers with no C equivalent, pass the pointer to the \
+         first element and the length instead (`*const u8, usize`)"
+    );
+}
+
+#[test]
+fn strip_module_prefix() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            pub mod api {
+                #[no_mangle]
+                pub extern "C" fn init() {}
+
+                pub mod net {
+                    #[no_mangle]
+                    pub extern "C" fn connect() {}
+                }
+            }
+
+            pub mod util {
+                #[no_mangle]
+                pub extern "C" fn version() {}
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    lang.strip_module_prefix(&["ffi", "api"]);
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let api = fetch(&outputs, &format!("backend{}backend.h", sep));
+    assert!(api.contains("void init(void);"));
+    let net = fetch(&outputs, &format!("backend{}net.h", sep));
+    assert!(net.contains("void connect(void);"));
+    let util = fetch(&outputs, &format!("backend{}util.h", sep));
+    assert!(util.contains("void version(void);"));
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains(&format!("#include \"backend{}net.h\"", sep)));
+}
+
+#[test]
+fn write_outputs_if_changed() {
+    let root = std::env::temp_dir().join(format!("safe_bindgen_outputs_{}", std::process::id()));
+    let bindgen = unwrap!(Bindgen::new());
+
+    let mut outputs = Outputs::default();
+    let _ = outputs.insert("a.h".to_string(), "int a;\n".to_string());
+    let _ = outputs.insert("include/b.h".to_string(), "int b;\n".to_string());
+    let written = unwrap!(bindgen.write_outputs_if_changed(&root, &outputs));
+    assert_eq!(written.len(), 2);
+
+    let _ = outputs.insert("a.h".to_string(), "long a;\n".to_string());
+    let written = unwrap!(bindgen.write_outputs_if_changed(&root, &outputs));
+    assert_eq!(written, [root.join("a.h")]);
+    assert_eq!(
+        unwrap!(std::fs::read_to_string(root.join("a.h"))),
+        "long a;\n"
+    );
+
+    unwrap!(std::fs::remove_dir_all(&root));
+}
+
+#[test]
+fn error_code_messages() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        #[ffi(error_codes)]
+        pub enum ErrorCode {
+            /// The item was not found.
+            NotFound = 1,
+            /// The "name" is invalid:
+            /// it must not be empty.
+            InvalidName,
+            Unknown,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    let expected = indoc!(
+        "static inline const char* backend_strerror(int code) {
+         \tswitch (code) {
+         \tcase ErrorCode_NotFound: return \"The item was not found.\";
+         \tcase ErrorCode_InvalidName: return \"The \\\"name\\\" is invalid: it must not be empty.\";
+         \tcase ErrorCode_Unknown: return \"Unknown\";
+         \tdefault: return \"<unknown>\";
+         \t}
+         }"
+    );
+    assert!(actual.contains(expected), "{}", actual);
+}
+
+#[test]
+fn volatile_fields() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Registers {
+            #[ffi(volatile)]
+            status: u32,
+            #[ffi(volatile)]
+            buffer: *mut u8,
+            #[ffi(volatile, bits = 4)]
+            flags: u32,
+            config: u32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Registers {\n\
+         \tuint32_t volatile status;\n\
+         \tuint8_t* volatile buffer;\n\
+         \tuint32_t volatile flags : 4;\n\
+         \tuint32_t volatile : 28;\n\
+         \tuint32_t config;\n\
+         } Registers;"
+    ));
+
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Registers {
+            #[ffi(volatile)]
+            data: [u32; 4],
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::InvalidVolatile));
+}
+
+#[test]
+fn pointer_width_assert() {
+    let mut lang = LangC::new();
+    lang.assert_pointer_width(64);
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn len(data: *const u8) -> usize {}
+    });
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains(
+        "#include \".h\"\n\n\
+         #ifdef __cplusplus\n\
+         static_assert(sizeof(uintptr_t) == 8, \"backend requires 64-bit pointers\");\n\
+         #else\n\
+         _Static_assert(sizeof(uintptr_t) == 8, \"backend requires 64-bit pointers\");\n\
+         #endif\n"
+    ));
+
+    // Headers without pointer-sized integers work on any platform.
+    let mut lang = LangC::new();
+    lang.assert_pointer_width(64);
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn len(data: *const u8) -> u32 {}
+    });
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(!root.contains("uintptr_t"));
+}
+
+#[test]
+fn type_mappings() {
+    let mut lang = LangC::new();
+    unwrap!(lang.load_mappings_from_str(
+        "[types]\nUuid = \"uuid_t\"\n\"chrono::NaiveDate\" = \"int32_t\"\n"
+    ));
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Entry {
+            id: Uuid,
+            created: chrono::NaiveDate,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct Entry {\n\tuuid_t id;\n\tint32_t created;\n} Entry;"));
+
+    let mut lang = LangC::new();
+    let error = unwrap!(lang.load_mappings_from_str("[types]\nUuid = 16\n").err());
+    assert_eq!(error.code, Some(ErrorCode::InvalidMapping));
+    assert_eq!(error.message, "the C type of `Uuid` must be a string");
+
+    let error = unwrap!(lang.load_mappings_from_str("[typs]\n").err());
+    assert_eq!(error.message, "unknown mapping table `typs`");
+
+    let error = unwrap!(lang.load_mappings_from_str("[types\n").err());
+    assert!(error.message.starts_with("could not parse the mappings: "));
+}
+
+#[test]
+fn statics() {
+    let mut lang = LangC::new();
+    lang.emit_def_file("backend.def");
+
+    let outputs = compile!(lang, {
+        /// Number of open handles.
+        #[no_mangle]
+        pub static mut OPEN_HANDLES: u32 = 0;
+
+        #[no_mangle]
+        pub static VERSION: *const c_char = 0;
+
+        #[no_mangle]
+        pub static LOG: extern "C" fn(level: i32) = log;
+
+        pub static NOT_EXPORTED: u32 = 0;
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "/// Number of open handles.\n\
+         extern uint32_t OPEN_HANDLES;\n\n\
+         extern char const* const VERSION;\n\n\
+         extern void (*const LOG)(int32_t level);\n"
+    ));
+    assert!(!actual.contains("NOT_EXPORTED"));
+
+    assert_eq!(
+        fetch(&outputs, "backend.def"),
+        "; DO NOT EDIT: this file is generated by safe_bindgen for backend.\n\n\
+         LIBRARY backend\nEXPORTS\n\tOPEN_HANDLES DATA\n\tVERSION DATA\n\tLOG DATA\n"
+    );
+}
+
+#[test]
+fn c89_mode() {
+    let mut lang = LangC::new();
+    lang.c89_mode(true);
+    lang.emit_enum_to_string(true);
+
+    let outputs = compile!(lang, {
+        /// Kind of a shape.
+        #[repr(C)]
+        pub enum Kind {
+            /// A circle, see http://example.com.
+            Circle,
+            Square,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn is_round(kind: Kind) -> bool {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("#define bindgen_c89_types\ntypedef signed char int8_t;\n"));
+    assert!(actual.contains("typedef unsigned char bool;\n#endif\n"));
+    assert!(!actual.contains("stdint.h"));
+    assert!(!actual.contains("///"));
+    assert!(actual.contains(
+        "/* Kind of a shape. */\n\
+         typedef enum Kind {\n\
+         \t/* A circle, see http://example.com. */\n\
+         \tKind_Circle,\n\
+         \tKind_Square\n\
+         } Kind;\n\n\
+         static const char* Kind_to_string(Kind value) {"
+    ));
+    assert!(actual.contains("bool is_round(Kind kind);"));
+
+    // The top-level header and the optional C99+ constructs are converted too.
+    let mut lang = LangC::new();
+    lang.c89_mode(true);
+    lang.nodiscard(true);
+    lang.emit_enum_value_checks(true);
+    lang.assert_pointer_width(64);
+    lang.add_custom_code("// Custom code.\n");
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Kind {
+            Circle,
+            Square,
+        }
+
+        #[must_use]
+        #[no_mangle]
+        pub extern "C" fn shape_count(kind: Kind) -> usize {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("#ifdef _WIN64\ntypedef long long intptr_t;\n"));
+    assert!(actual.contains("/* Values of Kind:\n * Kind_Circle = 0\n * Kind_Square = 1\n */"));
+    assert!(
+        actual.contains("uintptr_t shape_count(Kind kind) __attribute__((warn_unused_result));")
+    );
+    assert!(!actual.contains("static_assert"));
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains("/* Custom code. */\n"));
+    assert!(!root.contains("static_assert"));
+}
+
+#[test]
+fn pkg_config() {
+    let mut lang = LangC::new();
+    lang.set_lib_name("safe_app");
+    lang.emit_pkg_config("SAFE App", "0.9.1", "");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn app_new() {}
+    });
+
+    let expected = "# DO NOT EDIT: this file is generated by safe_bindgen for safe_app.\n\
+                    \n\
+                    prefix=/usr/local\n\
+                    includedir=${prefix}/include\n\
+                    libdir=${prefix}/lib\n\
+                    \n\
+                    Name: SAFE App\n\
+                    Description: C bindings of safe_app\n\
+                    Version: 0.9.1\n\
+                    Cflags: -I${includedir}\n\
+                    Libs: -L${libdir} -lsafe_app\n";
+    assert_multiline_eq!(fetch(&outputs, "safe_app.pc"), expected);
+
+    let mut lang = LangC::new();
+    lang.emit_pkg_config("Backend", "1.0.0", "-lbackend -lpthread");
+    let outputs = compile!(lang, {});
+    assert!(fetch(&outputs, "backend.pc").ends_with("Libs: -L${libdir} -lbackend -lpthread\n"));
+}
+
+#[test]
+fn groups() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            pub mod alloc {
+                #[ffi(group = "memory")]
+                #[no_mangle]
+                pub extern "C" fn buffer_new(len: usize) -> *mut u8 {}
+
+                #[no_mangle]
+                pub extern "C" fn arena_reset() {}
+            }
+
+            pub mod io {
+                #[ffi(group = "memory")]
+                #[no_mangle]
+                pub extern "C" fn buffer_free(buffer: *mut u8) {}
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let memory = fetch(&outputs, &format!("backend{}memory.h", sep));
+    assert!(memory.contains("uint8_t* buffer_new(uintptr_t len);"));
+    assert!(memory.contains("void buffer_free(uint8_t* buffer);"));
+
+    let alloc = fetch(&outputs, &format!("backend{}alloc.h", sep));
+    assert!(alloc.contains("void arena_reset(void);"));
+    assert!(!alloc.contains("buffer_new"));
+    assert!(!outputs.contains_key(&format!("backend{}io.h", sep)));
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains(&format!("#include \"backend{}memory.h\"", sep)));
+
+    // Groups follow the layout of the other headers.
+    let mut lang = LangC::new();
+    lang.flat_layout(true);
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    let memory = fetch(&outputs, "backend_memory.h");
+    assert!(memory.contains("void buffer_free(uint8_t* buffer);"));
+}
+
+#[test]
+fn verify_with_compiler() {
+    // The check needs a C compiler.
+    if std::process::Command::new("cc")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return;
+    }
+
+    let mut lang = LangC::new();
+    lang.verify_with_compiler("cc");
+    let _ = compile!(lang, {
+        #[repr(C)]
+        pub struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn distance(a: *const Point, b: *const Point) -> f64 {}
+    });
+
+    let mut lang = LangC::new();
+    lang.verify_with_compiler("cc");
+    let errors = unwrap!(try_compile!(lang, {
+        #[ffi(c_type(handle = "Unknown"))]
+        #[no_mangle]
+        pub extern "C" fn close(handle: *mut c_void) {}
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::CompileError));
+    assert!(
+        errors[0].message.contains("does not compile"),
+        "{}",
+        errors[0].message
+    );
+}
+
+#[test]
+fn ip_addresses() {
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Peer {
+            v6: std::net::Ipv6Addr,
+            port: u16,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn connect(addr: core::net::Ipv4Addr, port: u16) {}
+    })
+    .err());
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|error| error.code == Some(ErrorCode::IpAddress)));
+    assert!(errors[0].message.contains("`[u8; 16]`"));
+    assert!(errors[1].message.contains("`[u8; 4]`"));
+
+    // The octets are passed instead, and other types with the same name are left alone.
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Peer {
+            v4: [u8; 4],
+            v6: Ipv6Addr,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef struct Peer {\n\tuint8_t v4[4];\n\tIpv6Addr v6;\n} Peer;"));
+}
+
+#[test]
+fn identifier_transform() {
+    let mut lang = LangC::new();
+    lang.set_identifier_transform(Box::new(|name| format!("my_{}", name)));
+    lang.emit_enum_to_string(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            Failed = 4,
+        }
+
+        #[repr(C)]
+        pub struct Point {
+            x: f64,
+            status: Status,
+        }
+
+        pub type Points = *mut Point;
+
+        #[no_mangle]
+        pub extern "C" fn draw(point: *const Point, points: Points) -> Status {}
+
+        #[export_name = "erase_shape"]
+        #[no_mangle]
+        pub extern "C" fn erase() {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef enum my_Status {\n\tmy_Status_Ok,\n\tmy_Status_Failed = 4,\n} my_Status;"
+    ));
+    assert!(actual.contains("const char* my_Status_to_string(my_Status value)"));
+    assert!(actual.contains("\tcase my_Status_Failed: return \"Failed\";\n"));
+    assert!(actual.contains(
+        "typedef struct my_Point {\n\tdouble my_x;\n\tmy_Status my_status;\n} my_Point;"
+    ));
+    assert!(actual.contains("typedef my_Point* my_Points;"));
+    assert!(actual.contains("my_Status my_draw(my_Point const* point, my_Points points);"));
+    assert!(actual.contains("void erase_shape(void);"));
+}
+
+#[test]
+fn non_exhaustive_structs() {
+    let outputs = compile!(LangC::new(), {
+        /// Options of a connection.
+        #[non_exhaustive]
+        #[ffi(reserved = 16)]
+        #[repr(C)]
+        pub struct Options {
+            timeout: u32,
+            retries: u8,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "/// Options of a connection.\n\
+         /* Non-exhaustive: fields may be added in later versions, so don't allocate it on the \
+         stack or rely on its `sizeof`. */\n\
+         typedef struct Options {\n\
+         \tuint32_t timeout;\n\
+         \tuint8_t retries;\n\
+         \tuint8_t _reserved[16];\n\
+         } Options;"
+    ));
+}
+
+#[test]
+fn arrays_of_fn_ptrs() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Dispatcher {
+            handlers: [extern "C" fn(); 8],
+            filters: [extern "C" fn(event: u32, user_data: *mut c_void) -> bool; 4],
+        }
+
+        #[no_mangle]
+        pub extern "C" fn dispatch(handlers: [extern "C" fn(code: i32); 2]) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Dispatcher {\n\
+         \tvoid (*handlers[8])(void);\n\
+         \tbool (*filters[4])(uint32_t event, void* user_data);\n\
+         } Dispatcher;"
+    ));
+    assert!(actual.contains("void dispatch(void (* const* handlers)(int32_t code));"));
+}
+
+#[test]
+fn std_header_names() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            pub mod string {
+                #[no_mangle]
+                pub extern "C" fn string_len(s: *const c_char) -> usize {}
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+    let sep = ::std::path::MAIN_SEPARATOR;
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    assert!(outputs.contains_key(&format!("backend{}string.h", sep)));
+    assert_eq!(lang.warnings().len(), 1);
+    assert_eq!(lang.warnings()[0].code, Some(ErrorCode::StdHeaderName));
+
+    let mut lang = LangC::new();
+    lang.prefix_std_headers(true);
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    let actual = fetch(&outputs, &format!("backend{}backend_string.h", sep));
+    assert!(actual.contains("uintptr_t string_len("));
+    assert!(lang.warnings().is_empty());
+}
+
+#[test]
+fn require_platform() {
+    let mut lang = LangC::new();
+    lang.set_version(1, 2, 3);
+    lang.require_platform(
+        "defined(__LP64__)",
+        "backend requires a \"64-bit\" platform",
+    );
+    lang.require_platform("!defined(_WIN32)", "backend doesn't support Windows");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn init() {}
+    });
+
+    let root = fetch(&outputs, "backend.h");
+    assert!(root.contains(
+        "#define bindgen_backend_root\n\n\
+         #if !(defined(__LP64__))\n\
+         #error \"backend requires a \\\"64-bit\\\" platform\"\n\
+         #endif\n\n\
+         #if !(!defined(_WIN32))\n\
+         #error \"backend doesn't support Windows\"\n\
+         #endif\n\n\
+         #define BACKEND_VERSION_MAJOR 1\n"
+    ));
+}
+
+#[test]
+fn system_types() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Stat {
+            size: libc::off_t,
+            modified: libc::time_t,
+            owner: libc::uid_t,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn spawn(path: *const c_char, pid: *mut libc::pid_t) -> libc::ssize_t {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("#include <stdbool.h>\n#include <sys/types.h>\n#include <time.h>\n"));
+    assert!(actual.contains(
+        "typedef struct Stat {\n\toff_t size;\n\ttime_t modified;\n\tuid_t owner;\n} Stat;"
+    ));
+    assert!(actual.contains("ssize_t spawn(char const* path, pid_t* pid);"));
+}
+
+#[test]
+fn banner() {
+    let mut lang = LangC::new();
+    lang.set_banner("Copyright 2018 MaidSafe.net limited.\n\nGenerated from the Rust code.");
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn init() {}
+    });
+
+    assert!(fetch(&outputs, "backend.h").starts_with(
+        "/*\n * Copyright 2018 MaidSafe.net limited.\n *\n * Generated from the Rust code.\n */\n\n\
+         #ifndef bindgen_backend_root\n"
+    ));
+
+    let mut lang = LangC::new();
+    lang.set_banner("");
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn init() {}
+    });
+
+    assert!(fetch(&outputs, "backend.h").starts_with("\n#ifndef bindgen_backend_root\n"));
+}
+
+#[test]
+fn maybe_uninit() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        #[repr(C)]
+        pub struct Slot {
+            /// Value of the slot.
+            value: MaybeUninit<Point>,
+            scratch: std::mem::MaybeUninit<[u8; 16]>,
+            filled: bool,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn slot_read(slot: *const Slot, out: *mut core::mem::MaybeUninit<Point>) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Slot {\n\
+         \t/// Value of the slot.\n\
+         \t/* May be uninitialized (`MaybeUninit`). */\n\
+         \tPoint value;\n\
+         \t/* May be uninitialized (`MaybeUninit`). */\n\
+         \tuint8_t scratch[16];\n\
+         \tbool filled;\n\
+         } Slot;"
+    ));
+    assert!(actual.contains("void slot_read(Slot const* slot, Point* out);"));
+}
+
+#[test]
+fn max_line_width() {
+    let mut lang = LangC::new();
+    lang.set_max_line_width(60);
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn short(a: i32, b: i32) -> i32 {}
+
+        #[no_mangle]
+        pub extern "C" fn connect(
+            host: *const c_char,
+            port: u16,
+            timeout_ms: u32,
+            retries: u8,
+        ) -> bool {
+        }
+
+        #[no_mangle]
+        pub extern "C" fn on_connect(
+            user_data: *mut c_void,
+            callback: extern "C" fn(user_data: *mut c_void, result: i32),
+        ) -> extern "C" fn(result: i32) {
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("int32_t short(int32_t a, int32_t b);\n"));
+    assert!(actual.contains(
+        "bool connect(char const* host,\n             \
+         uint16_t port,\n             \
+         uint32_t timeout_ms,\n             \
+         uint8_t retries);\n"
+    ));
+    assert!(actual.contains(
+        "void (*on_connect(void* user_data,\n                  \
+         void (*callback)(void* user_data, int32_t result)))(int32_t result);\n"
+    ));
+}
+
+#[test]
+fn fn_hints() {
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        #[ffi(pure)]
+        pub extern "C" fn length(s: *const c_char) -> usize {}
+
+        #[no_mangle]
+        #[ffi(const, hot)]
+        pub extern "C" fn square(x: i32) -> i32 {}
+
+        #[no_mangle]
+        #[must_use]
+        #[ffi(cold)]
+        pub extern "C" fn report_error(code: i32) -> i32 {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("#ifndef bindgen_fn_hints\n#define bindgen_fn_hints\n"));
+    assert!(actual.contains("#else\n#define BINDGEN_PURE\n"));
+    assert!(actual.contains("uintptr_t length(char const* s) BINDGEN_PURE;\n"));
+    assert!(actual.contains("int32_t square(int32_t x) BINDGEN_CONST BINDGEN_HOT;\n"));
+    assert!(actual.contains(
+        "int32_t report_error(int32_t code) __attribute__((warn_unused_result)) BINDGEN_COLD;\n"
+    ));
+
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn square(x: i32) -> i32 {}
+    });
+    assert!(!fetch(&outputs, ".h").contains("bindgen_fn_hints"));
+}
+
+#[test]
+fn nested_arrays() {
+    let outputs = compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Transform {
+            matrix: [[f32; 4]; 4],
+            grid: [[[u8; 3]; 2]; 8],
+            names: [[c_char; 16]; 2],
+        }
+
+        #[no_mangle]
+        pub extern "C" fn transform_apply(matrix: [[f32; 3]; 2], out: *mut Transform) {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Transform {\n\
+         \tfloat matrix[4][4];\n\
+         \tuint8_t grid[8][2][3];\n\
+         \tchar names[2][16];\n\
+         } Transform;"
+    ));
+    assert!(actual.contains("void transform_apply(float (*matrix)[3], Transform* out);"));
+}
+
+#[test]
+fn enum_value_checks() {
+    let mut lang = LangC::new();
+    lang.emit_enum_value_checks(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Axis {
+            X,
+            Y,
+            Z,
+        }
+
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            NotFound = 4,
+            Timeout,
+            Custom = FIRST_CUSTOM,
+            Other,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "} Axis;\n\n\
+         #ifdef __cplusplus\n\
+         static_assert(Axis_Z == 2, \"Axis must be contiguous from 0\");\n\
+         #else\n\
+         _Static_assert(Axis_Z == 2, \"Axis must be contiguous from 0\");\n\
+         #endif\n"
+    ));
+    assert!(actual.contains(
+        "} Status;\n\n\
+         /* Values of Status:\n \
+         * Status_Ok = 0\n \
+         * Status_NotFound = 4\n \
+         * Status_Timeout = 5\n \
+         * Status_Custom = FIRST_CUSTOM\n \
+         * Status_Other = (FIRST_CUSTOM) + 1\n \
+         */\n"
+    ));
+}
+
+#[test]
+fn empty_param_style() {
+    let outputs = compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn init() -> i32 {}
+    });
+    assert!(fetch(&outputs, ".h").contains("int32_t init(void);\n"));
+
+    let mut lang = LangC::new();
+    lang.empty_param_style(EmptyParams::Empty);
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn init() -> i32 {}
+
+        #[no_mangle]
+        pub extern "C" fn add(a: i32, b: i32) -> i32 {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("int32_t init();\n"));
+    assert!(actual.contains("int32_t add(int32_t a, int32_t b);\n"));
+}
+
+#[test]
+fn const_array_lengths() {
+    let outputs = compile!(LangC::new(), {
+        pub const KEY_LEN: usize = 32;
+        pub const NAME_LEN: u32 = KEY_LEN;
+
+        #[repr(C)]
+        pub struct Key {
+            bytes: [u8; KEY_LEN],
+            name: [c_char; NAME_LEN],
+            handlers: [extern "C" fn(); KEY_LEN],
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "typedef struct Key {\n\
+         \tuint8_t bytes[32];\n\
+         \tchar name[32];\n\
+         \tvoid (*handlers[32])(void);\n\
+         } Key;"
+    ));
+
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Key {
+            bytes: [u8; KEY_LEN],
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnknownArrayLen));
+    assert!(errors[0].message.contains("`KEY_LEN`"));
+}
+
+#[test]
+fn root_header_includes_every_module() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            pub mod shapes {
+                use super::geometry::Point;
+
+                #[repr(C)]
+                pub struct Circle {
+                    center: Point,
+                    radius: f32,
+                }
+            }
+
+            pub mod geometry {
+                #[repr(C)]
+                pub struct Point {
+                    x: f32,
+                    y: f32,
+                }
+            }
+
+            pub mod logging {
+                #[no_mangle]
+                pub extern "C" fn log_flush() {}
+            }
+
+            pub mod audio {
+                #[no_mangle]
+                pub extern "C" fn audio_init() {}
+            }
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &["ffi".to_string()],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    // Isolated modules are included too, in alphabetical order around the sorted dependencies.
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let root = fetch(&outputs, "backend.h");
+    let includes: Vec<_> = root
+        .lines()
+        .filter(|line| line.starts_with("#include"))
+        .collect();
+    assert_eq!(
+        includes,
+        vec![
+            format!("#include \"backend{}audio.h\"", sep),
+            format!("#include \"backend{}geometry.h\"", sep),
+            format!("#include \"backend{}logging.h\"", sep),
+            format!("#include \"backend{}shapes.h\"", sep),
+        ]
+    );
+}
+
+#[test]
+fn anonymous_enum_tags() {
+    let mut lang = LangC::new();
+    lang.enum_tag_style(EnumTagStyle::Anonymous);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            Failed,
+        }
+
+        #[repr(C)]
+        pub struct Point {
+            x: f32,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("typedef enum {\n\tStatus_Ok,\n\tStatus_Failed,\n} Status;"));
+    assert!(actual.contains("typedef struct Point {"));
+
+    // Without typedefs, enums need their tag.
+    let mut lang = LangC::new();
+    lang.enum_tag_style(EnumTagStyle::Anonymous);
+    lang.typedefs(false);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+        }
+    });
+    assert!(fetch(&outputs, ".h").contains("enum Status {\n\tStatus_Ok,\n};"));
+}
+
+#[test]
+fn rust_wrappers() {
+    let mut lang = LangC::new();
+    lang.rust_wrappers("wrappers.rs");
+
+    let outputs = compile!(lang, {
+        /// Greet someone.
+        #[ffi(wrap)]
+        pub fn greet(name: &str, times: u32) -> bool {}
+
+        #[ffi(wrap = "backend_sum")]
+        pub fn sum(values: &[i32], out: &mut i64) {}
+
+        #[ffi(wrap)]
+        pub fn fill(buffer: &mut [u8]) -> usize {}
+
+        pub fn not_wrapped(name: &str) {}
+    });
+
+    let header = fetch(&outputs, ".h");
+    assert!(header.contains(
+        "/// Greet someone.\n\
+         bool greet_ffi(char const* name, uintptr_t name_len, uint32_t times);"
+    ));
+    assert!(header
+        .contains("void backend_sum(int32_t const* values, uintptr_t values_len, int64_t* out);"));
+    assert!(header.contains("uintptr_t fill_ffi(uint8_t* buffer, uintptr_t buffer_len);"));
+    assert!(!header.contains("not_wrapped"));
+
+    let wrappers = fetch(&outputs, "wrappers.rs");
+    assert!(wrappers.starts_with("// DO NOT EDIT: this file is generated by safe_bindgen"));
+    assert!(wrappers.contains(
+        "/// Greet someone.\n\
+         #[no_mangle]\n\
+         pub unsafe extern \"C\" fn greet_ffi(name: *const std::os::raw::c_char, name_len: usize, \
+         times: u32) -> bool {\n    \
+         let name = String::from_utf8_lossy(std::slice::from_raw_parts(name as *const u8, \
+         name_len));\n    \
+         greet(&name, times)\n\
+         }\n"
+    ));
+    assert!(wrappers.contains(
+        "pub unsafe extern \"C\" fn backend_sum(values: *const i32, values_len: usize, \
+         out: *mut i64) {\n    \
+         let values = std::slice::from_raw_parts(values, values_len);\n    \
+         sum(values, &mut *out)\n\
+         }\n"
+    ));
+    assert!(wrappers.contains("let buffer = std::slice::from_raw_parts_mut(buffer, buffer_len);"));
+}
+
+#[test]
+fn rust_wrappers_keep_attributes() {
+    let mut lang = LangC::new();
+    lang.rust_wrappers("wrappers.rs");
+    lang.allowlist_only(true);
+
+    let outputs = compile!(lang, {
+        #[ffi(wrap)]
+        #[ffi(export)]
+        #[ffi(internal)]
+        #[must_use]
+        pub fn check(mut name: &str, _: u32) -> bool {}
+
+        #[ffi(wrap)]
+        pub fn skipped(name: &str) {}
+    });
+
+    let public = fetch(&outputs, ".h");
+    assert!(!public.contains("check_ffi"));
+    assert!(!public.contains("skipped"));
+
+    let internal = fetch(&outputs, "_internal.h");
+    assert!(internal.contains(
+        "bool check_ffi(char const* name, uintptr_t name_len, uint32_t arg1) \
+         __attribute__((warn_unused_result));"
+    ));
+
+    let wrappers = fetch(&outputs, "wrappers.rs");
+    assert!(wrappers.contains("fn check_ffi(name: *const std::os::raw::c_char, name_len: usize"));
+    assert!(wrappers.contains("check(&name, arg1)"));
+    assert!(!wrappers.contains("skipped"));
+}
+
+#[test]
+fn underscore_bool() {
+    let mut lang = LangC::new();
+    lang.use_underscore_bool(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Flags {
+            enabled: bool,
+            dirty: std::sync::atomic::AtomicBool,
+        }
+
+        #[no_mangle]
+        pub extern "C" fn is_ready(flags: *const Flags) -> bool {}
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains("#include <stdint.h>\n"));
+    assert!(!actual.contains("stdbool.h"));
+    assert!(actual.contains("\t_Bool enabled;\n"));
+    assert!(actual.contains("\t/* Atomic: use atomic or volatile access. */\n\t_Bool dirty;\n"));
+    assert!(actual.contains("_Bool is_ready(Flags const* flags);"));
+}
+
+#[test]
+fn empty_module_path() {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let krate = unwrap!(parse::parse_crate_from_source_str(
+        "lib.rs".to_string(),
+        stringify!(
+            #[no_mangle]
+            pub extern "C" fn init() {}
+        )
+        .to_string(),
+        &sess
+    ));
+
+    let mut lang = LangC::new();
+    let mut outputs = Outputs::default();
+    unwrap!(crate::parse::walk_crate(
+        &krate,
+        &[],
+        &mut lang,
+        &mut outputs
+    ));
+    unwrap!(lang.finalise_output(&mut outputs));
+
+    let sep = ::std::path::MAIN_SEPARATOR;
+    let header = fetch(&outputs, &format!("backend{}backend.h", sep));
+    assert!(header.contains("void init(void);"));
+    assert!(fetch(&outputs, "backend.h").contains(&format!("#include \"backend{}backend.h\"", sep)));
+}
+
+#[test]
+fn discriminant_comments() {
+    let mut lang = LangC::new();
+    lang.emit_discriminant_comments(true);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub enum Flag {
+            None = 0,
+            Negative = -1,
+            Read = 1 << 2,
+            Write = Flag::Read as isize * 2,
+            Next,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "\tFlag_None = 0,\n\
+         \tFlag_Negative = -1,\n\
+         \tFlag_Read = 4, /* rust: = 1 << 2 */\n\
+         \tFlag_Write = 8, /* rust: = (Flag::Read as isize) * 2 */\n\
+         \tFlag_Next,\n\
+         } Flag;\n"
+    ));
+
+    let mut lang = LangC::new();
+    lang.emit_discriminant_comments(true);
+    let errors = unwrap!(try_compile!(lang, {
+        #[repr(C)]
+        pub enum Mode {
+            Read = MODE_READ,
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnknownDiscriminant));
+}
+
+#[test]
+fn offset_comments() {
+    let mut lang = LangC::new();
+    lang.emit_offset_comments(true);
+    lang.assert_pointer_width(64);
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Packet {
+            pub kind: u8,
+            pub len: u32,
+            pub data: *const u8,
+            pub checksum: [u16; 3],
+            #[ffi(size = 12, align = 4)]
+            pub header: Header,
+            pub opaque: Opaque,
+            pub flags: u8,
+            #[ffi(offset = 40)]
+            pub timestamp: f64,
+        }
+    });
+
+    let actual = fetch(&outputs, ".h");
+    assert!(actual.contains(
+        "\tuint8_t kind; /* offset 0 */\n\
+         \tuint32_t len; /* offset 4 */\n\
+         \tuint8_t const* data; /* offset 8 */\n\
+         \tuint16_t checksum[3]; /* offset 16 */\n\
+         \tHeader header; /* offset 24 */\n\
+         \tOpaque opaque; /* offset ? */\n\
+         \tuint8_t flags; /* offset ? */\n\
+         \tdouble timestamp; /* offset 40 */\n"
+    ));
+}
+
+#[test]
+fn trait_objects() {
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn on_event(callback: Box<Fn(u32)>) {}
+    })
+    .err());
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::TraitObject));
+    assert_eq!(
+        errors[0].message,
+        "trait objects and closures have no C equivalent, pass an `extern \"C\" fn` pointer \
+         instead (with a `*mut c_void` argument for its state if needed)"
+    );
+
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[no_mangle]
+        pub extern "C" fn on_close(callback: impl Fn()) {}
+    })
+    .err());
+    assert_eq!(errors[0].code, Some(ErrorCode::TraitObject));
+
+    let errors = unwrap!(try_compile!(LangC::new(), {
+        #[repr(C)]
+        pub struct Handler {
+            pub callback: *mut FnMut(),
+        }
+    })
+    .err());
+    assert_eq!(errors[0].code, Some(ErrorCode::TraitObject));
+}
@@ -2,6 +2,27 @@
 
 use std::fmt::{self, Display, Formatter};
 
+/// Types defined by the system headers (mostly `libc` types whose size varies by platform),
+/// with the header defining them.
+pub const SYSTEM_TYPES: &[(&str, &str)] = &[
+    ("wchar_t", "wchar.h"),
+    ("size_t", "stddef.h"),
+    ("ptrdiff_t", "stddef.h"),
+    ("FILE", "stdio.h"),
+    ("time_t", "time.h"),
+    ("clock_t", "time.h"),
+    ("off_t", "sys/types.h"),
+    ("ssize_t", "sys/types.h"),
+    ("pid_t", "sys/types.h"),
+    ("uid_t", "sys/types.h"),
+    ("gid_t", "sys/types.h"),
+    ("mode_t", "sys/types.h"),
+    ("dev_t", "sys/types.h"),
+    ("ino_t", "sys/types.h"),
+    ("socklen_t", "sys/socket.h"),
+    ("sa_family_t", "sys/socket.h"),
+];
+
 #[derive(Debug, PartialEq)]
 pub struct CTypeNamed(pub String, pub CType);
 
@@ -12,6 +33,15 @@ impl Display for CTypeNamed {
             // special case
             CType::FnDecl { .. } => write!(f, "{}", self.1),
 
+            // Unnamed, e.g. the parameters of `extern "C" fn(i32, i32)`
+            _ if self.0.is_empty() => write!(f, "{}", self.1),
+
+            // Arrays have their lengths after the name
+            CType::Array(..) => {
+                let (elem, lens) = self.1.array_lens();
+                write!(f, "{} {}{}", elem, self.0, lens)
+            }
+
             // For all other cases we add a type prefix
             _ => write!(f, "{} {}", self.1, self.0),
         }
@@ -37,8 +67,20 @@ impl Display for CPtrType {
 pub enum CType {
     Void,
     Mapping(String),
+    /// C type provided by the user, used verbatim.
+    Custom(String),
     Native(&'static str),
+    /// Struct or enum referred to by its tag, e.g. `struct Name`.
+    Tagged(&'static str, String),
+    /// Rust atomic represented by its underlying integer type.
+    Atomic(&'static str),
+    /// Pointer to the inner type, with the qualifier applying to the pointee.
+    ///
+    /// The qualifier is written after the pointee, so that nested pointers read inside-out like
+    /// in C: `*const *mut T` is `T* const*` and `*mut *const T` is `T const**`.
     Ptr(Box<CType>, CPtrType),
+    /// Fixed-length array with its length expression.
+    Array(Box<CType>, String),
     FnDecl {
         inner: String,
         args: Vec<CTypeNamed>,
@@ -47,6 +89,23 @@ pub enum CType {
 }
 
 impl CType {
+    /// Splits (nested) arrays into their element type and their lengths, outermost first, e.g.
+    /// `float` and `[2][4]` for `[[f32; 4]; 2]`.
+    fn array_lens(&self) -> (&CType, String) {
+        let mut cty = self;
+        let mut lens = String::new();
+        while let CType::Array(ref elem, ref len) = *cty {
+            lens.push_str(&format!("[{}]", len));
+            cty = elem;
+        }
+        (cty, lens)
+    }
+
+    /// Whether this is an atomic, or an array of atomics.
+    pub fn is_atomic(&self) -> bool {
+        matches!(*self.array_lens().0, CType::Atomic(_))
+    }
+
     /// Returns a list of user-defined types this `CType` depends on
     pub fn dependencies(&self) -> Vec<String> {
         match *self {
@@ -63,20 +122,92 @@ impl CType {
                         .flat_map(|&CTypeNamed(_, ref cty)| cty.dependencies()),
                 )
                 .collect(),
-            CType::Ptr(ref cty, _) => cty.dependencies(),
-            CType::Mapping(ref mapping) => vec![mapping.clone()],
+            CType::Ptr(ref cty, _) | CType::Array(ref cty, _) => cty.dependencies(),
+            CType::Mapping(ref name) | CType::Tagged(_, ref name) => vec![name.clone()],
+            _ => Default::default(),
+        }
+    }
+
+    /// Returns the standard headers needed to use this `CType`, besides `stdint.h` and
+    /// `stdbool.h`.
+    pub fn std_includes(&self) -> Vec<&'static str> {
+        match *self {
+            CType::FnDecl {
+                ref args,
+                ref return_type,
+                ..
+            } => return_type
+                .std_includes()
+                .into_iter()
+                .chain(
+                    args.iter()
+                        .flat_map(|CTypeNamed(_, cty)| cty.std_includes()),
+                )
+                .collect(),
+            CType::Ptr(ref cty, _) | CType::Array(ref cty, _) => cty.std_includes(),
+            CType::Native(ty) => SYSTEM_TYPES
+                .iter()
+                .filter(|&&(name, _)| name == ty)
+                .map(|&(_, header)| header)
+                .collect(),
+            _ => Default::default(),
+        }
+    }
+
+    /// Returns the optional floating-point types (`_Float16` and `_Float128`) this `CType` uses,
+    /// which not all compilers support.
+    pub fn float_extensions(&self) -> Vec<&'static str> {
+        match *self {
+            CType::FnDecl {
+                ref args,
+                ref return_type,
+                ..
+            } => return_type
+                .float_extensions()
+                .into_iter()
+                .chain(
+                    args.iter()
+                        .flat_map(|CTypeNamed(_, cty)| cty.float_extensions()),
+                )
+                .collect(),
+            CType::Ptr(ref cty, _) | CType::Array(ref cty, _) => cty.float_extensions(),
+            CType::Native(ty @ "_Float16") | CType::Native(ty @ "_Float128") => vec![ty],
             _ => Default::default(),
         }
     }
+
+    /// Returns whether this `CType` uses `intptr_t` or `uintptr_t`, whose width depends on the
+    /// platform.
+    pub fn is_pointer_sized(&self) -> bool {
+        match *self {
+            CType::FnDecl {
+                ref args,
+                ref return_type,
+                ..
+            } => {
+                return_type.is_pointer_sized()
+                    || args.iter().any(|CTypeNamed(_, cty)| cty.is_pointer_sized())
+            }
+            CType::Ptr(ref cty, _) | CType::Array(ref cty, _) => cty.is_pointer_sized(),
+            CType::Native("intptr_t") | CType::Native("uintptr_t") => true,
+            _ => false,
+        }
+    }
 }
 
 impl Display for CType {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match *self {
             CType::Void => write!(f, "void"),
-            CType::Mapping(ref s) => write!(f, "{}", s),
+            CType::Mapping(ref s) | CType::Custom(ref s) => write!(f, "{}", s),
             CType::Native(s) => write!(f, "{}", s),
+            CType::Tagged(kind, ref name) => write!(f, "{} {}", kind, name),
+            CType::Atomic(s) => write!(f, "{}", s),
             CType::Ptr(ref cty, ref ptrty) => write!(f, "{}{}*", cty, ptrty),
+            CType::Array(..) => {
+                let (elem, lens) = self.array_lens();
+                write!(f, "{}{}", elem, lens)
+            }
             CType::FnDecl {
                 ref inner,
                 ref args,
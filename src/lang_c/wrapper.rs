@@ -0,0 +1,124 @@
+//! Generation of `#[no_mangle]` wrappers of functions whose signatures aren't FFI-safe.
+
+use crate::common;
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::{Error, ErrorCode, Level};
+
+/// Generate the Rust source of a C-compatible wrapper named `wrapper_name` of the function
+/// `item`, which converts the arguments and calls it.
+///
+/// String slices and slices are passed as a pointer and a length (`name`, `name_len`) and
+/// references as pointers. Other types are passed through unchanged.
+pub fn rust_wrapper(item: &ast::Item, wrapper_name: &str) -> Result<String, Error> {
+    let fn_decl = match item.node {
+        ast::ItemKind::Fn(ref fn_decl, _, _, _, ref generics, _) => {
+            if generics.is_parameterized() {
+                return Err(Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::GenericFn),
+                    span: Some(item.span),
+                    message: "bindgen can not wrap parameterized functions".into(),
+                });
+            }
+            fn_decl
+        }
+        _ => {
+            return Err(Error {
+                level: Level::Bug,
+                code: Some(ErrorCode::WrongItem),
+                span: Some(item.span),
+                message: "`rust_wrapper` called on wrong `Item_`".into(),
+            });
+        }
+    };
+
+    let mut params = Vec::new();
+    let mut conversions = String::new();
+    let mut args = Vec::new();
+    for (index, arg) in fn_decl.inputs.iter().enumerate() {
+        // Only the identifier of patterns like `mut name` is kept, and other patterns get a name.
+        let name = match arg.pat.node {
+            ast::PatKind::Ident(_, ident, None) => ident.node.name.as_str().to_string(),
+            _ => format!("arg{}", index),
+        };
+        match arg.ty.node {
+            ast::TyKind::Rptr(_, ref ptr) => match ptr.ty.node {
+                ast::TyKind::Path(None, ref path) if pprust::path_to_string(path) == "str" => {
+                    params.push(format!("{}: *const std::os::raw::c_char", name));
+                    params.push(format!("{}_len: usize", name));
+                    conversions.push_str(&format!(
+                        "    let {0} = String::from_utf8_lossy(std::slice::from_raw_parts(\
+                         {0} as *const u8, {0}_len));\n",
+                        name
+                    ));
+                    args.push(format!("&{}", name));
+                }
+                ast::TyKind::Slice(ref elem) => {
+                    let (ptr_ty, from_raw_parts) = match ptr.mutbl {
+                        ast::Mutability::Immutable => ("*const", "from_raw_parts"),
+                        ast::Mutability::Mutable => ("*mut", "from_raw_parts_mut"),
+                    };
+                    let elem = pprust::ty_to_string(elem);
+                    params.push(format!("{}: {} {}", name, ptr_ty, elem));
+                    params.push(format!("{}_len: usize", name));
+                    conversions.push_str(&format!(
+                        "    let {0} = std::slice::{1}({0}, {0}_len);\n",
+                        name, from_raw_parts
+                    ));
+                    args.push(name);
+                }
+                _ => {
+                    let ty = pprust::ty_to_string(&ptr.ty);
+                    let (ptr_ty, reborrow) = match ptr.mutbl {
+                        ast::Mutability::Immutable => ("*const", "&*"),
+                        ast::Mutability::Mutable => ("*mut", "&mut *"),
+                    };
+                    params.push(format!("{}: {} {}", name, ptr_ty, ty));
+                    args.push(format!("{}{}", reborrow, name));
+                }
+            },
+            _ => {
+                params.push(format!("{}: {}", name, pprust::ty_to_string(&arg.ty)));
+                args.push(name);
+            }
+        }
+    }
+
+    let output = match fn_decl.output {
+        ast::FunctionRetTy::Default(..) => String::new(),
+        ast::FunctionRetTy::Ty(ref ty) => match ty.node {
+            ast::TyKind::Rptr(..) => {
+                return Err(Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::UnsupportedType),
+                    span: Some(ty.span),
+                    message: "bindgen can not wrap functions returning references".into(),
+                });
+            }
+            _ => format!(" -> {}", pprust::ty_to_string(ty)),
+        },
+    };
+
+    let docs: String = item
+        .attrs
+        .iter()
+        .filter_map(|attr| common::retrieve_docstring(attr, ""))
+        .flat_map(|docs| {
+            docs.lines()
+                .map(|line| format!("///{}\n", line))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    Ok(format!(
+        "{}#[no_mangle]\npub unsafe extern \"C\" fn {}({}){} {{\n{}    {}({})\n}}\n",
+        docs,
+        wrapper_name,
+        params.join(", "),
+        output,
+        conversions,
+        item.ident.name.as_str(),
+        args.join(", ")
+    ))
+}
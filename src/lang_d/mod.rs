@@ -0,0 +1,236 @@
+//! Functions for generating D bindings.
+
+#[cfg(test)]
+mod tests;
+mod types;
+
+use self::types::{escape, primitive_to_d, ret_ty_to_d, rust_to_d};
+use crate::common::{
+    self, append_output, check_no_mangle, check_repr_c, indent, parse_attr,
+    retrieve_rust_style_docstring, Lang, Outputs,
+};
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::Error;
+
+/// Generates a single D module declaring the exported items with C linkage.
+///
+/// D can use C types and declarations directly, so the structs, enums and functions are
+/// declared as they are in the C headers, in the order they appear in the Rust code.
+pub struct LangD {
+    lib_name: String,
+    code: String,
+}
+
+impl LangD {
+    pub fn new() -> Self {
+        LangD {
+            lib_name: "backend".to_owned(),
+            code: String::new(),
+        }
+    }
+
+    /// Set the name of the native library, used to name the generated D module.
+    pub fn set_lib_name<T: Into<String>>(&mut self, name: T) {
+        self.lib_name = name.into();
+    }
+}
+
+impl Default for LangD {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lang for LangD {
+    /// Convert `pub type A = B;` into `alias A = B;`.
+    fn parse_ty(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        if let ast::ItemKind::Ty(ref ty, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Ok(());
+            }
+
+            let (_, docs) = parse_attr(&item.attrs, |_| true, retrieve_rust_style_docstring);
+            self.code.push_str(&docs);
+            self.code.push_str(&format!(
+                "alias {} = {};\n\n",
+                item.ident.name.as_str(),
+                rust_to_d(ty)?
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Convert a `#[repr(C)]` enum into a D enum, with the integer type given in the `repr`.
+    fn parse_enum(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, retrieve_rust_style_docstring);
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Enum(ref definition, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_enum_error(item.span));
+            }
+
+            let base = common::parse_repr(&item.attrs)
+                .int
+                .and_then(primitive_to_d)
+                .unwrap_or("int");
+
+            self.code.push_str(&docs);
+            self.code.push_str(&format!(
+                "enum {} : {} {{\n",
+                item.ident.name.as_str(),
+                base
+            ));
+
+            for variant in &definition.variants {
+                if !variant.node.data.is_unit() {
+                    return Err(common::data_enum_error(variant.span));
+                }
+            }
+            let values = common::discriminants(&item.ident.name.as_str(), definition)?;
+
+            for (variant, value) in definition.variants.iter().zip(values) {
+                let (_, docs) =
+                    parse_attr(&variant.node.attrs, |_| true, retrieve_rust_style_docstring);
+                self.code.push_str(&indent(&docs));
+
+                let name = escape(&variant.node.name.name.as_str());
+                match variant.node.disr_expr {
+                    Some(ref expr) => self.code.push_str(&format!(
+                        "    {} = {},\n",
+                        name,
+                        common::c_like_source(expr).unwrap_or_else(|| value.to_string())
+                    )),
+                    None => self.code.push_str(&format!("    {},\n", name)),
+                }
+            }
+
+            self.code.push_str("}\n\n");
+        }
+
+        Ok(())
+    }
+
+    /// Convert a `#[repr(C)]` struct into a D struct.
+    ///
+    /// `repr(align(N))` becomes `align(N) struct` and `repr(packed(N))` an `align(N):` attribute
+    /// for the fields. Tuple structs with a single field are opaque, so they're only declared.
+    fn parse_struct(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, retrieve_rust_style_docstring);
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Struct(ref variants, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_struct_error(item.span));
+            }
+
+            let name = item.ident.name.as_str();
+            let repr = common::parse_repr(&item.attrs);
+            self.code.push_str(&docs);
+            if let Some(align) = repr.align {
+                self.code.push_str(&format!("align({}) ", align));
+            }
+
+            if variants.is_struct() {
+                self.code.push_str(&format!("struct {} {{\n", name));
+                if let Some(packed) = repr.packed {
+                    self.code.push_str(&format!("align({}):\n", packed));
+                }
+                for field in variants.fields() {
+                    let (_, docs) =
+                        parse_attr(&field.attrs, |_| true, retrieve_rust_style_docstring);
+                    self.code.push_str(&indent(&docs));
+
+                    let field_name = field.ident.map(|ident| escape(&ident.name.as_str()));
+                    self.code.push_str(&format!(
+                        "    {} {};\n",
+                        rust_to_d(&field.ty)?,
+                        field_name.unwrap_or_default()
+                    ));
+                }
+                self.code.push_str("}\n\n");
+            } else if variants.is_tuple() && variants.fields().len() == 1 {
+                self.code.push_str(&format!("struct {};\n\n", name));
+            } else {
+                return Err(common::tuple_struct_error(item.span));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Declare an exported function.
+    fn parse_fn(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (no_mangle, docs) =
+            parse_attr(&item.attrs, check_no_mangle, retrieve_rust_style_docstring);
+        if !no_mangle {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Fn(ref fn_decl, _, _, abi, ref generics, _) = item.node {
+            if !common::is_extern(abi) {
+                return Ok(());
+            }
+            if generics.is_parameterized() {
+                return Err(common::generic_fn_error(item.span));
+            }
+
+            let name = common::symbol_name(item);
+
+            let mut args = Vec::new();
+            for arg in &fn_decl.inputs {
+                args.push(format!(
+                    "{} {}",
+                    rust_to_d(&arg.ty)?,
+                    escape(&pprust::pat_to_string(&arg.pat))
+                ));
+            }
+
+            self.code.push_str(&docs);
+            self.code.push_str(&format!(
+                "{} {}({});\n\n",
+                ret_ty_to_d(&fn_decl.output)?,
+                name,
+                args.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn finalise_output(&mut self, outputs: &mut Outputs) -> Result<(), Error> {
+        let code = format!(
+            "module {};\n\nimport core.stdc.config : c_long, c_ulong;\n\nextern (C):\n\n{}",
+            self.lib_name, self.code
+        );
+        append_output(code, &format!("{}.d", self.lib_name), outputs);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use super::LangD;
+use crate::test_utils::fetch;
+use crate::ErrorCode;
+
+#[test]
+fn module() {
+    let outputs = compile!(LangD::new(), {
+        /// Status of an operation.
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            /// Something went wrong.
+            NotFound = 4,
+        }
+
+        #[repr(C, u8)]
+        pub enum Mode {
+            Read,
+            Write,
+        }
+
+        #[repr(C)]
+        pub struct Shape {
+            /// Number of sides.
+            num_sides: u32,
+            name: *const c_char,
+            data: *mut c_void,
+            coords: [f32; 2],
+            status: Status,
+        }
+
+        #[repr(C)]
+        pub struct Handle(u64);
+
+        pub type Callback = extern "C" fn(user_data: *mut c_void, result: i32);
+
+        /// Draw a shape.
+        #[no_mangle]
+        pub extern "C" fn draw(shape: *const Shape, size: usize, cb: Callback) -> bool {}
+
+        #[no_mangle]
+        pub extern "C" fn compare(f: extern "C" fn(libc::c_long, i64) -> c_int) {}
+
+        pub extern "C" fn not_exported() {}
+    });
+
+    let actual = fetch(&outputs, "backend.d");
+    let expected = indoc!(
+        "module backend;
+
+         import core.stdc.config : c_long, c_ulong;
+
+         extern (C):
+
+         /// Status of an operation.
+         enum Status : int {
+             Ok,
+             /// Something went wrong.
+             NotFound = 4,
+         }
+
+         enum Mode : ubyte {
+             Read,
+             Write,
+         }
+
+         struct Shape {
+             /// Number of sides.
+             uint num_sides;
+             const(char)* name;
+             void* data;
+             float[2] coords;
+             Status status;
+         }
+
+         struct Handle;
+
+         alias Callback = void function(void* user_data, int result);
+
+         /// Draw a shape.
+         bool draw(const(Shape)* shape, size_t size, Callback cb);
+
+         void compare(int function(c_long, long) f);
+
+         "
+    );
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn keywords() {
+    let outputs = compile!(LangD::new(), {
+        #[repr(C)]
+        pub enum Scope {
+            Module,
+            version,
+        }
+
+        #[repr(C)]
+        pub struct Request {
+            body: *const u8,
+            module: u32,
+            out: [u8; 4],
+        }
+
+        #[no_mangle]
+        pub extern "C" fn call(function: extern "C" fn(alias: i32), version: u32) {}
+    });
+
+    let actual = fetch(&outputs, "backend.d");
+    let expected = indoc!(
+        "module backend;
+
+         import core.stdc.config : c_long, c_ulong;
+
+         extern (C):
+
+         enum Scope : int {
+             Module,
+             version_,
+         }
+
+         struct Request {
+             const(ubyte)* body_;
+             uint module_;
+             ubyte[4] out_;
+         }
+
+         void call(void function(int alias_) function_, uint version_);
+
+         "
+    );
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn enum_discriminants() {
+    let outputs = compile!(LangD::new(), {
+        #[repr(C, u16)]
+        pub enum Flags {
+            Shifted = 1 << 2,
+            Suffixed = 5u16,
+            Implicit,
+            Inverted = !0xFF00 as u16,
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.d");
+    assert!(actual.contains(
+        "enum Flags : ushort {\n    \
+         Shifted = 1 << 2,\n    \
+         Suffixed = 5,\n    \
+         Implicit,\n    \
+         Inverted = 255,\n\
+         }\n"
+    ));
+}
+
+#[test]
+fn export_names_and_arrays() {
+    let outputs = compile!(LangD::new(), {
+        #[repr(C)]
+        pub struct Grid {
+            cells: [[u8; 3]; 3],
+        }
+
+        #[no_mangle]
+        #[export_name = "backend_fill"]
+        pub extern "C" fn fill(grid: *mut Grid, value: u8) {}
+    });
+
+    let actual = fetch(&outputs, "backend.d");
+    assert!(actual.contains("struct Grid {\n    ubyte[3][3] cells;\n}\n"));
+    assert!(actual.contains("void backend_fill(Grid* grid, ubyte value);\n"));
+}
+
+#[test]
+fn packed_and_aligned_structs() {
+    let outputs = compile!(LangD::new(), {
+        #[repr(C, packed)]
+        pub struct Header {
+            tag: u8,
+            len: u32,
+        }
+
+        #[repr(C, packed(2))]
+        pub struct Pair {
+            tag: u8,
+            len: u32,
+        }
+
+        #[repr(C, align(16))]
+        pub struct Block {
+            data: [u8; 4],
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.d");
+    assert!(actual.contains("struct Header {\nalign(1):\n    ubyte tag;\n    uint len;\n}\n"));
+    assert!(actual.contains("struct Pair {\nalign(2):\n    ubyte tag;\n    uint len;\n}\n"));
+    assert!(actual.contains("align(16) struct Block {\n    ubyte[4] data;\n}\n"));
+}
+
+#[test]
+fn references() {
+    let errors = unwrap!(try_compile!(LangD::new(), {
+        #[no_mangle]
+        pub extern "C" fn area(shape: &Shape) -> f64 {}
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnsupportedType));
+    assert!(errors[0].message.contains("`&Shape`"));
+}
@@ -0,0 +1,164 @@
+//! Conversion of Rust types into D types.
+
+use crate::common::{self, Primitive};
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::{Error, ErrorCode, Level};
+
+/// Turn a Rust type into a D type.
+///
+/// Types which aren't primitives are assumed to be declared in the same D module.
+pub fn rust_to_d(ty: &ast::Ty) -> Result<String, Error> {
+    match ty.node {
+        // References aren't guaranteed to be valid in C, so only raw pointers are allowed.
+        ast::TyKind::Ptr(ref ptr) => {
+            let pointee = rust_to_d(&ptr.ty)?;
+            Ok(match ptr.mutbl {
+                ast::Mutability::Mutable => format!("{}*", pointee),
+                ast::Mutability::Immutable => format!("const({})*", pointee),
+            })
+        }
+        ast::TyKind::Array(ref inner, ref len) => Ok(format!(
+            "{}[{}]",
+            rust_to_d(inner)?,
+            pprust::expr_to_string(len)
+        )),
+        ast::TyKind::BareFn(ref bare_fn) => fn_ptr_to_d(&bare_fn.decl),
+        ast::TyKind::Path(None, ref path) => path_to_d(path, ty),
+        _ if pprust::ty_to_string(ty) == "()" => Ok("void".to_string()),
+        _ => Err(unsupported(ty)),
+    }
+}
+
+/// Turn the return type of a Rust function into a D type.
+pub fn ret_ty_to_d(output: &ast::FunctionRetTy) -> Result<String, Error> {
+    match *output {
+        ast::FunctionRetTy::Default(..) => Ok("void".to_string()),
+        ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
+            Err(common::never_return_error(ty.span))
+        }
+        ast::FunctionRetTy::Ty(ref ty) => rust_to_d(ty),
+    }
+}
+
+/// Function pointers use D's `function` type, e.g. `int function(int value)`.
+fn fn_ptr_to_d(decl: &ast::FnDecl) -> Result<String, Error> {
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        let ty = rust_to_d(&arg.ty)?;
+        let name = escape(&pprust::pat_to_string(&arg.pat));
+        args.push(if name.is_empty() {
+            ty
+        } else {
+            format!("{} {}", ty, name)
+        });
+    }
+
+    Ok(format!(
+        "{} function({})",
+        ret_ty_to_d(&decl.output)?,
+        args.join(", ")
+    ))
+}
+
+fn path_to_d(path: &ast::Path, ty: &ast::Ty) -> Result<String, Error> {
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .collect();
+
+    match segments.split_last() {
+        Some((name, [])) => Ok(primitive_to_d(name).map_or_else(|| name.clone(), str::to_string)),
+        Some((name, module)) => match &*module.join("::") {
+            "libc" | "std::os::raw" | "core::ffi" | "std::ffi" => primitive_to_d(name)
+                .map(str::to_string)
+                .ok_or_else(|| unsupported(ty)),
+            _ => Err(Error {
+                level: Level::Error,
+                code: Some(ErrorCode::ForeignModuleType),
+                span: Some(ty.span),
+                message: "bindgen can not handle types in other modules (yet)".into(),
+            }),
+        },
+        None => Err(unsupported(ty)),
+    }
+}
+
+/// Convert a Rust primitive (or `libc`/`std::os::raw` type) into D.
+///
+/// `c_long` and `c_ulong` come from `core.stdc.config`, since their size depends on the
+/// platform.
+pub fn primitive_to_d(ty: &str) -> Option<&'static str> {
+    if let Some(primitive) = common::primitive_named(ty) {
+        return Some(match primitive {
+            Primitive::Unit => "void",
+            Primitive::Bool => "bool",
+            Primitive::Float { bits: 32 } => "float",
+            Primitive::Float { .. } => "double",
+            Primitive::Int { bits, signed } => match (bits, signed) {
+                (Some(8), true) => "byte",
+                (Some(16), true) => "short",
+                (Some(32), true) => "int",
+                (Some(_), true) => "long",
+                (None, true) => "ptrdiff_t",
+                (Some(8), false) => "ubyte",
+                (Some(16), false) => "ushort",
+                (Some(32), false) => "uint",
+                (Some(_), false) => "ulong",
+                (None, false) => "size_t",
+            },
+        });
+    }
+
+    Some(match ty {
+        "c_void" => "void",
+        "c_float" => "float",
+        "c_double" => "double",
+        "c_schar" => "byte",
+        "c_short" => "short",
+        "c_int" => "int",
+        "c_longlong" => "long",
+        "c_uchar" => "ubyte",
+        "c_ushort" => "ushort",
+        "c_uint" => "uint",
+        "c_ulonglong" => "ulong",
+        "size_t" => "size_t",
+        "c_char" => "char",
+        "c_long" => "c_long",
+        "c_ulong" => "c_ulong",
+        _ => return None,
+    })
+}
+
+fn unsupported(ty: &ast::Ty) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::UnsupportedType),
+        span: Some(ty.span),
+        message: format!(
+            "bindgen can not handle the type `{}` in D",
+            pprust::ty_to_string(ty)
+        ),
+    }
+}
+
+/// Append an underscore to identifiers which are D keywords, as the D style guide recommends.
+pub fn escape(name: &str) -> String {
+    match name {
+        "abstract" | "alias" | "align" | "asm" | "assert" | "auto" | "body" | "bool" | "break"
+        | "byte" | "case" | "cast" | "catch" | "cdouble" | "cent" | "cfloat" | "char" | "class"
+        | "const" | "continue" | "creal" | "dchar" | "debug" | "default" | "delegate"
+        | "delete" | "deprecated" | "do" | "double" | "else" | "enum" | "export" | "extern"
+        | "false" | "final" | "finally" | "float" | "for" | "foreach" | "foreach_reverse"
+        | "function" | "goto" | "idouble" | "if" | "ifloat" | "immutable" | "import" | "in"
+        | "inout" | "int" | "interface" | "invariant" | "ireal" | "is" | "lazy" | "long"
+        | "macro" | "mixin" | "module" | "new" | "nothrow" | "null" | "out" | "override"
+        | "package" | "pragma" | "private" | "protected" | "public" | "pure" | "real" | "ref"
+        | "return" | "scope" | "shared" | "short" | "static" | "struct" | "super" | "switch"
+        | "synchronized" | "template" | "this" | "throw" | "true" | "try" | "typeid" | "typeof"
+        | "ubyte" | "ucent" | "uint" | "ulong" | "union" | "unittest" | "ushort" | "version"
+        | "void" | "wchar" | "while" | "with" => format!("{}_", name),
+        _ => name.to_string(),
+    }
+}
@@ -0,0 +1,524 @@
+//! Functions for generating Kotlin bindings using JNA.
+
+#[cfg(test)]
+mod tests;
+mod types;
+
+use self::types::{primitive_to_kotlin, ret_ty_to_kotlin, rust_to_kotlin, Usage};
+use crate::common::{
+    self, append_output, check_no_mangle, check_repr_c, indent, parse_attr,
+    retrieve_rust_style_docstring, Lang, Outputs,
+};
+use crate::syntax::ast;
+use crate::syntax::ptr::P;
+use crate::{Error, ErrorCode, Level};
+use inflector::Inflector;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+/// Generates a Kotlin file binding the C ABI of the library with JNA, so no glue code is needed.
+///
+/// Functions are methods of a `Library` interface, structs are `Structure` subclasses and enums
+/// are objects with a constant for each variant, since JNA passes C enums as integers.
+pub struct LangKotlin {
+    lib_name: String,
+    package: Option<String>,
+    context: Context,
+    decls: Vec<Decl>,
+}
+
+pub struct Context {
+    /// Kotlin integer type of each enum.
+    enums: HashMap<String, &'static str>,
+    /// Names of the structs, which are `Structure` subclasses.
+    structs: HashSet<String>,
+    /// Names of the function pointer aliases, which are `Callback` interfaces.
+    callbacks: HashSet<String>,
+    /// Other type aliases.
+    aliases: HashMap<String, P<ast::Ty>>,
+    /// Values of the integer constants, used for array lengths.
+    consts: HashMap<String, i64>,
+    /// Whether `isize` or `usize` is used, so the `IntPtr` and `UIntPtr` classes are needed.
+    pointer_sized: Cell<bool>,
+}
+
+/// Declaration in the generated file, in the order of the Rust code.
+enum Decl {
+    Enum {
+        docs: String,
+        name: String,
+        int: &'static str,
+        variants: Vec<(String, String, String)>,
+    },
+    Struct {
+        docs: String,
+        name: String,
+        fields: Vec<(String, String, P<ast::Ty>)>,
+    },
+    Alias {
+        docs: String,
+        name: String,
+        ty: P<ast::Ty>,
+    },
+    Callback {
+        docs: String,
+        name: String,
+        decl: P<ast::FnDecl>,
+    },
+    Function {
+        docs: String,
+        name: String,
+        decl: P<ast::FnDecl>,
+    },
+}
+
+impl LangKotlin {
+    pub fn new() -> Self {
+        LangKotlin {
+            lib_name: "backend".to_owned(),
+            package: None,
+            context: Context {
+                enums: Default::default(),
+                structs: Default::default(),
+                callbacks: Default::default(),
+                aliases: Default::default(),
+                consts: Default::default(),
+                pointer_sized: Cell::new(false),
+            },
+            decls: Vec::new(),
+        }
+    }
+
+    /// Set the name of the native library, loaded with `Native.load` and used to name the
+    /// generated Kotlin file.
+    pub fn set_lib_name<T: Into<String>>(&mut self, name: T) {
+        self.lib_name = name.into();
+    }
+
+    /// Set the package of the generated Kotlin file.
+    pub fn set_package<T: Into<String>>(&mut self, package: T) {
+        self.package = Some(package.into());
+    }
+
+    /// Name of the `Library` interface, e.g. `BackendLibrary`.
+    fn library_name(&self) -> String {
+        format!("{}Library", self.lib_name.to_pascal_case())
+    }
+
+    fn emit_enum(
+        output: &mut String,
+        docs: &str,
+        name: &str,
+        int: &str,
+        variants: &[(String, String, String)],
+    ) {
+        output.push_str(docs);
+        unwrap!(writeln!(output, "object {} {{", name));
+        for (docs, variant, value) in variants {
+            output.push_str(&indent(docs));
+            unwrap!(writeln!(
+                output,
+                "    const val {}: {} = {}",
+                variant, int, value
+            ));
+        }
+        output.push_str("}\n\n");
+    }
+
+    fn emit_struct(
+        &self,
+        output: &mut String,
+        docs: &str,
+        name: &str,
+        fields: &[(String, String, P<ast::Ty>)],
+    ) -> Result<(), Error> {
+        let order: Vec<_> = fields
+            .iter()
+            .map(|(_, field, _)| format!("\"{}\"", field))
+            .collect();
+
+        output.push_str(docs);
+        unwrap!(writeln!(
+            output,
+            "@Structure.FieldOrder({})",
+            order.join(", ")
+        ));
+        unwrap!(writeln!(output, "open class {} : Structure() {{", name));
+        for (docs, field, ty) in fields {
+            let ty = rust_to_kotlin(ty, Usage::Field, &self.context)?;
+            output.push_str(&indent(docs));
+            unwrap!(writeln!(
+                output,
+                "    @JvmField var {}: {} = {}",
+                escape(field),
+                ty.name,
+                ty.default
+            ));
+        }
+        unwrap!(write!(
+            output,
+            "\n    class ByReference : {0}(), Structure.ByReference\n    \
+             class ByValue : {0}(), Structure.ByValue\n}}\n\n",
+            name
+        ));
+
+        Ok(())
+    }
+
+    /// Kotlin parameters of a function, e.g. `shape: Shape?, size: Long`.
+    fn params(&self, decl: &ast::FnDecl) -> Result<String, Error> {
+        let mut params = Vec::new();
+        for (index, arg) in decl.inputs.iter().enumerate() {
+            let name = match arg.pat.node {
+                ast::PatKind::Ident(_, ident, _) => escape(&ident.node.name.as_str()),
+                _ => format!("arg{}", index),
+            };
+            let ty = rust_to_kotlin(&arg.ty, Usage::Fn, &self.context)?;
+            params.push(format!("{}: {}", name, ty.name));
+        }
+
+        Ok(params.join(", "))
+    }
+
+    /// Declaration of a function, e.g. `fun draw(shape: Shape?): Byte`.
+    fn fn_signature(&self, name: &str, decl: &ast::FnDecl) -> Result<String, Error> {
+        let params = self.params(decl)?;
+        Ok(match ret_ty_to_kotlin(&decl.output, &self.context)? {
+            Some(ret) => format!("fun {}({}): {}", name, params, ret.name),
+            None => format!("fun {}({})", name, params),
+        })
+    }
+}
+
+impl Default for LangKotlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lang for LangKotlin {
+    /// Record the value of an integer constant, which may be used as an array length.
+    fn parse_const(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        if let ast::ItemKind::Const(_, ref expr) = item.node {
+            let consts = &self.context.consts;
+            if let Some(value) =
+                common::eval_int_with(expr, &|path| consts.get(&types::path_name(path)).cloned())
+            {
+                let _ = self
+                    .context
+                    .consts
+                    .insert(item.ident.name.as_str().to_string(), value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convert `pub type A = B;` into `typealias A = B`, or into a `Callback` interface for
+    /// function pointers.
+    fn parse_ty(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        if let ast::ItemKind::Ty(ref ty, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Ok(());
+            }
+
+            let (_, docs) = parse_attr(&item.attrs, |_| true, retrieve_rust_style_docstring);
+            let name = item.ident.name.as_str().to_string();
+            if let ast::TyKind::BareFn(ref bare_fn) = ty.node {
+                let _ = self.context.callbacks.insert(name.clone());
+                self.decls.push(Decl::Callback {
+                    docs,
+                    name,
+                    decl: bare_fn.decl.clone(),
+                });
+            } else {
+                let _ = self.context.aliases.insert(name.clone(), ty.clone());
+                self.decls.push(Decl::Alias {
+                    docs,
+                    name,
+                    ty: ty.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convert a `#[repr(C)]` enum into an object with a constant for each variant.
+    fn parse_enum(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, retrieve_rust_style_docstring);
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Enum(ref definition, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_enum_error(item.span));
+            }
+
+            let int = match common::parse_repr(&item.attrs)
+                .int
+                .and_then(primitive_to_kotlin)
+            {
+                Some(ty) => match &*ty.name {
+                    "Byte" => "Byte",
+                    "Short" => "Short",
+                    "Long" => "Long",
+                    "IntPtr" | "UIntPtr" => {
+                        return Err(Error {
+                            level: Level::Error,
+                            code: Some(ErrorCode::UnsupportedType),
+                            span: Some(item.span),
+                            message: "pointer-sized enums can not be used in Kotlin, since JNA \
+                                      has no constants of that size"
+                                .into(),
+                        });
+                    }
+                    _ => "Int",
+                },
+                None => "Int",
+            };
+
+            let mut variants = Vec::new();
+            for variant in &definition.variants {
+                if !variant.node.data.is_unit() {
+                    return Err(common::data_enum_error(variant.span));
+                }
+            }
+            let values = common::discriminants(&item.ident.name.as_str(), definition)?;
+
+            for (variant, value) in definition.variants.iter().zip(values) {
+                let (_, docs) =
+                    parse_attr(&variant.node.attrs, |_| true, retrieve_rust_style_docstring);
+                let name = variant.node.name.name.as_str().to_string();
+                variants.push((docs, name, int_literal(value, int)));
+            }
+
+            let name = item.ident.name.as_str().to_string();
+            let _ = self.context.enums.insert(name.clone(), int);
+            self.decls.push(Decl::Enum {
+                docs,
+                name,
+                int,
+                variants,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Convert a `#[repr(C)]` struct into a `Structure` subclass.
+    ///
+    /// Opaque (unit or tuple) structs are only used behind pointers, so they're `Pointer`s.
+    fn parse_struct(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, retrieve_rust_style_docstring);
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Struct(ref variants, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_struct_error(item.span));
+            }
+            if !variants.is_struct() {
+                return Ok(());
+            }
+
+            let mut fields = Vec::new();
+            for field in variants.fields() {
+                let (_, docs) = parse_attr(&field.attrs, |_| true, retrieve_rust_style_docstring);
+                if let Some(ident) = field.ident {
+                    fields.push((docs, ident.name.as_str().to_string(), field.ty.clone()));
+                }
+            }
+
+            let name = item.ident.name.as_str().to_string();
+            let _ = self.context.structs.insert(name.clone());
+            self.decls.push(Decl::Struct { docs, name, fields });
+        }
+
+        Ok(())
+    }
+
+    /// Declare an exported function as a method of the `Library` interface.
+    fn parse_fn(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (no_mangle, docs) =
+            parse_attr(&item.attrs, check_no_mangle, retrieve_rust_style_docstring);
+        if !no_mangle {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Fn(ref fn_decl, _, _, abi, ref generics, _) = item.node {
+            if !common::is_extern(abi) {
+                return Ok(());
+            }
+            if generics.is_parameterized() {
+                return Err(common::generic_fn_error(item.span));
+            }
+
+            // JNA looks the symbols up by the names of the methods.
+            let name = common::symbol_name(item);
+
+            self.decls.push(Decl::Function {
+                docs,
+                name,
+                decl: fn_decl.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn finalise_output(&mut self, outputs: &mut Outputs) -> Result<(), Error> {
+        let mut output = String::new();
+        if let Some(ref package) = self.package {
+            unwrap!(write!(output, "package {}\n\n", package));
+        }
+        let mut decls = String::new();
+        let mut functions = String::new();
+        for decl in &self.decls {
+            match *decl {
+                Decl::Enum {
+                    ref docs,
+                    ref name,
+                    int,
+                    ref variants,
+                } => Self::emit_enum(&mut decls, docs, name, int, variants),
+                Decl::Struct {
+                    ref docs,
+                    ref name,
+                    ref fields,
+                } => self.emit_struct(&mut decls, docs, name, fields)?,
+                Decl::Alias {
+                    ref docs,
+                    ref name,
+                    ref ty,
+                } => {
+                    let ty = rust_to_kotlin(ty, Usage::Fn, &self.context)?;
+                    decls.push_str(docs);
+                    unwrap!(write!(decls, "typealias {} = {}\n\n", name, ty.name));
+                }
+                Decl::Callback {
+                    ref docs,
+                    ref name,
+                    ref decl,
+                } => {
+                    decls.push_str(docs);
+                    unwrap!(write!(
+                        decls,
+                        "interface {} : com.sun.jna.Callback {{\n    {}\n}}\n\n",
+                        name,
+                        self.fn_signature("invoke", decl)?
+                    ));
+                }
+                Decl::Function {
+                    ref docs,
+                    ref name,
+                    ref decl,
+                } => {
+                    functions.push_str(&indent(docs));
+                    unwrap!(write!(
+                        functions,
+                        "    {}\n\n",
+                        self.fn_signature(&escape(name), decl)?
+                    ));
+                }
+            }
+        }
+
+        let pointer_sized = self.context.pointer_sized.get();
+        if pointer_sized {
+            output.push_str("import com.sun.jna.IntegerType\n");
+        }
+        output.push_str(
+            "import com.sun.jna.Library\n\
+             import com.sun.jna.Native\n\
+             import com.sun.jna.NativeLong\n\
+             import com.sun.jna.Pointer\n\
+             import com.sun.jna.Structure\n\n",
+        );
+        if pointer_sized {
+            output.push_str(POINTER_SIZED_TYPES);
+        }
+        output.push_str(&decls);
+
+        unwrap!(write!(
+            output,
+            "interface {0} : Library {{\n{1}    companion object {{\n        \
+             val INSTANCE: {0} = Native.load(\"{2}\", {0}::class.java)\n    }}\n}}\n",
+            self.library_name(),
+            functions,
+            self.lib_name
+        ));
+
+        append_output(output, &format!("{}.kt", self.lib_name), outputs);
+
+        Ok(())
+    }
+}
+
+/// Pointer-sized integers, which are 4 or 8 bytes depending on the platform.
+const POINTER_SIZED_TYPES: &str = "\
+/// Pointer-sized signed integer (`isize`).
+class IntPtr(value: Long = 0) : IntegerType(Native.POINTER_SIZE, value, false)
+
+/// Pointer-sized unsigned integer (`usize`).
+class UIntPtr(value: Long = 0) : IntegerType(Native.POINTER_SIZE, value, true)
+
+";
+
+/// Kotlin literal of the given integer type, e.g. `5.toByte()` or `5L`.
+///
+/// Values out of range wrap around, like the unsigned integers JNA maps to signed types.
+fn int_literal(value: i64, int: &str) -> String {
+    let operand = if value < 0 {
+        format!("({})", value)
+    } else {
+        value.to_string()
+    };
+    match int {
+        "Byte" => format!("{}.toByte()", operand),
+        "Short" => format!("{}.toShort()", operand),
+        "Long" => format!("{}L", value),
+        _ if i32::try_from(value).is_ok() => value.to_string(),
+        _ => format!("{}.toInt()", operand),
+    }
+}
+
+/// Quote identifiers which are Kotlin keywords with backticks.
+fn escape(name: &str) -> String {
+    match name {
+        "as" | "break" | "class" | "continue" | "do" | "else" | "false" | "for" | "fun" | "if"
+        | "in" | "interface" | "is" | "null" | "object" | "package" | "return" | "super"
+        | "this" | "throw" | "true" | "try" | "typealias" | "typeof" | "val" | "var" | "when"
+        | "while" => format!("`{}`", name),
+        _ => name.to_string(),
+    }
+}
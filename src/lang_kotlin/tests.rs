@@ -0,0 +1,287 @@
+use super::LangKotlin;
+use crate::test_utils::fetch;
+use crate::ErrorCode;
+
+#[test]
+fn library() {
+    let outputs = compile!(LangKotlin::new(), {
+        /// Status of an operation.
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            /// Something went wrong.
+            NotFound = 4,
+            Timeout,
+        }
+
+        #[repr(C, u8)]
+        pub enum Mode {
+            Read,
+            Write,
+        }
+
+        #[repr(C)]
+        pub struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        #[repr(C)]
+        pub struct Shape {
+            /// Number of sides.
+            num_sides: u32,
+            name: *const c_char,
+            data: *mut c_void,
+            coords: [f32; 2],
+            status: Status,
+            origin: Point,
+            next: *mut Shape,
+            object: c_ulong,
+        }
+
+        #[repr(C)]
+        pub struct Handle(u64);
+
+        pub type Id = u64;
+
+        pub type Callback = extern "C" fn(user_data: *mut c_void, result: i32);
+
+        /// Draw a shape.
+        #[no_mangle]
+        pub extern "C" fn draw(shape: *const Shape, size: usize, cb: Callback) -> bool {}
+
+        #[no_mangle]
+        pub extern "C" fn move_to(handle: *mut Handle, origin: Point, id: Id) -> Status {}
+
+        pub extern "C" fn not_exported() {}
+    });
+
+    let actual = fetch(&outputs, "backend.kt");
+    let expected = indoc!(
+        "import com.sun.jna.IntegerType
+         import com.sun.jna.Library
+         import com.sun.jna.Native
+         import com.sun.jna.NativeLong
+         import com.sun.jna.Pointer
+         import com.sun.jna.Structure
+
+         /// Pointer-sized signed integer (`isize`).
+         class IntPtr(value: Long = 0) : IntegerType(Native.POINTER_SIZE, value, false)
+
+         /// Pointer-sized unsigned integer (`usize`).
+         class UIntPtr(value: Long = 0) : IntegerType(Native.POINTER_SIZE, value, true)
+
+         /// Status of an operation.
+         object Status {
+             const val Ok: Int = 0
+             /// Something went wrong.
+             const val NotFound: Int = 4
+             const val Timeout: Int = 5
+         }
+
+         object Mode {
+             const val Read: Byte = 0.toByte()
+             const val Write: Byte = 1.toByte()
+         }
+
+         @Structure.FieldOrder(\"x\", \"y\")
+         open class Point : Structure() {
+             @JvmField var x: Double = 0.0
+             @JvmField var y: Double = 0.0
+
+             class ByReference : Point(), Structure.ByReference
+             class ByValue : Point(), Structure.ByValue
+         }
+
+         @Structure.FieldOrder(\"num_sides\", \"name\", \"data\", \"coords\", \"status\", \"origin\", \"next\", \"object\")
+         open class Shape : Structure() {
+             /// Number of sides.
+             @JvmField var num_sides: Int = 0
+             @JvmField var name: String? = null
+             @JvmField var data: Pointer? = null
+             @JvmField var coords: FloatArray = FloatArray(2)
+             @JvmField var status: Int = 0
+             @JvmField var origin: Point = Point()
+             @JvmField var next: Shape.ByReference? = null
+             @JvmField var `object`: NativeLong = NativeLong()
+
+             class ByReference : Shape(), Structure.ByReference
+             class ByValue : Shape(), Structure.ByValue
+         }
+
+         typealias Id = Long
+
+         interface Callback : com.sun.jna.Callback {
+             fun invoke(user_data: Pointer?, result: Int)
+         }
+
+         interface BackendLibrary : Library {
+             /// Draw a shape.
+             fun draw(shape: Shape?, size: UIntPtr, cb: Callback?): Byte
+
+             fun move_to(handle: Pointer?, origin: Point.ByValue, id: Id): Int
+
+             companion object {
+                 val INSTANCE: BackendLibrary = Native.load(\"backend\", BackendLibrary::class.java)
+             }
+         }
+         "
+    );
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn package() {
+    let mut lang = LangKotlin::new();
+    lang.set_package("net.maidsafe.backend");
+    lang.set_lib_name("safe_app");
+
+    let outputs = compile!(lang, {
+        #[no_mangle]
+        pub extern "C" fn init() {}
+    });
+
+    let actual = fetch(&outputs, "safe_app.kt");
+    assert!(actual.starts_with("package net.maidsafe.backend\n\nimport com.sun.jna.Library\n"));
+    assert!(actual.contains(
+        "interface SafeAppLibrary : Library {\n    fun init()\n\n    companion object {\n        \
+         val INSTANCE: SafeAppLibrary = Native.load(\"safe_app\", SafeAppLibrary::class.java)"
+    ));
+}
+
+#[test]
+fn enum_discriminants() {
+    let outputs = compile!(LangKotlin::new(), {
+        #[repr(C)]
+        pub enum Flags {
+            A = 1 << 2,
+            B = 5u32,
+            C,
+            All = 0xFFFF_FFFF,
+        }
+
+        #[repr(C, u8)]
+        pub enum Level {
+            Low = 200,
+            High,
+        }
+
+        #[repr(C, i16)]
+        pub enum Offset {
+            Before = -1,
+            At,
+        }
+
+        #[repr(C, i64)]
+        pub enum Big {
+            Max = 1 << 40,
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.kt");
+    assert!(actual.contains(
+        "object Flags {\n    \
+         const val A: Int = 4\n    \
+         const val B: Int = 5\n    \
+         const val C: Int = 6\n    \
+         const val All: Int = 4294967295.toInt()\n}\n"
+    ));
+    assert!(actual.contains(
+        "object Offset {\n    \
+         const val Before: Short = (-1).toShort()\n    \
+         const val At: Short = 0.toShort()\n}\n"
+    ));
+    assert!(actual.contains("object Big {\n    const val Max: Long = 1099511627776L\n}\n"));
+    assert!(actual.contains(
+        "object Level {\n    \
+         const val Low: Byte = 200.toByte()\n    \
+         const val High: Byte = 201.toByte()\n}\n"
+    ));
+}
+
+#[test]
+fn keywords_export_names_and_arrays() {
+    let outputs = compile!(LangKotlin::new(), {
+        #[repr(C)]
+        pub struct Query {
+            object: u32,
+            when: [u8; 4],
+        }
+
+        pub type Visitor = extern "C" fn(typealias: u8);
+
+        #[no_mangle]
+        #[export_name = "backend_run"]
+        pub extern "C" fn run(val: i32, fun: Visitor) {}
+    });
+
+    let actual = fetch(&outputs, "backend.kt");
+    assert!(actual.contains(
+        "@Structure.FieldOrder(\"object\", \"when\")\n\
+         open class Query : Structure() {\n    \
+         @JvmField var `object`: Int = 0\n    \
+         @JvmField var `when`: ByteArray = ByteArray(4)\n"
+    ));
+    assert!(actual.contains("    fun invoke(`typealias`: Byte)\n"));
+    assert!(actual.contains("    fun backend_run(`val`: Int, `fun`: Visitor?)\n"));
+
+    // Arrays of arrays aren't supported.
+    let errors = unwrap!(try_compile!(LangKotlin::new(), {
+        #[repr(C)]
+        pub struct Matrix {
+            cells: [[f32; 2]; 2],
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnsupportedType));
+}
+
+#[test]
+fn array_lengths() {
+    let outputs = compile!(LangKotlin::new(), {
+        pub const KEY_LEN: usize = 32;
+
+        #[repr(C)]
+        pub struct Keys {
+            key: [u8; KEY_LEN],
+            keys: [u8; KEY_LEN * 2],
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.kt");
+    assert!(actual.contains("    @JvmField var key: ByteArray = ByteArray(32)\n"));
+    assert!(actual.contains("    @JvmField var keys: ByteArray = ByteArray(64)\n"));
+
+    let errors = unwrap!(try_compile!(LangKotlin::new(), {
+        #[repr(C)]
+        pub struct Key {
+            key: [u8; KEY_LEN],
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnknownArrayLen));
+    assert!(errors[0].message.contains("`KEY_LEN`"));
+}
+
+#[test]
+fn pointer_sized_integers() {
+    let outputs = compile!(LangKotlin::new(), {
+        #[no_mangle]
+        pub extern "C" fn offset(base: isize) -> i32 {}
+    });
+
+    let actual = fetch(&outputs, "backend.kt");
+    assert!(actual.starts_with("import com.sun.jna.IntegerType\n"));
+    assert!(actual.contains("    fun offset(base: IntPtr): Int\n"));
+
+    let outputs = compile!(LangKotlin::new(), {
+        #[no_mangle]
+        pub extern "C" fn offset(base: i64) -> i32 {}
+    });
+
+    let actual = fetch(&outputs, "backend.kt");
+    assert!(!actual.contains("IntegerType"));
+    assert!(!actual.contains("IntPtr"));
+}
@@ -0,0 +1,257 @@
+//! Conversion of Rust types into the Kotlin types used by JNA.
+
+use super::Context;
+use crate::common::{self, Primitive};
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::{Error, ErrorCode, Level};
+
+/// Where a type is used, which decides how structs are passed.
+///
+/// JNA passes structs by reference in function arguments unless they're `Structure.ByValue`,
+/// and embeds them by value in other structs unless they're `Structure.ByReference`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Usage {
+    /// Function argument or return type.
+    Fn,
+    /// Struct field.
+    Field,
+}
+
+/// Kotlin type, with the value a struct field of this type is initialised with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KotlinType {
+    pub name: String,
+    pub default: String,
+}
+
+impl KotlinType {
+    fn new<N: Into<String>, D: Into<String>>(name: N, default: D) -> Self {
+        KotlinType {
+            name: name.into(),
+            default: default.into(),
+        }
+    }
+
+    fn nullable<N: Into<String>>(name: N) -> Self {
+        KotlinType::new(format!("{}?", name.into()), "null")
+    }
+}
+
+/// Turn a Rust type into a Kotlin type.
+pub fn rust_to_kotlin(ty: &ast::Ty, usage: Usage, context: &Context) -> Result<KotlinType, Error> {
+    match ty.node {
+        ast::TyKind::Ptr(ref ptr) | ast::TyKind::Rptr(_, ref ptr) => {
+            ptr_to_kotlin(ptr, usage, context)
+        }
+        ast::TyKind::Array(ref elem, ref len) if usage == Usage::Field => {
+            let elem = rust_to_kotlin(elem, usage, context)?;
+            match &*elem.name {
+                "Byte" | "Short" | "Int" | "Long" | "Float" | "Double" => {
+                    let array = format!("{}Array", elem.name);
+                    let default = format!("{}({})", array, array_len(len, context)?);
+                    Ok(KotlinType::new(array, default))
+                }
+                _ => Err(unsupported(ty)),
+            }
+        }
+        // Arrays decay into pointers in function signatures.
+        ast::TyKind::Array(..) => Ok(KotlinType::nullable("Pointer")),
+        ast::TyKind::BareFn(..) => Err(Error {
+            level: Level::Error,
+            code: Some(ErrorCode::AnonymousFnPtr),
+            span: Some(ty.span),
+            message: "function pointers must be declared with a type alias to be used in Kotlin"
+                .into(),
+        }),
+        ast::TyKind::Path(None, ref path) => path_to_kotlin(path, ty, usage, context),
+        _ if pprust::ty_to_string(ty) == "()" => Ok(KotlinType::new("Unit", "Unit")),
+        _ => Err(unsupported(ty)),
+    }
+}
+
+/// Turn the return type of a Rust function into a Kotlin type, or `None` if it returns nothing.
+pub fn ret_ty_to_kotlin(
+    output: &ast::FunctionRetTy,
+    context: &Context,
+) -> Result<Option<KotlinType>, Error> {
+    match *output {
+        ast::FunctionRetTy::Default(..) => Ok(None),
+        ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
+            Err(common::never_return_error(ty.span))
+        }
+        ast::FunctionRetTy::Ty(ref ty) => {
+            let ty = rust_to_kotlin(ty, Usage::Fn, context)?;
+            Ok(if ty.name == "Unit" { None } else { Some(ty) })
+        }
+    }
+}
+
+/// Pointers to structs are mapped to the structs, C strings to `String` and anything else to a
+/// raw `Pointer`.
+fn ptr_to_kotlin(ptr: &ast::MutTy, usage: Usage, context: &Context) -> Result<KotlinType, Error> {
+    if let ast::TyKind::Path(None, ref path) = ptr.ty.node {
+        let name = path_name(path);
+        if name == "c_char" && ptr.mutbl == ast::Mutability::Immutable {
+            return Ok(KotlinType::nullable("String"));
+        }
+        if context.structs.contains(&name) {
+            return Ok(match usage {
+                Usage::Fn => KotlinType::nullable(name),
+                Usage::Field => KotlinType::nullable(format!("{}.ByReference", name)),
+            });
+        }
+    }
+
+    Ok(KotlinType::nullable("Pointer"))
+}
+
+fn path_to_kotlin(
+    path: &ast::Path,
+    ty: &ast::Ty,
+    usage: Usage,
+    context: &Context,
+) -> Result<KotlinType, Error> {
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .collect();
+
+    let name = match segments.split_last() {
+        Some((name, [])) => name,
+        Some((name, module)) => match &*module.join("::") {
+            "libc" | "std::os::raw" | "core::ffi" | "std::ffi" => {
+                return context_primitive(name, context).ok_or_else(|| unsupported(ty));
+            }
+            _ => {
+                return Err(Error {
+                    level: Level::Error,
+                    code: Some(ErrorCode::ForeignModuleType),
+                    span: Some(ty.span),
+                    message: "bindgen can not handle types in other modules (yet)".into(),
+                });
+            }
+        },
+        None => return Err(unsupported(ty)),
+    };
+
+    if let Some(primitive) = context_primitive(name, context) {
+        return Ok(primitive);
+    }
+
+    if let Some(&int) = context.enums.get(name) {
+        Ok(primitive_type(int))
+    } else if context.structs.contains(name) {
+        Ok(match usage {
+            Usage::Fn => {
+                KotlinType::new(format!("{}.ByValue", name), format!("{}.ByValue()", name))
+            }
+            Usage::Field => KotlinType::new(name.clone(), format!("{}()", name)),
+        })
+    } else if context.callbacks.contains(name) {
+        Ok(KotlinType::nullable(name.clone()))
+    } else if let Some(alias) = context.aliases.get(name) {
+        let aliased = rust_to_kotlin(alias, usage, context)?;
+        Ok(KotlinType::new(name.clone(), aliased.default))
+    } else {
+        Err(unsupported(ty))
+    }
+}
+
+/// Convert a Rust primitive (or `libc`/`std::os::raw` type) into the Kotlin type JNA maps it to.
+///
+/// The JVM has no unsigned types, so unsigned integers use the signed type of the same width.
+/// `bool` is a `Byte`, since JNA maps `Boolean` to a 4-byte `int`, and `isize`/`usize` are the
+/// pointer-sized `IntPtr`/`UIntPtr` classes generated alongside the bindings.
+pub fn primitive_to_kotlin(ty: &str) -> Option<KotlinType> {
+    let name = match common::primitive_named(ty) {
+        Some(Primitive::Unit) => return None,
+        Some(Primitive::Bool) | Some(Primitive::Int { bits: Some(8), .. }) => "Byte",
+        Some(Primitive::Int { bits: Some(16), .. }) => "Short",
+        Some(Primitive::Int { bits: Some(32), .. }) => "Int",
+        Some(Primitive::Int { bits: None, signed }) => {
+            if signed {
+                "IntPtr"
+            } else {
+                "UIntPtr"
+            }
+        }
+        Some(Primitive::Int { .. }) => "Long",
+        Some(Primitive::Float { bits: 32 }) => "Float",
+        Some(Primitive::Float { .. }) => "Double",
+        None => match ty {
+            "c_char" | "c_schar" | "c_uchar" => "Byte",
+            "c_short" | "c_ushort" => "Short",
+            "c_int" | "c_uint" => "Int",
+            "c_longlong" | "c_ulonglong" => "Long",
+            "ssize_t" => "IntPtr",
+            "size_t" => "UIntPtr",
+            "c_float" => "Float",
+            "c_double" => "Double",
+            "c_long" | "c_ulong" => "NativeLong",
+            _ => return None,
+        },
+    };
+
+    Some(primitive_type(name))
+}
+
+/// Convert a primitive like `primitive_to_kotlin`, recording whether the pointer-sized integer
+/// classes must be generated.
+fn context_primitive(ty: &str, context: &Context) -> Option<KotlinType> {
+    let primitive = primitive_to_kotlin(ty)?;
+    if primitive.name == "IntPtr" || primitive.name == "UIntPtr" {
+        context.pointer_sized.set(true);
+    }
+    Some(primitive)
+}
+
+/// Kotlin type of a primitive, with its zero value.
+fn primitive_type(name: &str) -> KotlinType {
+    let default = match name {
+        "Float" => "0f",
+        "Double" => "0.0",
+        "NativeLong" => "NativeLong()",
+        "IntPtr" => "IntPtr()",
+        "UIntPtr" => "UIntPtr()",
+        _ => "0",
+    };
+    KotlinType::new(name, default)
+}
+
+/// Evaluate the length of an array, which may refer to integer constants declared before it.
+fn array_len(len: &ast::Expr, context: &Context) -> Result<i64, Error> {
+    common::eval_int_with(len, &|path| context.consts.get(&path_name(path)).cloned())
+        .filter(|len| *len >= 0)
+        .ok_or_else(|| Error {
+            level: Level::Error,
+            code: Some(ErrorCode::UnknownArrayLen),
+            span: Some(len.span),
+            message: format!(
+                "unknown array length `{}`, it must be an integer or a `pub` integer constant \
+                 declared before the array",
+                pprust::expr_to_string(len)
+            ),
+        })
+}
+
+/// Returns the last segment of a path, e.g. `c_int` for `libc::c_int`.
+pub fn path_name(path: &ast::Path) -> String {
+    path.segments
+        .last()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .unwrap_or_default()
+}
+
+fn unsupported(ty: &ast::Ty) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::UnsupportedType),
+        span: Some(ty.span),
+        message: format!(
+            "bindgen can not handle the type `{}` in Kotlin",
+            pprust::ty_to_string(ty)
+        ),
+    }
+}
@@ -0,0 +1,313 @@
+//! Functions for generating Objective-C headers.
+
+#[cfg(test)]
+mod tests;
+
+use crate::common::{
+    self, append_output, check_ffi, check_no_mangle, check_repr_c, parse_attr, Lang, Outputs,
+};
+use crate::lang_c::{
+    field_rust_to_c, ret_ty_to_c, retrieve_docstring, rust_to_c, rust_ty_to_c, Context,
+};
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::Error;
+
+/// Generates a single Objective-C header declaring the exported items.
+///
+/// The declarations are the C ones, except that enums are declared with `NS_ENUM` (or
+/// `NS_OPTIONS` when marked with `#[ffi(options)]`) so that they import as proper types, and
+/// `isize`/`usize` are `NSInteger`/`NSUInteger`.
+///
+/// The structs are forward-declared at the top of the header, so that the declarations can use
+/// them in any order.
+pub struct LangObjC {
+    lib_name: String,
+    context: Context,
+    forward_decls: String,
+    code: String,
+}
+
+impl LangObjC {
+    pub fn new() -> Self {
+        // `NSInteger` and `NSUInteger` have the same size as pointers on Apple platforms.
+        let mut context = Context::default();
+        context.set_pointer_sized_types("NSInteger", "NSUInteger");
+
+        LangObjC {
+            lib_name: "backend".to_owned(),
+            context,
+            forward_decls: String::new(),
+            code: String::new(),
+        }
+    }
+
+    /// Set the name of the native library, used to name the generated header.
+    pub fn set_lib_name<T: Into<String>>(&mut self, name: T) {
+        self.lib_name = name.into();
+    }
+}
+
+impl Default for LangObjC {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lang for LangObjC {
+    /// Integer constants are only used for the lengths of arrays.
+    fn parse_const(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        self.context.define_const(item);
+        Ok(())
+    }
+
+    /// Convert `pub type A = B;` into `typedef B A;`.
+    fn parse_ty(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        if let ast::ItemKind::Ty(ref ty, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Ok(());
+            }
+
+            let (_, docs) = parse_attr(&item.attrs, |_| true, |attr| retrieve_docstring(attr, ""));
+            let ty = rust_to_c(ty, &item.ident.name.as_str(), &self.context)?;
+            self.code.push_str(&docs);
+            self.code.push_str(&format!("typedef {};\n\n", ty));
+        }
+
+        Ok(())
+    }
+
+    /// Convert a `#[repr(C)]` enum into an `NS_ENUM`, or an `NS_OPTIONS` for the enums marked
+    /// with `#[ffi(options)]`, with the integer type given in the `repr`.
+    ///
+    /// The constants are prefixed with the name of the enum, e.g. `StatusOk`.
+    fn parse_enum(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, |attr| {
+            retrieve_docstring(attr, "")
+        });
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Enum(ref definition, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_enum_error(item.span));
+            }
+
+            let base = match common::parse_repr(&item.attrs).int {
+                Some(int) => rust_ty_to_c(int, &self.context).to_string(),
+                None => "int".to_string(),
+            };
+            let macro_name = if item.attrs.iter().any(|attr| check_ffi(attr, "options")) {
+                "NS_OPTIONS"
+            } else {
+                "NS_ENUM"
+            };
+
+            let name = item.ident.name.as_str();
+            self.code.push_str(&docs);
+            self.code
+                .push_str(&format!("typedef {}({}, {}) {{\n", macro_name, base, name));
+
+            for variant in &definition.variants {
+                if !variant.node.data.is_unit() {
+                    return Err(common::data_enum_error(variant.span));
+                }
+            }
+            let values = common::discriminants(&item.ident.name.as_str(), definition)?;
+
+            for (variant, value) in definition.variants.iter().zip(values) {
+                let (_, docs) = parse_attr(
+                    &variant.node.attrs,
+                    |_| true,
+                    |attr| retrieve_docstring(attr, "\t"),
+                );
+                self.code.push_str(&docs);
+
+                let constant = format!("{}{}", name, variant.node.name.name.as_str());
+                match variant.node.disr_expr {
+                    Some(ref expr) => self.code.push_str(&format!(
+                        "\t{} = {},\n",
+                        constant,
+                        common::c_like_source(expr).unwrap_or_else(|| value.to_string())
+                    )),
+                    None => self.code.push_str(&format!("\t{},\n", constant)),
+                }
+            }
+
+            self.code.push_str("};\n\n");
+        }
+
+        Ok(())
+    }
+
+    /// Convert a `#[repr(C)]` struct into a C struct, typedef'd with the forward declarations.
+    ///
+    /// Tuple structs with a single field are opaque, so they're only forward-declared.
+    fn parse_struct(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, |attr| {
+            retrieve_docstring(attr, "")
+        });
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Struct(ref variants, ref generics) = item.node {
+            if generics.is_parameterized() {
+                return Err(common::generic_struct_error(item.span));
+            }
+
+            let name = item.ident.name.as_str();
+
+            if variants.is_struct() {
+                self.forward_decls
+                    .push_str(&format!("typedef struct {0} {0};\n", name));
+                self.code.push_str(&docs);
+                self.code.push_str(&format!("struct {} {{\n", name));
+                for field in variants.fields() {
+                    let (_, docs) = parse_attr(
+                        &field.attrs,
+                        |_| true,
+                        |attr| retrieve_docstring(attr, "\t"),
+                    );
+                    self.code.push_str(&docs);
+
+                    let field_name = field
+                        .ident
+                        .map(|ident| ident.name.as_str().to_string())
+                        .unwrap_or_default();
+                    let ty = field_rust_to_c(&field.ty, &field_name, &self.context)?;
+                    self.code.push_str(&format!("\t{};\n", ty));
+                }
+                self.code.push_str("};\n\n");
+            } else if variants.is_tuple() && variants.fields().len() == 1 {
+                self.forward_decls.push_str(&docs);
+                self.forward_decls
+                    .push_str(&format!("typedef struct {0} {0};\n", name));
+            } else {
+                return Err(common::tuple_struct_error(item.span));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Declare an exported static with `FOUNDATION_EXPORT`, `const` unless it's `mut`.
+    fn parse_static(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (no_mangle, docs) = parse_attr(&item.attrs, check_no_mangle, |attr| {
+            retrieve_docstring(attr, "")
+        });
+        if !no_mangle {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Static(ref ty, mutbl, _) = item.node {
+            let name = common::symbol_name(item);
+            let declarator = match mutbl {
+                ast::Mutability::Mutable => name,
+                ast::Mutability::Immutable => format!("const {}", name),
+            };
+            let ty = rust_to_c(ty, &declarator, &self.context)?;
+            self.code.push_str(&docs);
+            self.code
+                .push_str(&format!("FOUNDATION_EXPORT {};\n\n", ty));
+        }
+
+        Ok(())
+    }
+
+    /// Declare an exported function.
+    fn parse_fn(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (no_mangle, docs) = parse_attr(&item.attrs, check_no_mangle, |attr| {
+            retrieve_docstring(attr, "")
+        });
+        if !no_mangle {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Fn(ref fn_decl, _, _, abi, ref generics, _) = item.node {
+            if !common::is_extern(abi) {
+                return Ok(());
+            }
+            if generics.is_parameterized() {
+                return Err(common::generic_fn_error(item.span));
+            }
+
+            let mut args = Vec::new();
+            for arg in &fn_decl.inputs {
+                let name = pprust::pat_to_string(&arg.pat);
+                args.push(rust_to_c(&arg.ty, &name, &self.context)?.to_string());
+            }
+            let call = format!(
+                "{}({})",
+                common::symbol_name(item),
+                if args.is_empty() {
+                    "void".to_string()
+                } else {
+                    args.join(", ")
+                }
+            );
+
+            // Returned function pointers have to wrap the rest of the declaration.
+            let decl = match fn_decl.output {
+                ast::FunctionRetTy::Ty(ref ty) if matches!(ty.node, ast::TyKind::BareFn(..)) => {
+                    rust_to_c(ty, &call, &self.context)?.to_string()
+                }
+                ref output => format!("{} {}", ret_ty_to_c(output, &self.context)?, call),
+            };
+
+            self.code.push_str(&docs);
+            self.code.push_str(&format!("{};\n\n", decl));
+        }
+
+        Ok(())
+    }
+
+    fn finalise_output(&mut self, outputs: &mut Outputs) -> Result<(), Error> {
+        let forward_decls = if self.forward_decls.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", self.forward_decls)
+        };
+        let code = format!(
+            "#import <Foundation/Foundation.h>\n\n\
+             #ifdef __cplusplus\nextern \"C\" {{\n#endif\n\n\
+             {}{}\
+             #ifdef __cplusplus\n}}\n#endif\n",
+            forward_decls, self.code
+        );
+        append_output(code, &format!("{}.h", self.lib_name), outputs);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use super::LangObjC;
+use crate::test_utils::fetch;
+use crate::ErrorCode;
+
+#[test]
+fn header() {
+    let outputs = compile!(LangObjC::new(), {
+        /// Status of an operation.
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            /// Something went wrong.
+            NotFound = 4,
+        }
+
+        #[ffi(options)]
+        #[repr(C, u32)]
+        pub enum Permissions {
+            Read = 1 << 0,
+            Write = 1 << 1,
+        }
+
+        #[repr(C)]
+        pub struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        #[repr(C)]
+        pub struct Buffer {
+            data: *mut u8,
+            len: usize,
+            offsets: [isize; 4],
+            status: Status,
+        }
+
+        #[repr(C)]
+        pub struct Handle(u64);
+
+        pub type Callback = extern "C" fn(user_data: *mut c_void, result: i32);
+
+        #[no_mangle]
+        pub static VERSION: u32 = 1;
+
+        /// Draw a point.
+        #[no_mangle]
+        pub extern "C" fn draw(point: *const Point, size: usize, cb: Callback) -> bool {}
+
+        #[no_mangle]
+        pub extern "C" fn open(handle: *mut *mut Handle) -> isize {}
+
+        pub extern "C" fn not_exported() {}
+    });
+
+    let actual = fetch(&outputs, "backend.h");
+    let expected = indoc!(
+        "#import <Foundation/Foundation.h>
+
+         #ifdef __cplusplus
+         extern \"C\" {
+         #endif
+
+         typedef struct Point Point;
+         typedef struct Buffer Buffer;
+         typedef struct Handle Handle;
+
+         /// Status of an operation.
+         typedef NS_ENUM(int, Status) {
+         \tStatusOk,
+         \t/// Something went wrong.
+         \tStatusNotFound = 4,
+         };
+
+         typedef NS_OPTIONS(uint32_t, Permissions) {
+         \tPermissionsRead = 1 << 0,
+         \tPermissionsWrite = 1 << 1,
+         };
+
+         struct Point {
+         \tdouble x;
+         \tdouble y;
+         };
+
+         struct Buffer {
+         \tuint8_t* data;
+         \tNSUInteger len;
+         \tNSInteger offsets[4];
+         \tStatus status;
+         };
+
+         typedef void (*Callback)(void* user_data, int32_t result);
+
+         FOUNDATION_EXPORT uint32_t const VERSION;
+
+         /// Draw a point.
+         bool draw(Point const* point, NSUInteger size, Callback cb);
+
+         NSInteger open(Handle** handle);
+
+         #ifdef __cplusplus
+         }
+         #endif
+         "
+    );
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn enum_discriminants() {
+    let outputs = compile!(LangObjC::new(), {
+        #[repr(C)]
+        pub enum Flags {
+            Shifted = 1 << 2,
+            Suffixed = 5u32,
+            Implicit,
+            Cast = 0x10 as isize,
+            Inverted = !0,
+            Negative = -(2 + 1),
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.h");
+    assert!(actual.contains(
+        "typedef NS_ENUM(int, Flags) {\n\
+         \tFlagsShifted = 1 << 2,\n\
+         \tFlagsSuffixed = 5,\n\
+         \tFlagsImplicit,\n\
+         \tFlagsCast = 16,\n\
+         \tFlagsInverted = -1,\n\
+         \tFlagsNegative = -(2 + 1),\n\
+         };\n"
+    ));
+
+    let errors = unwrap!(try_compile!(LangObjC::new(), {
+        #[repr(C)]
+        pub enum Mode {
+            Read = MODE_READ,
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnknownDiscriminant));
+}
+
+#[test]
+fn export_names() {
+    let outputs = compile!(LangObjC::new(), {
+        #[no_mangle]
+        #[export_name = "backend_version"]
+        pub static VERSION: u32 = 1;
+
+        #[no_mangle]
+        #[export_name = "backend_reset"]
+        pub extern "C" fn reset() {}
+    });
+
+    let actual = fetch(&outputs, "backend.h");
+    assert!(actual.contains("FOUNDATION_EXPORT uint32_t const backend_version;\n"));
+    assert!(actual.contains("void backend_reset(void);\n"));
+    assert!(!actual.contains("VERSION"));
+}
+
+#[test]
+fn array_fields() {
+    let outputs = compile!(LangObjC::new(), {
+        pub const NAME_LEN: usize = 16;
+
+        #[repr(C)]
+        pub struct Record {
+            name: [c_char; NAME_LEN],
+            matrix: [[f32; 4]; 4],
+            ids: [usize; 2],
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.h");
+    assert!(actual.contains(
+        "struct Record {\n\
+         \tchar name[16];\n\
+         \tfloat matrix[4][4];\n\
+         \tNSUInteger ids[2];\n\
+         };\n"
+    ));
+}
+
+#[test]
+fn forward_declarations() {
+    let outputs = compile!(LangObjC::new(), {
+        #[repr(C)]
+        pub struct Node {
+            next: *mut Node,
+            list: *mut List,
+        }
+
+        #[repr(C)]
+        pub struct List {
+            head: *mut Node,
+            len: usize,
+        }
+
+        pub type Address = uintptr_t;
+    });
+
+    let actual = fetch(&outputs, "backend.h");
+    let forward = unwrap!(actual.find("typedef struct List List;\n"));
+    let node = unwrap!(actual.find("struct Node {\n\tNode* next;\n\tList* list;\n};\n"));
+    assert!(forward < node);
+    assert!(actual.contains("\tNSUInteger len;\n"));
+    // Only the Rust types are mapped, not the C names they're usually mapped to.
+    assert!(actual.contains("typedef uintptr_t Address;\n"));
+}
@@ -75,13 +75,26 @@ extern crate indoc;
 #[macro_use]
 extern crate unwrap;
 
-pub use crate::common::FilterMode;
-use crate::common::{Lang, Outputs};
+// Helpers for backends implemented in other crates.
+pub use crate::common::{
+    append_output, check_no_mangle, check_repr_c, is_extern, parse_attr, primitive,
+    primitive_named, retrieve_docstring, Primitive,
+};
+pub use crate::common::{FilterMode, Lang, LineEnding, Outputs};
 pub use crate::csharp::LangCSharp;
 pub use crate::errors::Level;
 pub use crate::java::LangJava;
-pub use crate::lang_c::LangC;
-use crate::syntax::codemap::{FilePathMapping, Span};
+pub use crate::lang_c::{
+    EmptyParams, EnumTagStyle, HeaderNamer, IdentifierTransform, IncludeStyle, LangC, PackingStyle,
+    TypeResolver,
+};
+pub use crate::lang_d::LangD;
+pub use crate::lang_kotlin::LangKotlin;
+pub use crate::lang_objc::LangObjC;
+pub use crate::parse::walk_crate;
+pub use crate::swift::LangSwift;
+pub use crate::syntax::ast::Crate;
+use crate::syntax::codemap::{CodeMap, FilePathMapping, Span};
 use jni;
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -97,16 +110,22 @@ mod common;
 mod csharp;
 mod java;
 mod lang_c;
+mod lang_d;
+mod lang_kotlin;
+mod lang_objc;
 mod output;
 mod parse;
 mod struct_field;
+mod swift;
 
 /// Describes an error encountered by the compiler.
 ///
 /// These can be printed nicely using the `Cheddar::print_err` method.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Error {
     pub level: Level,
+    /// Stable identifier of the kind of error, if there is one.
+    pub code: Option<ErrorCode>,
     span: Option<Span>,
     pub message: String,
 }
@@ -115,15 +134,165 @@ impl Error {
     pub fn error(message: &str) -> Self {
         Error {
             level: Level::Error,
+            code: None,
             span: None,
             message: message.to_string(),
         }
     }
+
+    /// Resolve the span of the error to a location in the source code, if it has one.
+    pub fn location(&self, codemap: &CodeMap) -> Option<SourceLocation> {
+        let span = self.span?;
+        let loc = codemap.lookup_char_pos(span.lo);
+
+        Some(SourceLocation {
+            file: loc.file.name.to_string(),
+            line: loc.line,
+            column: loc.col.0 + 1,
+            byte_offset: (span.lo - loc.file.start_pos).0 as usize,
+        })
+    }
+}
+
+/// Location in the source code, displayed as `file:line:column`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+    /// Offset in bytes from the start of the file.
+    pub byte_offset: usize,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{}:{}:{}", self.file, self.line, self.column)
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(formatter, "{}: {}", self.level, self.message)
+        match self.code {
+            Some(code) => write!(formatter, "{}[{}]: {}", self.level, code, self.message),
+            None => write!(formatter, "{}: {}", self.level, self.message),
+        }
+    }
+}
+
+/// Stable codes identifying the errors reported by bindgen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// I/O error while reading the input or writing the output.
+    Io,
+    /// The cargo manifest could not be read or parsed.
+    Manifest,
+    /// An item was passed to the wrong parsing function (internal error).
+    WrongItem,
+    /// A type could not be parsed (internal error).
+    InvalidType,
+    /// Generic enums can not be exported.
+    GenericEnum,
+    /// Enums with data-carrying variants can not be exported.
+    DataEnum,
+    /// Generic structs can not be exported.
+    GenericStruct,
+    /// Array field whose length is a generic parameter.
+    GenericArrayLen,
+    /// Unit structs and tuple structs with several fields can not be exported.
+    TupleStruct,
+    /// Generic functions can not be exported.
+    GenericFn,
+    /// Functions can not return `!`.
+    NeverReturn,
+    /// Function pointers need a name or declaration associated with them.
+    AnonymousFnPtr,
+    /// Function pointers with lifetimes can not be exported.
+    Lifetimes,
+    /// The type has no C equivalent.
+    UnsupportedType,
+    /// The atomic type has no C equivalent.
+    UnsupportedAtomic,
+    /// Types from modules other than `libc`, `std::os::raw` and `std::sync::atomic`.
+    ForeignModuleType,
+    /// A source file could not be parsed.
+    Parse,
+    /// Bitfield widths must be non-zero and fit in the integer type of the field.
+    InvalidBitfield,
+    /// Type aliases of the unit type `()` have no C equivalent.
+    UnitAlias,
+    /// A struct field uses a type which is neither declared nor a known primitive.
+    UndeclaredType,
+    /// Generic type aliases are only generated for their instantiations.
+    GenericAlias,
+    /// Tuples can't be returned by value.
+    TupleReturn,
+    /// Pointers to slices have no C equivalent.
+    SlicePointer,
+    /// Pointers and references to `CStr` and `CString` aren't C strings.
+    CStrPointer,
+    /// `Ipv4Addr` and `Ipv6Addr` have no guaranteed layout.
+    IpAddress,
+    /// Only scalar and pointer fields can be volatile.
+    InvalidVolatile,
+    /// Type mappings must be a table of strings.
+    InvalidMapping,
+    /// A generated header was rejected by the C compiler.
+    CompileError,
+    /// A generated header has the name of a standard C header.
+    StdHeaderName,
+    /// Array length referring to a constant which isn't known.
+    UnknownArrayLen,
+    /// Trait objects and closures have no C equivalent.
+    TraitObject,
+    /// Enum discriminant which isn't a constant integer expression.
+    UnknownDiscriminant,
+}
+
+impl ErrorCode {
+    /// Returns the code as a string, e.g. `E_GENERIC_STRUCT`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Io => "E_IO",
+            ErrorCode::Manifest => "E_MANIFEST",
+            ErrorCode::WrongItem => "E_WRONG_ITEM",
+            ErrorCode::InvalidType => "E_INVALID_TYPE",
+            ErrorCode::GenericEnum => "E_GENERIC_ENUM",
+            ErrorCode::DataEnum => "E_DATA_ENUM",
+            ErrorCode::GenericStruct => "E_GENERIC_STRUCT",
+            ErrorCode::GenericArrayLen => "E_GENERIC_ARRAY_LEN",
+            ErrorCode::TupleStruct => "E_TUPLE_STRUCT",
+            ErrorCode::GenericFn => "E_GENERIC_FN",
+            ErrorCode::NeverReturn => "E_NEVER_RETURN",
+            ErrorCode::AnonymousFnPtr => "E_ANONYMOUS_FN_PTR",
+            ErrorCode::Lifetimes => "E_LIFETIMES",
+            ErrorCode::UnsupportedType => "E_UNSUPPORTED_TYPE",
+            ErrorCode::UnsupportedAtomic => "E_UNSUPPORTED_ATOMIC",
+            ErrorCode::ForeignModuleType => "E_FOREIGN_MODULE_TYPE",
+            ErrorCode::Parse => "E_PARSE",
+            ErrorCode::InvalidBitfield => "E_INVALID_BITFIELD",
+            ErrorCode::UnitAlias => "E_UNIT_ALIAS",
+            ErrorCode::UndeclaredType => "E_UNDECLARED_TYPE",
+            ErrorCode::GenericAlias => "E_GENERIC_ALIAS",
+            ErrorCode::TupleReturn => "E_TUPLE_RETURN",
+            ErrorCode::SlicePointer => "E_SLICE_POINTER",
+            ErrorCode::CStrPointer => "E_CSTR_POINTER",
+            ErrorCode::IpAddress => "E_IP_ADDRESS",
+            ErrorCode::InvalidVolatile => "E_INVALID_VOLATILE",
+            ErrorCode::InvalidMapping => "E_INVALID_MAPPING",
+            ErrorCode::CompileError => "E_COMPILE_ERROR",
+            ErrorCode::StdHeaderName => "E_STD_HEADER_NAME",
+            ErrorCode::UnknownArrayLen => "E_UNKNOWN_ARRAY_LEN",
+            ErrorCode::TraitObject => "E_TRAIT_OBJECT",
+            ErrorCode::UnknownDiscriminant => "E_UNKNOWN_DISCRIMINANT",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str(self.as_str())
     }
 }
 
@@ -144,6 +313,7 @@ impl From<IoError> for Error {
     fn from(e: IoError) -> Self {
         Error {
             level: Level::Fatal,
+            code: Some(ErrorCode::Io),
             span: None,
             message: format!("I/O Error: {}", e),
         }
@@ -162,50 +332,54 @@ impl Error {
     fn print(&self, sess: &syntax::parse::ParseSess) {
         // TODO: there must be some way to reduce the amount of code here.
         // Throw away the results (with { ...; }) since they are handled elsewhere.
+        let message = match self.code {
+            Some(code) => format!("[{}] {}", code, self.message),
+            None => self.message.clone(),
+        };
+
         if let Some(span) = self.span {
             match self.level {
                 Level::Bug => {
-                    sess.span_diagnostic.span_bug(span, &self.message);
+                    sess.span_diagnostic.span_bug(span, &message);
                 }
                 Level::Fatal => {
-                    sess.span_diagnostic.span_fatal(span, &self.message);
+                    sess.span_diagnostic.span_fatal(span, &message);
                 }
                 Level::Error => {
-                    sess.span_diagnostic.span_err(span, &self.message);
+                    sess.span_diagnostic.span_err(span, &message);
                 }
                 Level::Warning => {
-                    sess.span_diagnostic.span_warn(span, &self.message);
+                    sess.span_diagnostic.span_warn(span, &message);
                 }
                 Level::Note => {
-                    sess.span_diagnostic
-                        .span_note_without_error(span, &self.message);
+                    sess.span_diagnostic.span_note_without_error(span, &message);
                 }
                 Level::Help => {
                     sess.span_diagnostic
                         .struct_dummy()
-                        .span_help(span, &self.message);
+                        .span_help(span, &message);
                 }
                 _ => unreachable!(),
             };
         } else {
             match self.level {
                 Level::Bug => {
-                    sess.span_diagnostic.bug(&self.message);
+                    sess.span_diagnostic.bug(&message);
                 }
                 Level::Fatal => {
-                    sess.span_diagnostic.fatal(&self.message);
+                    sess.span_diagnostic.fatal(&message);
                 }
                 Level::Error => {
-                    sess.span_diagnostic.err(&self.message);
+                    sess.span_diagnostic.err(&message);
                 }
                 Level::Warning => {
-                    sess.span_diagnostic.warn(&self.message);
+                    sess.span_diagnostic.warn(&message);
                 }
                 Level::Note => {
-                    sess.span_diagnostic.note_without_error(&self.message);
+                    sess.span_diagnostic.note_without_error(&message);
                 }
                 Level::Help => {
-                    sess.span_diagnostic.struct_dummy().help(&self.message);
+                    sess.span_diagnostic.struct_dummy().help(&message);
                 }
                 _ => unreachable!(),
             };
@@ -298,7 +472,7 @@ impl Bindgen {
     /// This does not add any include-guards, includes, or extern declarations. It is mainly
     /// intended for internal use, but may be of interest to people who wish to embed
     /// moz-cheddar's generated code in another file.
-    pub fn compile<L: Lang>(
+    pub fn compile<L: Lang + ?Sized>(
         &mut self,
         lang: &mut L,
         outputs: &mut Outputs,
@@ -318,21 +492,68 @@ impl Bindgen {
         Ok(())
     }
 
-    fn compile_from_path<L: Lang>(
+    /// Parse the root source file of a crate, to be compiled with `compile_crates`.
+    pub fn parse_crate<P: AsRef<Path>>(&self, path: P) -> Result<Crate, Error> {
+        let path = path.as_ref();
+        syntax::parse::parse_crate_from_file(path, &self.session).map_err(|mut diagnostic| {
+            diagnostic.emit();
+            Error {
+                level: Level::Fatal,
+                code: Some(ErrorCode::Parse),
+                span: None,
+                message: format!("could not parse {}", path.display()),
+            }
+        })
+    }
+
+    /// Compile several crates (e.g. the members of a workspace) into a single set of outputs.
+    ///
+    /// The crates must have been parsed with `parse_crate`. They share the state of `lang`, so
+    /// types declared in one crate can be used by the others and the dependencies between the
+    /// headers span all of them.
+    pub fn compile_crates<L: Lang + ?Sized>(
+        &self,
+        lang: &mut L,
+        outputs: &mut Outputs,
+        crates: &[Crate],
+        finalise: bool,
+    ) -> Result<(), Vec<Error>> {
+        for krate in crates {
+            let path = PathBuf::from(self.session.codemap().span_to_filename(krate.span));
+            self.compile_crate(lang, outputs, krate, &path)?;
+        }
+        if finalise {
+            lang.finalise_output(outputs)?;
+        }
+        Ok(())
+    }
+
+    fn compile_from_path<L: Lang + ?Sized>(
         &self,
         lang: &mut L,
         outputs: &mut Outputs,
         path: &PathBuf,
+    ) -> Result<(), Vec<Error>> {
+        // Parse the top level mod.
+        let krate = unwrap!(syntax::parse::parse_crate_from_file(path, &self.session));
+        self.compile_crate(lang, outputs, &krate, path)
+    }
+
+    /// Compile a crate parsed from `path`, along with the FFI modules it imports.
+    fn compile_crate<L: Lang + ?Sized>(
+        &self,
+        lang: &mut L,
+        outputs: &mut Outputs,
+        krate: &Crate,
+        path: &Path,
     ) -> Result<(), Vec<Error>> {
         let base_path = unwrap!(path.parent());
         let mod_path = unwrap!(path.to_str()).to_string();
 
-        // Parse the top level mod.
-        let krate = unwrap!(syntax::parse::parse_crate_from_file(path, &self.session));
         let module = convert_lib_path_to_module(&PathBuf::from(mod_path.clone()));
         eprintln!("Parsing {} ({:?})", module.join("::"), mod_path);
 
-        parse::parse_mod(lang, &krate.module, &module, outputs)?;
+        walk_crate(krate, &module, lang, outputs)?;
 
         // Parse other mods.
         let modules = parse::imported_mods(&krate.module);
@@ -355,12 +576,12 @@ impl Bindgen {
                 &mod_path,
                 &self.session
             ));
-            parse::parse_mod(lang, &krate.module, &module, outputs)?;
+            walk_crate(&krate, &module, lang, outputs)?;
         }
         Ok(())
     }
 
-    fn compile_from_source<L: Lang>(
+    fn compile_from_source<L: Lang + ?Sized>(
         &self,
         lang: &mut L,
         outputs: &mut Outputs,
@@ -377,11 +598,11 @@ impl Bindgen {
 
         eprintln!("Parsing {} (from string)", module.join("::"));
 
-        parse::parse_mod(lang, &krate.module, &module, outputs)?;
+        walk_crate(&krate, &module, lang, outputs)?;
         Ok(())
     }
 
-    pub fn compile_or_panic<L: Lang>(
+    pub fn compile_or_panic<L: Lang + ?Sized>(
         &mut self,
         lang: &mut L,
         outputs: &mut Outputs,
@@ -401,18 +622,36 @@ impl Bindgen {
         let root = root.as_ref();
 
         for (path, contents) in outputs {
-            let full_path = root.join(PathBuf::from(path));
+            write_file(&root.join(PathBuf::from(path)), contents)?;
+        }
 
-            if let Some(parent_dirs) = full_path.parent() {
-                fs::create_dir_all(parent_dirs)?;
+        Ok(())
+    }
+
+    /// Writes virtual files to the file system, skipping the files which already have the same
+    /// contents so that their modification times don't trigger rebuilds.
+    ///
+    /// Returns the paths of the files which were written.
+    pub fn write_outputs_if_changed<P: AsRef<Path>>(
+        &self,
+        root: P,
+        outputs: &Outputs,
+    ) -> Result<Vec<PathBuf>, IoError> {
+        let root = root.as_ref();
+        let mut written = Vec::new();
+
+        for (path, contents) in outputs {
+            let full_path = root.join(PathBuf::from(path));
+            match fs::read(&full_path) {
+                Ok(ref existing) if existing == contents.as_bytes() => continue,
+                _ => (),
             }
 
-            let mut f = fs::File::create(full_path)?;
-            f.write_all(contents.as_bytes())?;
-            f.sync_all()?;
+            write_file(&full_path, contents)?;
+            written.push(full_path);
         }
 
-        Ok(())
+        Ok(written)
     }
 
     pub fn write_outputs_or_panic<P: AsRef<Path>>(&self, root: P, outputs: &Outputs) {
@@ -430,16 +669,46 @@ impl Bindgen {
     /// # Panics
     ///
     /// Panics on any compilation error so that the build script exits and prints output.
-    pub fn run_build<P: AsRef<Path>, L: Lang>(&mut self, lang: &mut L, output_dir: P) {
+    ///
+    /// Only the headers which changed are rewritten, so that unchanged ones don't trigger
+    /// rebuilds.
+    pub fn run_build<P: AsRef<Path>, L: Lang + ?Sized>(&mut self, lang: &mut L, output_dir: P) {
         let mut outputs = HashMap::new();
         self.compile_or_panic(lang, &mut outputs, true);
-        self.write_outputs_or_panic(output_dir, &outputs);
+        if let Err(err) = self.write_outputs_if_changed(output_dir, &outputs) {
+            self.print_error(&From::from(err));
+            panic!("Failed to write output.");
+        }
     }
 
     /// Print an error using the ParseSess stored in Cheddar.
     pub fn print_error(&self, error: &Error) {
         error.print(&self.session);
     }
+
+    /// Resolve the location of an error in the compiled source code.
+    pub fn error_location(&self, error: &Error) -> Option<SourceLocation> {
+        error.location(self.session.codemap())
+    }
+
+    /// Format an error prefixed with its location, e.g. `src/lib.rs:3:5: error: ...`.
+    pub fn format_error(&self, error: &Error) -> String {
+        match self.error_location(error) {
+            Some(location) => format!("{}: {}", location, error),
+            None => error.to_string(),
+        }
+    }
+}
+
+/// Write a file, creating its parent directories if needed.
+pub(crate) fn write_file(path: &Path, contents: &str) -> Result<(), IoError> {
+    if let Some(parent_dirs) = path.parent() {
+        fs::create_dir_all(parent_dirs)?;
+    }
+
+    let mut f = fs::File::create(path)?;
+    f.write_all(contents.as_bytes())?;
+    f.sync_all()
 }
 
 /// Convert a path into a top-level module name (e.g. `ffi_utils/src/lib.rs` -> `ffi_libs`)
@@ -482,6 +751,7 @@ fn source_file_from_cargo() -> Result<String, Error> {
         Err(..) => {
             return Err(Error {
                 level: Level::Fatal,
+                code: Some(ErrorCode::Manifest),
                 span: None,
                 message: "could not read cargo manifest".into(),
             })
@@ -493,6 +763,7 @@ fn source_file_from_cargo() -> Result<String, Error> {
         Err(..) => {
             return Err(Error {
                 level: Level::Fatal,
+                code: Some(ErrorCode::Manifest),
                 span: None,
                 message: "could not parse cargo manifest".into(),
             })
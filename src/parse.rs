@@ -33,11 +33,62 @@ pub fn imported_mods(module: &ast::Mod) -> Vec<Vec<String>> {
     imported
 }
 
+/// Walk through a whole crate, including its public inline modules, and dispatch every item to
+/// the target language.
+///
+/// `module_path` is the path of the crate root; items in submodules get the submodule names
+/// appended to it.
+pub fn walk_crate<L: Lang + ?Sized>(
+    krate: &ast::Crate,
+    module_path: &[String],
+    lang: &mut L,
+    outputs: &mut Outputs,
+) -> Result<(), Vec<Error>> {
+    walk_mod(lang, &krate.module, module_path, outputs)
+}
+
+/// Parse a module and recurse into its public inline submodules.
+fn walk_mod<L: Lang + ?Sized>(
+    lang: &mut L,
+    module: &ast::Mod,
+    module_path: &[String],
+    outputs: &mut Outputs,
+) -> Result<(), Vec<Error>> {
+    let mut errors = match parse_mod(lang, module, module_path, outputs) {
+        Ok(()) => vec![],
+        Err(errors) => errors,
+    };
+
+    for item in &module.items {
+        if let ast::Visibility::Public = item.vis {
+            if let ast::ItemKind::Mod(ref submodule) = item.node {
+                // Modules declared in other files (`mod foo;`) are handled by the caller.
+                if !item.span.contains(submodule.inner) {
+                    continue;
+                }
+
+                let mut path = module_path.to_vec();
+                path.push(item.ident.name.as_str().to_string());
+
+                if let Err(errs) = walk_mod(lang, submodule, &path, outputs) {
+                    errors.extend(errs);
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /// The manager of bindgen and entry point when the crate is the module.
 ///
 /// Iterates through all items in the module and dispatches to correct methods, then pulls all
 /// the results together into a header.
-pub fn parse_mod<L: Lang>(
+pub fn parse_mod<L: Lang + ?Sized>(
     lang: &mut L,
     module: &ast::Mod,
     module_path: &[String],
@@ -46,6 +97,21 @@ pub fn parse_mod<L: Lang>(
     let mut errors = vec![];
 
     for item in &module.items {
+        // `extern` blocks have no visibility of their own, only their items do.
+        if let ast::ItemKind::ForeignMod(ref foreign_mod) = item.node {
+            for foreign_item in &foreign_mod.items {
+                let is_fn = matches!(foreign_item.node, ast::ForeignItemKind::Fn(..));
+                if is_fn && foreign_item.vis == ast::Visibility::Public {
+                    if let Err(error) =
+                        lang.parse_foreign_fn(foreign_item, foreign_mod.abi, module_path, outputs)
+                    {
+                        errors.push(error);
+                    }
+                }
+            }
+            continue;
+        }
+
         // If it's not visible it can't be called from C.
         if let ast::Visibility::Inherited = item.vis {
             continue;
@@ -61,6 +127,7 @@ pub fn parse_mod<L: Lang>(
             ast::ItemKind::Enum(..) => lang.parse_enum(item, module_path, outputs),
             ast::ItemKind::Struct(..) => lang.parse_struct(item, module_path, outputs),
             ast::ItemKind::Fn(..) => lang.parse_fn(item, module_path, outputs),
+            ast::ItemKind::Static(..) => lang.parse_static(item, module_path, outputs),
             _ => Ok(()),
         };
 
@@ -0,0 +1,412 @@
+//! Functions for generating Swift wrappers around the C headers.
+
+#[cfg(test)]
+mod tests;
+mod types;
+
+use self::types::{primitive_to_swift, rust_to_swift, SwiftType};
+use crate::common::{
+    self, append_output, check_no_mangle, check_repr_c, indent, parse_attr,
+    retrieve_rust_style_docstring, Lang, Outputs,
+};
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::syntax::ptr::P;
+use crate::Error;
+use inflector::Inflector;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+pub struct LangSwift {
+    lib_name: String,
+    context: Context,
+    enums: Vec<Enum>,
+    structs: Vec<Struct>,
+    functions: Vec<Function>,
+}
+
+pub struct Context {
+    /// Name of the Swift module the C header is imported as.
+    c_module: String,
+    /// Names of the enums which get a Swift mirror.
+    enums: HashSet<String>,
+    /// Names of the structs which get a Swift mirror.
+    structs: HashSet<String>,
+}
+
+struct Enum {
+    docs: String,
+    name: String,
+    raw_type: &'static str,
+    variants: Vec<(String, String, i64)>,
+}
+
+struct Struct {
+    docs: String,
+    name: String,
+    fields: Vec<(String, P<ast::Ty>)>,
+}
+
+struct Function {
+    docs: String,
+    name: String,
+    /// Name of the C function, which differs from `name` if it's renamed with `#[export_name]`.
+    symbol: String,
+    args: Vec<(String, P<ast::Ty>)>,
+    output: Option<P<ast::Ty>>,
+}
+
+impl LangSwift {
+    pub fn new() -> Self {
+        LangSwift {
+            lib_name: "backend".to_owned(),
+            context: Context {
+                c_module: "Backend".to_owned(),
+                enums: Default::default(),
+                structs: Default::default(),
+            },
+            enums: Vec::new(),
+            structs: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+
+    /// Set the name of the native library, used to name the generated Swift file.
+    pub fn set_lib_name<T: Into<String>>(&mut self, name: T) {
+        self.lib_name = name.into();
+    }
+
+    /// Set the name of the Swift module the generated C header is imported as (`Backend` by
+    /// default).
+    pub fn set_c_module<T: Into<String>>(&mut self, name: T) {
+        self.context.c_module = name.into();
+    }
+
+    fn emit_enum(&self, item: &Enum, output: &mut String) {
+        let c_type = format!("{}.{}", self.context.c_module, item.name);
+
+        output.push_str(&item.docs);
+        unwrap!(writeln!(
+            output,
+            "public enum {}: {} {{",
+            item.name, item.raw_type
+        ));
+        // Like in Rust, raw values are only needed when they don't follow the previous one.
+        let mut next = Some(0);
+        for &(ref docs, ref name, value) in &item.variants {
+            output.push_str(&indent(docs));
+            if next == Some(value) {
+                unwrap!(writeln!(output, "    case {}", name));
+            } else {
+                unwrap!(writeln!(output, "    case {} = {}", name, value));
+            }
+            next = value.checked_add(1);
+        }
+        unwrap!(write!(
+            output,
+            "\n    public init?(_ c: {0}) {{\n        \
+             self.init(rawValue: c.rawValue)\n    \
+             }}\n\n    \
+             public var c: {0} {{\n        \
+             return {0}(rawValue: rawValue)\n    \
+             }}\n}}\n\n",
+            c_type
+        ));
+    }
+
+    fn emit_struct(&self, item: &Struct, output: &mut String) -> Result<(), Error> {
+        let c_type = format!("{}.{}", self.context.c_module, item.name);
+
+        let mut fields = Vec::new();
+        for (name, ty) in &item.fields {
+            fields.push((member_name(name), name, rust_to_swift(ty, &self.context)?));
+        }
+
+        output.push_str(&item.docs);
+        unwrap!(writeln!(output, "public struct {} {{", item.name));
+        for (name, _, ty) in &fields {
+            unwrap!(writeln!(output, "    public var {}: {}", name, ty.name()));
+        }
+
+        // Memberwise initialiser, which isn't public by default.
+        let params: Vec<_> = fields
+            .iter()
+            .map(|(name, _, ty)| format!("{}: {}", name, ty.name()))
+            .collect();
+        unwrap!(writeln!(
+            output,
+            "\n    public init({}) {{",
+            params.join(", ")
+        ));
+        for (name, _, _) in &fields {
+            unwrap!(writeln!(output, "        self.{0} = {0}", name));
+        }
+        output.push_str("    }\n");
+
+        // Like the enum mirrors, the conversion fails if a field can't be converted.
+        unwrap!(writeln!(output, "\n    public init?(_ c: {}) {{", c_type));
+        for &(ref name, c_name, ref ty) in &fields {
+            let value = ty.convert_from_c(&format!("c.{}", c_name));
+            if ty.is_fallible() {
+                unwrap!(writeln!(
+                    output,
+                    "        guard let {0} = {1} else {{\n            return nil\n        }}\n        \
+                     self.{0} = {0}",
+                    name, value
+                ));
+            } else {
+                unwrap!(writeln!(output, "        self.{} = {}", name, value));
+            }
+        }
+        output.push_str("    }\n");
+
+        let args: Vec<_> = fields
+            .iter()
+            .map(|&(ref name, c_name, ref ty)| format!("{}: {}", c_name, ty.convert_to_c(name)))
+            .collect();
+        unwrap!(write!(
+            output,
+            "\n    public var c: {} {{\n        return {}({})\n    }}\n}}\n\n",
+            c_type,
+            c_type,
+            args.join(", ")
+        ));
+
+        Ok(())
+    }
+
+    fn emit_function(&self, item: &Function, output: &mut String) -> Result<(), Error> {
+        let mut params = Vec::new();
+        let mut args = Vec::new();
+        for (name, ty) in &item.args {
+            let ty = rust_to_swift(ty, &self.context)?;
+            let name = member_name(name);
+            params.push(format!("_ {}: {}", name, ty.name()));
+            args.push(ty.convert_to_c(&name));
+        }
+
+        let call = format!(
+            "{}.{}({})",
+            self.context.c_module,
+            item.symbol,
+            args.join(", ")
+        );
+
+        output.push_str(&item.docs);
+        match item.output {
+            Some(ref ty) => {
+                let ty = rust_to_swift(ty, &self.context)?;
+                unwrap!(write!(
+                    output,
+                    "public func {}({}) -> {}{} {{\n    return {}\n}}\n\n",
+                    item.name.to_camel_case(),
+                    params.join(", "),
+                    ty.name(),
+                    if ty.is_fallible() { "?" } else { "" },
+                    ty.convert_from_c(&call)
+                ));
+            }
+            None => unwrap!(write!(
+                output,
+                "public func {}({}) {{\n    {}\n}}\n\n",
+                item.name.to_camel_case(),
+                params.join(", "),
+                call
+            )),
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for LangSwift {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lang for LangSwift {
+    /// Mirror a `#[repr(C)]` enum with a Swift enum.
+    fn parse_enum(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, retrieve_rust_style_docstring);
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Enum(ref definition, ref generics) = item.node {
+            if generics.is_parameterized() || definition.variants.is_empty() {
+                return Ok(());
+            }
+
+            let mut variants = Vec::new();
+            for variant in &definition.variants {
+                if !variant.node.data.is_unit() {
+                    return Err(common::data_enum_error(variant.span));
+                }
+            }
+            let values = common::discriminants(&item.ident.name.as_str(), definition)?;
+
+            for (variant, value) in definition.variants.iter().zip(values) {
+                let (_, docs) =
+                    parse_attr(&variant.node.attrs, |_| true, retrieve_rust_style_docstring);
+                let name = member_name(&variant.node.name.name.as_str());
+                variants.push((docs, name, value));
+            }
+
+            // C enums are imported with an unsigned raw value unless some values are negative.
+            let raw_type = match common::parse_repr(&item.attrs).int {
+                Some(int) => match primitive_to_swift(int) {
+                    Some(SwiftType::Native(raw_type)) => raw_type,
+                    _ => return Ok(()),
+                },
+                None if variants.iter().any(|&(_, _, value)| value < 0) => "Int32",
+                None => "UInt32",
+            };
+
+            let name = item.ident.name.as_str().to_string();
+            let _ = self.context.enums.insert(name.clone());
+            self.enums.push(Enum {
+                docs,
+                name,
+                raw_type,
+                variants,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Mirror a `#[repr(C)]` struct with a Swift struct.
+    ///
+    /// Opaque (unit or tuple) structs are only used behind pointers, so they aren't mirrored.
+    fn parse_struct(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (repr_c, docs) = parse_attr(&item.attrs, check_repr_c, retrieve_rust_style_docstring);
+        if !repr_c {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Struct(ref variants, ref generics) = item.node {
+            if generics.is_parameterized() || !variants.is_struct() {
+                return Ok(());
+            }
+
+            let fields = variants
+                .fields()
+                .iter()
+                .filter_map(|field| {
+                    field
+                        .ident
+                        .map(|ident| (ident.name.as_str().to_string(), field.ty.clone()))
+                })
+                .collect();
+
+            let name = item.ident.name.as_str().to_string();
+            let _ = self.context.structs.insert(name.clone());
+            self.structs.push(Struct { docs, name, fields });
+        }
+
+        Ok(())
+    }
+
+    /// Wrap an exported function with a Swift function.
+    fn parse_fn(
+        &mut self,
+        item: &ast::Item,
+        _module: &[String],
+        _outputs: &mut Outputs,
+    ) -> Result<(), Error> {
+        let (no_mangle, docs) =
+            parse_attr(&item.attrs, check_no_mangle, retrieve_rust_style_docstring);
+        if !no_mangle {
+            return Ok(());
+        }
+
+        if let ast::ItemKind::Fn(ref fn_decl, _, _, abi, ref generics, _) = item.node {
+            if !common::is_extern(abi) || generics.is_parameterized() {
+                return Ok(());
+            }
+
+            let args = fn_decl
+                .inputs
+                .iter()
+                .enumerate()
+                .map(|(index, arg)| (arg_name(&arg.pat, index), arg.ty.clone()))
+                .collect();
+            let output = match fn_decl.output {
+                ast::FunctionRetTy::Ty(ref ty) if ty.node == ast::TyKind::Never => {
+                    return Ok(());
+                }
+                ast::FunctionRetTy::Ty(ref ty) if pprust::ty_to_string(ty) != "()" => {
+                    Some(ty.clone())
+                }
+                _ => None,
+            };
+
+            self.functions.push(Function {
+                docs,
+                name: item.ident.name.as_str().to_string(),
+                symbol: common::symbol_name(item),
+                args,
+                output,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn finalise_output(&mut self, outputs: &mut Outputs) -> Result<(), Error> {
+        let mut output = format!("import {}\n\n", self.context.c_module);
+
+        for item in &self.enums {
+            self.emit_enum(item, &mut output);
+        }
+        for item in &self.structs {
+            self.emit_struct(item, &mut output)?;
+        }
+        for item in &self.functions {
+            self.emit_function(item, &mut output)?;
+        }
+
+        append_output(output, &format!("{}.swift", self.lib_name), outputs);
+
+        Ok(())
+    }
+}
+
+/// Name of a parameter: the identifier of patterns like `mut name`, and `argN` for the others
+/// (e.g. `_`).
+fn arg_name(pat: &ast::Pat, index: usize) -> String {
+    match pat.node {
+        ast::PatKind::Ident(_, ident, None) => ident.node.name.as_str().to_string(),
+        _ => format!("arg{}", index),
+    }
+}
+
+/// Swift name of an enum case, struct field or parameter, in camel case.
+///
+/// `c` is renamed to `c_` as it would clash with the `c` property of the mirrors, and keywords are
+/// quoted with backticks.
+fn member_name(name: &str) -> String {
+    let name = name.to_camel_case();
+    match &*name {
+        "c" => "c_".to_string(),
+        "associatedtype" | "break" | "case" | "catch" | "class" | "continue" | "default"
+        | "defer" | "deinit" | "do" | "else" | "enum" | "extension" | "fallthrough" | "false"
+        | "fileprivate" | "for" | "func" | "guard" | "if" | "import" | "in" | "init" | "inout"
+        | "internal" | "is" | "let" | "nil" | "operator" | "private" | "protocol" | "public"
+        | "repeat" | "rethrows" | "return" | "self" | "static" | "struct" | "subscript"
+        | "super" | "switch" | "throw" | "throws" | "true" | "try" | "typealias" | "var"
+        | "where" | "while" => format!("`{}`", name),
+        _ => name,
+    }
+}
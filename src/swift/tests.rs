@@ -0,0 +1,239 @@
+use super::LangSwift;
+use crate::test_utils::fetch;
+use crate::ErrorCode;
+
+#[test]
+fn enums() {
+    let outputs = compile!(LangSwift::new(), {
+        /// Status of an operation.
+        #[repr(C)]
+        pub enum Status {
+            Ok,
+            /// Something went wrong.
+            NotFound = 4,
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.swift");
+    let expected = indoc!(
+        "import Backend
+
+         /// Status of an operation.
+         public enum Status: UInt32 {
+             case ok
+             /// Something went wrong.
+             case notFound = 4
+
+             public init?(_ c: Backend.Status) {
+                 self.init(rawValue: c.rawValue)
+             }
+
+             public var c: Backend.Status {
+                 return Backend.Status(rawValue: rawValue)
+             }
+         }
+
+         "
+    );
+    assert_multiline_eq!(actual, expected);
+}
+
+#[test]
+fn structs() {
+    let outputs = compile!(LangSwift::new(), {
+        #[repr(C)]
+        pub enum Kind {
+            Circle,
+        }
+
+        #[repr(C)]
+        pub struct Shape {
+            kind: Kind,
+            num_sides: u32,
+            name: *const c_char,
+        }
+
+        #[repr(C)]
+        pub struct Handle(u64);
+    });
+
+    let actual = fetch(&outputs, "backend.swift");
+    let expected = indoc!(
+        "public struct Shape {
+             public var kind: Kind
+             public var numSides: UInt32
+             public var name: UnsafePointer<CChar>?
+
+             public init(kind: Kind, numSides: UInt32, name: UnsafePointer<CChar>?) {
+                 self.kind = kind
+                 self.numSides = numSides
+                 self.name = name
+             }
+
+             public init?(_ c: Backend.Shape) {
+                 guard let kind = Kind(c.kind) else {
+                     return nil
+                 }
+                 self.kind = kind
+                 self.numSides = c.num_sides
+                 self.name = c.name
+             }
+
+             public var c: Backend.Shape {
+                 return Backend.Shape(kind: kind.c, num_sides: numSides, name: name)
+             }
+         }
+
+         "
+    );
+    assert!(actual.ends_with(expected), "{}", actual);
+    assert!(!actual.contains("Handle"));
+}
+
+#[test]
+fn functions() {
+    let mut lang = LangSwift::new();
+    lang.set_lib_name("safe_app");
+    lang.set_c_module("SafeApp");
+
+    let outputs = compile!(lang, {
+        #[repr(C)]
+        pub struct Point {
+            x: i32,
+        }
+
+        /// Move a point.
+        #[no_mangle]
+        pub extern "C" fn point_move(point: Point, user_data: *mut c_void) -> Point {}
+
+        #[no_mangle]
+        pub extern "C" fn app_free(
+            app: *mut App,
+            cb: extern "C" fn(user_data: *mut c_void, result: *const FfiResult),
+        ) {
+        }
+
+        #[no_mangle]
+        pub extern "C" fn app_points(
+            mut points: *mut *const Point,
+            _: *const i32,
+            handles: *mut *mut App,
+        ) {
+        }
+    });
+
+    let actual = fetch(&outputs, "safe_app.swift");
+    assert!(actual.starts_with("import SafeApp\n"));
+    assert!(actual.contains(
+        "/// Move a point.\n\
+         public func pointMove(_ point: Point, _ userData: UnsafeMutableRawPointer?) -> Point? {\n    \
+         return Point(SafeApp.point_move(point.c, userData))\n}\n"
+    ));
+    assert!(actual.contains(
+        "public func appFree(_ app: OpaquePointer?, \
+         _ cb: @convention(c) (UnsafeMutableRawPointer?, OpaquePointer?) \
+         -> Void) {\n    SafeApp.app_free(app, cb)\n}\n"
+    ));
+    assert!(actual.contains(
+        "public func appPoints(_ points: UnsafeMutablePointer<UnsafePointer<SafeApp.Point>?>?, \
+         _ arg1: UnsafePointer<Int32>?, _ handles: UnsafeMutablePointer<OpaquePointer?>?) {\n    \
+         SafeApp.app_points(points, arg1, handles)\n}\n"
+    ));
+}
+
+#[test]
+fn enum_discriminants() {
+    let outputs = compile!(LangSwift::new(), {
+        #[repr(C)]
+        pub enum Flags {
+            A = 1 << 2,
+            B = 5u32,
+            C,
+            Default = (0x10 | 1) as isize,
+            Masked = !0xFF00u32 as u8 as isize,
+        }
+
+        #[repr(C)]
+        pub enum Sign {
+            Negative = -1,
+            Zero,
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.swift");
+    assert!(actual.contains(
+        "public enum Flags: UInt32 {\n    \
+         case a = 4\n    \
+         case b\n    \
+         case c_\n    \
+         case `default` = 17\n    \
+         case masked = 255\n"
+    ));
+    assert!(actual.contains("public enum Sign: Int32 {\n    case negative = -1\n    case zero\n"));
+
+    let errors = unwrap!(try_compile!(LangSwift::new(), {
+        #[repr(C)]
+        pub enum Mode {
+            Read = MODE_READ,
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnknownDiscriminant));
+    assert!(errors[0].message.contains("`MODE_READ`"));
+}
+
+#[test]
+fn member_names() {
+    let outputs = compile!(LangSwift::new(), {
+        #[repr(C)]
+        pub struct Range {
+            c: u8,
+            repeat: bool,
+        }
+
+        #[no_mangle]
+        #[export_name = "backend_retry"]
+        pub extern "C" fn retry(default: i32) {}
+    });
+
+    let actual = fetch(&outputs, "backend.swift");
+    assert!(actual.contains("    public var c_: UInt8\n    public var `repeat`: Bool\n"));
+    assert!(actual.contains("        return Backend.Range(c: c_, repeat: `repeat`)\n"));
+    assert!(actual.contains(
+        "public func retry(_ `default`: Int32) {\n    Backend.backend_retry(`default`)\n}\n"
+    ));
+}
+
+#[test]
+fn array_fields() {
+    let outputs = compile!(LangSwift::new(), {
+        #[repr(C)]
+        pub struct Key {
+            bytes: [u8; 4],
+            grid: [[i16; 2]; 2],
+        }
+    });
+
+    let actual = fetch(&outputs, "backend.swift");
+    assert!(actual.contains(
+        "    public var bytes: (UInt8, UInt8, UInt8, UInt8)\n    \
+         public var grid: ((Int16, Int16), (Int16, Int16))\n"
+    ));
+    assert!(actual.contains("        self.bytes = c.bytes\n"));
+    assert!(actual.contains("        return Backend.Key(bytes: bytes, grid: grid)\n"));
+}
+
+#[test]
+fn unsupported_integers() {
+    let errors = unwrap!(try_compile!(LangSwift::new(), {
+        #[repr(C)]
+        pub struct Wide {
+            value: i128,
+        }
+    })
+    .err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, Some(ErrorCode::UnsupportedType));
+    assert!(errors[0].message.contains("`i128`"));
+}
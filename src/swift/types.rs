@@ -0,0 +1,219 @@
+//! Conversion of Rust types into Swift types.
+
+use super::Context;
+use crate::common::{self, Primitive};
+use crate::syntax::ast;
+use crate::syntax::print::pprust;
+use crate::{Error, ErrorCode, Level};
+
+/// Swift type used in the generated wrappers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwiftType {
+    Void,
+    /// Primitive type, which is the same in Swift and in the imported C declarations.
+    Native(&'static str),
+    /// Swift enum mirroring a C enum.
+    Enum(String),
+    /// Swift struct mirroring a C struct.
+    Struct(String),
+    /// Type imported from the C header, used as it is.
+    C(String),
+}
+
+impl SwiftType {
+    /// Name of the type in the Swift code.
+    pub fn name(&self) -> String {
+        match *self {
+            SwiftType::Void => "Void".to_string(),
+            SwiftType::Native(name) => name.to_string(),
+            SwiftType::Enum(ref name) | SwiftType::Struct(ref name) | SwiftType::C(ref name) => {
+                name.clone()
+            }
+        }
+    }
+
+    /// Convert a Swift expression of this type into the imported C type.
+    pub fn convert_to_c(&self, expr: &str) -> String {
+        match *self {
+            SwiftType::Enum(_) | SwiftType::Struct(_) => format!("{}.c", expr),
+            _ => expr.to_string(),
+        }
+    }
+
+    /// Whether the conversion from the imported C type may fail (e.g. for an enum value without a
+    /// matching case), in which case `convert_from_c` returns an optional.
+    pub fn is_fallible(&self) -> bool {
+        matches!(*self, SwiftType::Enum(_) | SwiftType::Struct(_))
+    }
+
+    /// Convert an expression of the imported C type into this type, or into an optional of this
+    /// type if the conversion is fallible.
+    pub fn convert_from_c(&self, expr: &str) -> String {
+        match *self {
+            SwiftType::Enum(ref name) | SwiftType::Struct(ref name) => {
+                format!("{}({})", name, expr)
+            }
+            _ => expr.to_string(),
+        }
+    }
+}
+
+/// Turn a Rust type into a Swift type.
+///
+/// Enums and structs declared in the crate are mapped to their Swift mirrors, while pointers and
+/// function pointers use the types imported from the C header.
+pub fn rust_to_swift(ty: &ast::Ty, context: &Context) -> Result<SwiftType, Error> {
+    match ty.node {
+        ast::TyKind::Path(None, ref path) => {
+            let name = path_name(path);
+            if let Some(native) = primitive(ty, &name)? {
+                return Ok(native);
+            }
+            if context.enums.contains(&name) {
+                Ok(SwiftType::Enum(name))
+            } else if context.structs.contains(&name) {
+                Ok(SwiftType::Struct(name))
+            } else {
+                Ok(SwiftType::C(format!("{}.{}", context.c_module, name)))
+            }
+        }
+        _ => Ok(SwiftType::C(rust_to_c_import(ty, context)?)),
+    }
+}
+
+/// Name of the Swift type imported from C for a Rust type.
+fn rust_to_c_import(ty: &ast::Ty, context: &Context) -> Result<String, Error> {
+    match ty.node {
+        ast::TyKind::Ptr(ref ptr) => {
+            let mutable = ptr.mutbl == ast::Mutability::Mutable;
+            if let ast::TyKind::Path(None, ref path) = ptr.ty.node {
+                let name = path_name(path);
+                if name == "c_void" {
+                    return Ok(if mutable {
+                        "UnsafeMutableRawPointer?".to_string()
+                    } else {
+                        "UnsafeRawPointer?".to_string()
+                    });
+                }
+                // Pointers to incomplete C types (opaque or declared elsewhere) are imported as
+                // `OpaquePointer`, whatever their mutability.
+                if primitive(&ptr.ty, &name)?.is_none()
+                    && !context.enums.contains(&name)
+                    && !context.structs.contains(&name)
+                {
+                    return Ok("OpaquePointer?".to_string());
+                }
+            }
+
+            let pointee = rust_to_c_import(&ptr.ty, context)?;
+            Ok(if mutable {
+                format!("UnsafeMutablePointer<{}>?", pointee)
+            } else {
+                format!("UnsafePointer<{}>?", pointee)
+            })
+        }
+        // C arrays are imported as tuples with an element per item.
+        ast::TyKind::Array(ref inner, ref len) => match common::eval_int(len) {
+            Some(len) if len > 0 => {
+                let elem = rust_to_c_import(inner, context)?;
+                Ok(format!("({})", vec![elem; len as usize].join(", ")))
+            }
+            _ => Err(unsupported(ty)),
+        },
+        ast::TyKind::BareFn(ref bare_fn) => {
+            let mut args = Vec::new();
+            for arg in &bare_fn.decl.inputs {
+                args.push(rust_to_c_import(&arg.ty, context)?);
+            }
+            let ret = match bare_fn.decl.output {
+                ast::FunctionRetTy::Default(..) => "Void".to_string(),
+                ast::FunctionRetTy::Ty(ref ty) => rust_to_c_import(ty, context)?,
+            };
+
+            Ok(format!("@convention(c) ({}) -> {}", args.join(", "), ret))
+        }
+        ast::TyKind::Path(None, ref path) => {
+            let name = path_name(path);
+            Ok(match primitive(ty, &name)? {
+                Some(native) => native.name(),
+                None => format!("{}.{}", context.c_module, name),
+            })
+        }
+        _ if pprust::ty_to_string(ty) == "()" => Ok("Void".to_string()),
+        _ => Err(unsupported(ty)),
+    }
+}
+
+/// Returns the last segment of a path, e.g. `c_int` for `libc::c_int`.
+fn path_name(path: &ast::Path) -> String {
+    path.segments
+        .last()
+        .map(|segment| segment.identifier.name.as_str().to_string())
+        .unwrap_or_default()
+}
+
+/// Convert a Rust primitive (or `libc`/`std::os::raw` type) into Swift.
+pub fn primitive_to_swift(ty: &str) -> Option<SwiftType> {
+    let native = match common::primitive_named(ty) {
+        Some(Primitive::Unit) => return Some(SwiftType::Void),
+        Some(Primitive::Bool) => "Bool",
+        Some(Primitive::Float { bits: 32 }) => "Float",
+        Some(Primitive::Float { .. }) => "Double",
+        Some(Primitive::Int { bits, signed }) => match (bits, signed) {
+            (Some(8), true) => "Int8",
+            (Some(16), true) => "Int16",
+            (Some(32), true) => "Int32",
+            (Some(64), true) => "Int64",
+            (None, true) => "Int",
+            (Some(8), false) => "UInt8",
+            (Some(16), false) => "UInt16",
+            (Some(32), false) => "UInt32",
+            (Some(64), false) => "UInt64",
+            (None, false) => "UInt",
+            // 128-bit integers have no C equivalent to be imported as.
+            _ => return None,
+        },
+        None => match ty {
+            "c_float" => "Float",
+            "c_double" => "Double",
+            "c_schar" => "Int8",
+            "c_short" => "Int16",
+            "c_int" => "Int32",
+            "c_longlong" => "Int64",
+            "c_long" => "Int",
+            "c_uchar" => "UInt8",
+            "c_ushort" => "UInt16",
+            "c_uint" => "UInt32",
+            "c_ulonglong" => "UInt64",
+            "c_ulong" => "UInt",
+            "c_char" => "CChar",
+            _ => return None,
+        },
+    };
+
+    Some(SwiftType::Native(native))
+}
+
+/// Convert the primitive named `name` into Swift, or return an error if it's a Rust primitive
+/// without a Swift equivalent (e.g. `i128`).
+fn primitive(ty: &ast::Ty, name: &str) -> Result<Option<SwiftType>, Error> {
+    match primitive_to_swift(name) {
+        Some(native) => Ok(Some(native)),
+        None if name == "i128" || name == "u128" || common::primitive_named(name).is_some() => {
+            Err(unsupported(ty))
+        }
+        None => Ok(None),
+    }
+}
+
+fn unsupported(ty: &ast::Ty) -> Error {
+    Error {
+        level: Level::Error,
+        code: Some(ErrorCode::UnsupportedType),
+        span: Some(ty.span),
+        message: format!(
+            "bindgen can not handle the type `{}` in Swift",
+            pprust::ty_to_string(ty)
+        ),
+    }
+}
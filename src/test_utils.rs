@@ -57,7 +57,7 @@ pub fn try_compile(
 
     let mut outputs = Outputs::default();
 
-    parse::parse_mod(&mut lang, &ast.module, &[Default::default()], &mut outputs)?;
+    parse::walk_crate(&ast, &[Default::default()], &mut lang, &mut outputs)?;
     lang.finalise_output(&mut outputs)?;
 
     Ok(outputs)